@@ -0,0 +1,232 @@
+use std::{iter::Peekable, str::Chars};
+
+use indexmap::IndexMap;
+use xelis_types::{Constant, Primitive, U256};
+
+// Parse a constant value written in the assembler syntax
+// Supported values:
+// - null
+// - bool true / bool false
+// - u8 10, u16 10, u32 10, u64 10, u128 10, u256 10
+// - string "hello world"
+// - bytes [1, 2, 3]
+// - range u64 0 10
+// - [u64 1, u64 2] for arrays
+// - {string "a": u64 1} for maps
+pub fn parse_constant(s: &str) -> Result<Constant, &'static str> {
+    let mut parser = ConstantParser {
+        chars: s.chars().peekable()
+    };
+
+    let constant = parser.read_constant()?;
+    parser.skip_whitespaces();
+    if parser.chars.next().is_some() {
+        return Err("Unexpected characters after constant");
+    }
+
+    Ok(constant)
+}
+
+struct ConstantParser<'a> {
+    chars: Peekable<Chars<'a>>
+}
+
+impl<'a> ConstantParser<'a> {
+    // Skip all the whitespaces until the next character
+    fn skip_whitespaces(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    // Check if the next character (ignoring whitespaces) is the expected one
+    // and consume it
+    fn next_is(&mut self, expected: char) -> bool {
+        self.skip_whitespaces();
+        self.chars.next_if_eq(&expected).is_some()
+    }
+
+    // Expect the next character (ignoring whitespaces)
+    fn expect(&mut self, expected: char, err: &'static str) -> Result<(), &'static str> {
+        if !self.next_is(expected) {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    // Read a word made of alphanumeric characters or underscores
+    fn read_word(&mut self) -> Result<String, &'static str> {
+        self.skip_whitespaces();
+        let mut word = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+            word.push(c);
+        }
+
+        if word.is_empty() {
+            return Err("Expected a word");
+        }
+
+        Ok(word)
+    }
+
+    // Read a quoted string, supporting basic escapes
+    fn read_string(&mut self) -> Result<String, &'static str> {
+        self.expect('"', "Expected a string")?;
+
+        let mut value = String::new();
+        loop {
+            match self.chars.next().ok_or("Unterminated string")? {
+                '"' => break,
+                '\\' => value.push(match self.chars.next().ok_or("Unterminated string")? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    '\\' => '\\',
+                    '"' => '"',
+                    _ => return Err("Invalid escape sequence")
+                }),
+                c => value.push(c)
+            }
+        }
+
+        Ok(value)
+    }
+
+    // Read a primitive number based on its type name
+    fn read_number(&mut self, ty: &str) -> Result<Primitive, &'static str> {
+        let word = self.read_word()?;
+        let value = word.replace('_', "");
+        Ok(match ty {
+            "u8" => Primitive::U8(value.parse().map_err(|_| "Invalid u8")?),
+            "u16" => Primitive::U16(value.parse().map_err(|_| "Invalid u16")?),
+            "u32" => Primitive::U32(value.parse().map_err(|_| "Invalid u32")?),
+            "u64" => Primitive::U64(value.parse().map_err(|_| "Invalid u64")?),
+            "u128" => Primitive::U128(value.parse().map_err(|_| "Invalid u128")?),
+            "u256" => Primitive::U256(U256::from_str_radix(&value, 10).map_err(|_| "Invalid u256")?),
+            _ => return Err("Invalid number type")
+        })
+    }
+
+    // Read a comma separated list until the closing character
+    fn read_list<T>(&mut self, close: char, mut f: impl FnMut(&mut Self) -> Result<T, &'static str>) -> Result<Vec<T>, &'static str> {
+        let mut values = Vec::new();
+        if self.next_is(close) {
+            return Ok(values);
+        }
+
+        loop {
+            values.push(f(self)?);
+            if self.next_is(close) {
+                break;
+            }
+
+            self.expect(',', "Expected ','")?;
+        }
+
+        Ok(values)
+    }
+
+    fn read_constant(&mut self) -> Result<Constant, &'static str> {
+        if self.next_is('[') {
+            let values = self.read_list(']', Self::read_constant)?;
+            return Ok(Constant::Array(values));
+        }
+
+        if self.next_is('{') {
+            let entries = self.read_list('}', |parser| {
+                let key = parser.read_constant()?;
+                if key.is_map() {
+                    return Err("Map cannot be used as a key");
+                }
+
+                parser.expect(':', "Expected ':'")?;
+                let value = parser.read_constant()?;
+                Ok((key, value))
+            })?;
+
+            let mut map = IndexMap::with_capacity(entries.len());
+            for (key, value) in entries {
+                if map.insert(key, value).is_some() {
+                    return Err("Duplicated map key");
+                }
+            }
+
+            return Ok(Constant::Map(map));
+        }
+
+        let ty = self.read_word()?;
+        Ok(match ty.as_str() {
+            "null" => Constant::Default(Primitive::Null),
+            "bool" => match self.read_word()?.as_str() {
+                "true" => Primitive::Boolean(true).into(),
+                "false" => Primitive::Boolean(false).into(),
+                _ => return Err("Invalid bool")
+            },
+            "string" => Primitive::String(self.read_string()?).into(),
+            "bytes" => {
+                self.expect('[', "Expected '['")?;
+                let values = self.read_list(']', |parser| {
+                    parser.read_word()?
+                        .parse::<u8>()
+                        .map_err(|_| "Invalid byte")
+                })?;
+
+                Constant::Bytes(values)
+            },
+            "range" => {
+                let ty = self.read_word()?;
+                let start = self.read_number(&ty)?;
+                let end = self.read_number(&ty)?;
+                Primitive::Range(Box::new((start, end))).into()
+            },
+            ty => self.read_number(ty)?.into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(parse_constant("null"), Ok(Primitive::Null.into()));
+        assert_eq!(parse_constant("bool true"), Ok(Primitive::Boolean(true).into()));
+        assert_eq!(parse_constant("u8 255"), Ok(Primitive::U8(255).into()));
+        assert_eq!(parse_constant("u64 1_000"), Ok(Primitive::U64(1000).into()));
+        assert_eq!(parse_constant("u256 10"), Ok(Primitive::U256(U256::from(10u64)).into()));
+        assert_eq!(parse_constant("string \"hi \\\"you\\\"\""), Ok(Primitive::String("hi \"you\"".to_owned()).into()));
+        assert_eq!(
+            parse_constant("range u32 0 10"),
+            Ok(Primitive::Range(Box::new((Primitive::U32(0), Primitive::U32(10)))).into())
+        );
+
+        assert!(parse_constant("u8 256").is_err());
+        assert!(parse_constant("u64 1 2").is_err());
+        assert!(parse_constant("string \"hi").is_err());
+    }
+
+    #[test]
+    fn test_structured() {
+        assert_eq!(
+            parse_constant("[u64 1, [u8 2], bytes [1, 2]]"),
+            Ok(Constant::Array(vec![
+                Primitive::U64(1).into(),
+                Constant::Array(vec![Primitive::U8(2).into()]),
+                Constant::Bytes(vec![1, 2])
+            ]))
+        );
+
+        let mut map = IndexMap::new();
+        map.insert(Primitive::String("a".to_owned()).into(), Primitive::U64(1).into());
+        map.insert(Primitive::String("b".to_owned()).into(), Constant::Array(Vec::new()));
+        assert_eq!(
+            parse_constant("{ string \"a\": u64 1, string \"b\": [] }"),
+            Ok(Constant::Map(map))
+        );
+
+        assert!(parse_constant("{ {}: u64 1 }").is_err());
+        assert!(parse_constant("{ u8 1: u64 1, u8 1: u64 2 }").is_err());
+        assert!(parse_constant("[u64 1").is_err());
+    }
+}
@@ -1,7 +1,9 @@
 mod opcode;
+mod constant;
 
 use log::{debug, trace};
 use opcode::OpCodeWithArgs;
+use constant::parse_constant;
 
 use thiserror::Error;
 use xelis_types::Constant;
@@ -13,6 +15,20 @@ pub enum AssemblerError {
     OpCode(&'static str),
     #[error("Expected a chunk")]
     ExpectedChunk,
+    #[error("Error on constant: {0}")]
+    Constant(&'static str),
+    #[error("Invalid directive: {0}")]
+    InvalidDirective(String),
+    #[error("Hook {0} is already registered")]
+    HookAlreadyRegistered(u8),
+}
+
+// Kind of chunk being assembled
+// It is used to register the chunk correctly in the module
+enum ChunkKind {
+    Declared,
+    Entry,
+    Hook(u8),
 }
 
 // Assembler to convert source code into bytecode
@@ -40,27 +56,76 @@ impl<'a> Assembler<'a> {
         self.module.add_constant(value)
     }
 
+    // Push a chunk into the module based on its kind
+    fn push_chunk(&mut self, chunk: Chunk, kind: ChunkKind) {
+        match kind {
+            ChunkKind::Declared => self.module.add_chunk(chunk),
+            ChunkKind::Entry => self.module.add_entry_chunk(chunk),
+            ChunkKind::Hook(id) => {
+                self.module.add_hook_chunk(id, chunk);
+            }
+        }
+    }
+
+    // Parse a chunk declaration line (without the '#')
+    // Supported forms:
+    // - #name
+    // - #entry name
+    // - #hook id name
+    fn parse_chunk_declaration(&self, line: &'a str) -> Result<(&'a str, ChunkKind), AssemblerError> {
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        match parts.as_slice() {
+            [name] => Ok((name, ChunkKind::Declared)),
+            ["entry", name] => Ok((name, ChunkKind::Entry)),
+            ["hook", id, name] => {
+                let id = id.parse::<u8>()
+                    .map_err(|_| AssemblerError::InvalidDirective(line.to_owned()))?;
+
+                if self.module.get_chunk_id_of_hook(id).is_some() {
+                    return Err(AssemblerError::HookAlreadyRegistered(id));
+                }
+
+                Ok((name, ChunkKind::Hook(id)))
+            },
+            _ => Err(AssemblerError::InvalidDirective(line.to_owned()))
+        }
+    }
+
     // Assemble the source code into bytecode
     pub fn assemble(mut self) -> Result<Module, AssemblerError> {
-        let mut chunk = None;
+        let mut chunk: Option<(Chunk, ChunkKind)> = None;
         for line in self.source.lines() {
             let line = line.trim();
             if line.is_empty() || line.starts_with("//") {
                 trace!("Ignoring line: {}", line);
                 // Ignore comments and empty lines
-            } else if line.starts_with("#") {
-                debug!("Creating new chunk: {}", &line[1..]);
+            } else if let Some(value) = line.strip_prefix(".const ") {
+                debug!("Registering constant: {}", value);
+                let constant = parse_constant(value)
+                    .map_err(AssemblerError::Constant)?;
+
+                self.add_constant(constant);
+            } else if let Some(declaration) = line.strip_prefix("#") {
+                let (name, kind) = self.parse_chunk_declaration(declaration)?;
+                // The pending chunk is not yet registered in the module, check it too
+                if let (Some((_, ChunkKind::Hook(previous))), ChunkKind::Hook(id)) = (&chunk, &kind) {
+                    if previous == id {
+                        return Err(AssemblerError::HookAlreadyRegistered(*id));
+                    }
+                }
+
+                debug!("Creating new chunk: {}", name);
                 // Push the previous chunk and create a new one
-                if let Some(chunk) = chunk.take() {
-                    self.module.add_chunk(chunk);
+                if let Some((chunk, kind)) = chunk.take() {
+                    self.push_chunk(chunk, kind);
                 }
 
-                chunk = Some(Chunk::new());
-                self.chunks_labels.push(&line[1..]);
+                chunk = Some((Chunk::new(), kind));
+                self.chunks_labels.push(name);
             } else if line.starts_with(":") {
                 debug!("Registering jump label: {}", &line[1..]);
                 // Register a jump label for the next instruction
-                let c = chunk.as_mut().ok_or(AssemblerError::ExpectedChunk)?;
+                let (c, _) = chunk.as_mut().ok_or(AssemblerError::ExpectedChunk)?;
                 self.jump_labels.push((&line[1..], c.index() as u32));
             } else {
                 debug!("Assembling line: {}", line);
@@ -68,12 +133,13 @@ impl<'a> Assembler<'a> {
                     .map_err(AssemblerError::OpCode)?;
 
                 trace!("Assembled: {:?}", op);
-                op.write_to_chunk(chunk.as_mut().ok_or(AssemblerError::ExpectedChunk)?);
+                let (c, _) = chunk.as_mut().ok_or(AssemblerError::ExpectedChunk)?;
+                op.write_to_chunk(c);
             }
         }
 
-        if let Some(chunk) = chunk.take() {
-            self.module.add_chunk(chunk);
+        if let Some((chunk, kind)) = chunk.take() {
+            self.push_chunk(chunk, kind);
         }
 
         Ok(self.module)
@@ -83,6 +149,7 @@ impl<'a> Assembler<'a> {
 #[cfg(test)]
 mod tests {
     use xelis_bytecode::OpCode;
+    use xelis_types::{Primitive, ValueCell};
     use super::*;
 
     #[test]
@@ -184,4 +251,122 @@ mod tests {
             ]
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_constants() {
+        let source = r#"
+            .const u64 10
+            .const string "hello"
+            .const [u8 1, u8 2]
+            .const { string "a": u64 1 }
+            .const u64 10
+
+            #main
+            CONSTANT 1
+        "#;
+
+        let assembler = Assembler::new(source);
+        let module = assembler.assemble().unwrap();
+
+        // Duplicated constants are merged
+        assert_eq!(module.constants().len(), 4);
+        assert_eq!(module.get_constant_at(0), Some(&Primitive::U64(10).into()));
+        assert_eq!(module.get_constant_at(1), Some(&Primitive::String("hello".to_owned()).into()));
+        assert_eq!(
+            module.get_constant_at(2),
+            Some(&ValueCell::Array(vec![Primitive::U8(1).into(), Primitive::U8(2).into()]))
+        );
+        assert!(module.get_constant_at(3).unwrap().is_map());
+    }
+
+    #[test]
+    fn test_invalid_constant() {
+        let source = r#"
+            .const u8 1000
+        "#;
+
+        let assembler = Assembler::new(source);
+        assert!(matches!(assembler.assemble(), Err(AssemblerError::Constant(_))));
+    }
+
+    #[test]
+    fn test_entry_and_hook() {
+        let source = r#"
+            #other
+            CONSTANT 0
+
+            #entry main
+            INVOKECHUNK #other false 0
+            RETURN
+
+            #hook 0 on_call
+            INVOKECHUNK #main false 0
+            RETURN
+        "#;
+
+        let assembler = Assembler::new(source);
+        let module = assembler.assemble().unwrap();
+
+        assert_eq!(module.chunks().len(), 3);
+        assert!(!module.is_entry_chunk(0));
+        assert!(module.is_entry_chunk(1));
+        assert!(!module.is_entry_chunk(2));
+        assert_eq!(module.get_chunk_id_of_hook(0), Some(2));
+        assert_eq!(
+            module.get_chunk_at(2).unwrap().get_instructions(),
+            &[OpCode::InvokeChunk.as_byte(), 1, 0, 0, 0, OpCode::Return.as_byte()]
+        );
+    }
+
+    #[test]
+    fn test_duplicated_hook() {
+        let source = r#"
+            #hook 1 first
+            RETURN
+
+            #hook 1 second
+            RETURN
+        "#;
+
+        let assembler = Assembler::new(source);
+        assert!(matches!(assembler.assemble(), Err(AssemblerError::HookAlreadyRegistered(1))));
+    }
+
+    #[test]
+    fn test_invalid_directive() {
+        let source = r#"
+            #hook main
+            RETURN
+        "#;
+
+        let assembler = Assembler::new(source);
+        assert!(matches!(assembler.assemble(), Err(AssemblerError::InvalidDirective(_))));
+    }
+
+    #[test]
+    fn test_assemble_superinstructions() {
+        let source = r#"
+            #main
+            :loop
+            MEMORYCONSTANTJUMPIFFALSE 0 1 LT :loop
+            MEMORYINCREMENT 0
+            MEMORYASSIGNCONSTANT 1 2 BITWISE_XOR
+            MEMORYOPERATOR 0 1 ADD
+        "#;
+
+        let assembler = Assembler::new(source);
+        let module = assembler.assemble().unwrap();
+
+        let chunk = module.get_chunk_at(0).unwrap();
+        assert_eq!(chunk.get_instructions(), &[
+            OpCode::MemoryConstantJumpIfFalse.as_byte(), 0, 0, 1, 0, OpCode::Lt.as_byte(), 0, 0, 0, 0,
+            OpCode::MemoryIncrement.as_byte(), 0, 0,
+            OpCode::MemoryAssignConstant.as_byte(), 1, 0, 2, 0, OpCode::BitwiseXor.as_byte(),
+            OpCode::MemoryOperator.as_byte(), 0, 0, 1, 0, OpCode::Add.as_byte(),
+        ]);
+
+        // Only binary operators can be fused
+        let assembler = Assembler::new("#main\nMEMORYOPERATOR 0 1 RETURN");
+        assert!(matches!(assembler.assemble(), Err(AssemblerError::OpCode("Invalid operator"))));
+    }
+}
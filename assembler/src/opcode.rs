@@ -179,6 +179,50 @@ pub enum OpCodeWithArgs {
     Inc,
     // --
     Dec,
+
+    // Superinstructions
+    // registers[left] op registers[right], push
+    MemoryOperator {
+        left_register: u16,
+        right_register: u16,
+        operator: OpCode
+    },
+    // registers[index] op constant, push
+    MemoryConstantOperator {
+        register_index: u16,
+        constant_index: u16,
+        operator: OpCode
+    },
+    // jump if registers[index] op constant is false
+    MemoryConstantJumpIfFalse {
+        register_index: u16,
+        constant_index: u16,
+        operator: OpCode,
+        addr: u32
+    },
+    // registers[index] op= constant
+    MemoryAssignConstant {
+        register_index: u16,
+        constant_index: u16,
+        operator: OpCode
+    },
+    // registers[index] += 1
+    MemoryIncrement {
+        register_index: u16
+    },
+}
+
+// Parse the operator of a superinstruction from its OpCode name
+fn parse_operator(s: &str) -> Result<OpCode, &'static str> {
+    let op = OpCodeWithArgs::from_str(s)
+        .map_err(|_| "Invalid operator")?
+        .as_opcode();
+
+    if !op.is_fusable_operator() {
+        return Err("Invalid operator");
+    }
+
+    Ok(op)
 }
 
 impl OpCodeWithArgs {
@@ -250,6 +294,12 @@ impl OpCodeWithArgs {
 
             OpCodeWithArgs::Inc => OpCode::Inc,
             OpCodeWithArgs::Dec => OpCode::Dec,
+
+            OpCodeWithArgs::MemoryOperator { .. } => OpCode::MemoryOperator,
+            OpCodeWithArgs::MemoryConstantOperator { .. } => OpCode::MemoryConstantOperator,
+            OpCodeWithArgs::MemoryConstantJumpIfFalse { .. } => OpCode::MemoryConstantJumpIfFalse,
+            OpCodeWithArgs::MemoryAssignConstant { .. } => OpCode::MemoryAssignConstant,
+            OpCodeWithArgs::MemoryIncrement { .. } => OpCode::MemoryIncrement,
         }
     }
 
@@ -284,6 +334,24 @@ impl OpCodeWithArgs {
             OpCodeWithArgs::IteratorNext { addr } => chunk.write_u32(*addr),
             OpCodeWithArgs::NewObject { length } => chunk.write_u8(*length),
             OpCodeWithArgs::NewMap { length } => chunk.write_u8(*length),
+            OpCodeWithArgs::MemoryOperator { left_register, right_register, operator } => {
+                chunk.write_u16(*left_register);
+                chunk.write_u16(*right_register);
+                chunk.write_u8(operator.as_byte());
+            },
+            OpCodeWithArgs::MemoryConstantOperator { register_index, constant_index, operator }
+            | OpCodeWithArgs::MemoryAssignConstant { register_index, constant_index, operator } => {
+                chunk.write_u16(*register_index);
+                chunk.write_u16(*constant_index);
+                chunk.write_u8(operator.as_byte());
+            },
+            OpCodeWithArgs::MemoryConstantJumpIfFalse { register_index, constant_index, operator, addr } => {
+                chunk.write_u16(*register_index);
+                chunk.write_u16(*constant_index);
+                chunk.write_u8(operator.as_byte());
+                chunk.write_u32(*addr);
+            },
+            OpCodeWithArgs::MemoryIncrement { register_index } => chunk.write_u16(*register_index),
             _ => {}
         }
     }
@@ -756,6 +824,67 @@ impl OpCodeWithArgs {
 
                 OpCodeWithArgs::Dec
             },
+            "MEMORYOPERATOR" => {
+                if args.len() != 3 {
+                    return Err("Invalid args count");
+                }
+
+                OpCodeWithArgs::MemoryOperator {
+                    left_register: args[0].parse().map_err(|_| "Invalid register index")?,
+                    right_register: args[1].parse().map_err(|_| "Invalid register index")?,
+                    operator: parse_operator(args[2])?
+                }
+            },
+            "MEMORYCONSTANTOPERATOR" => {
+                if args.len() != 3 {
+                    return Err("Invalid args count");
+                }
+
+                OpCodeWithArgs::MemoryConstantOperator {
+                    register_index: args[0].parse().map_err(|_| "Invalid register index")?,
+                    constant_index: args[1].parse().map_err(|_| "Invalid index")?,
+                    operator: parse_operator(args[2])?
+                }
+            },
+            "MEMORYCONSTANTJUMPIFFALSE" => {
+                if args.len() != 4 {
+                    return Err("Invalid args count");
+                }
+
+                let addr_arg = args[3];
+                let addr = if let Some(label) = addr_arg.strip_prefix(":") {
+                    goto.iter().find(|(l, _)| *l == label).map(|(_, a)| *a).ok_or("Invalid label")?
+                } else {
+                    addr_arg.parse().map_err(|_| "Invalid address")?
+                };
+
+                OpCodeWithArgs::MemoryConstantJumpIfFalse {
+                    register_index: args[0].parse().map_err(|_| "Invalid register index")?,
+                    constant_index: args[1].parse().map_err(|_| "Invalid index")?,
+                    operator: parse_operator(args[2])?,
+                    addr
+                }
+            },
+            "MEMORYASSIGNCONSTANT" => {
+                if args.len() != 3 {
+                    return Err("Invalid args count");
+                }
+
+                OpCodeWithArgs::MemoryAssignConstant {
+                    register_index: args[0].parse().map_err(|_| "Invalid register index")?,
+                    constant_index: args[1].parse().map_err(|_| "Invalid index")?,
+                    operator: parse_operator(args[2])?
+                }
+            },
+            "MEMORYINCREMENT" => {
+                if args.len() != 1 {
+                    return Err("Invalid args count");
+                }
+
+                OpCodeWithArgs::MemoryIncrement {
+                    register_index: args[0].parse().map_err(|_| "Invalid register index")?
+                }
+            },
             _ => return Err("Invalid OpCode")
         })
     }
@@ -25,7 +25,43 @@ pub enum Expression {
     IsNot(Box<Expression>), // !expr (where expr is a bool)
     Ternary(Box<Expression>, Box<Expression>, Box<Expression>), // bool expr, if true expr, else expr
     Cast(Box<Expression>, Type), // expr, type
-    ForceType(Box<Expression>, Type)
+    ForceType(Box<Expression>, Type),
+    Storage(StorageAccess), // storage_var.get(key)
+    Emit(EventEmission) // Event.emit(fields...)
+}
+
+// Operation available on a storage variable
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum StorageOperation {
+    Get,
+    Set,
+    Has,
+    Delete
+}
+
+// Access to a storage variable, lowered by the compiler to a system call
+// A map variable stores each entry under its own key,
+// so the key of the entry is the first parameter
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct StorageAccess {
+    // Index of the variable in the program storage
+    pub id: IdentifierType,
+    pub operation: StorageOperation,
+    // Native function called to access the storage
+    pub syscall: IdentifierType,
+    // Entry key for maps, then the value to set
+    pub parameters: Vec<Expression>
+}
+
+// Emission of a declared event, lowered by the compiler to a system call
+// The fields are given in their declaration order
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct EventEmission {
+    // Index of the event in the program events
+    pub id: IdentifierType,
+    // Native function called to emit the event
+    pub syscall: IdentifierType,
+    pub fields: Vec<Expression>
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -40,6 +76,8 @@ pub enum Statement {
     Break,
     Continue,
     Variable(DeclarationStatement),
+    // Source line of the next statements, only used for the debug info
+    Line(usize),
 }
 
 #[derive(Debug, Hash, Eq, PartialEq)]
@@ -53,4 +91,18 @@ pub struct DeclarationStatement {
 pub struct ConstantDeclaration {
     pub value: Constant,
     pub value_type: Type,
-}
\ No newline at end of file
+}
+
+// Variable persisted in the storage, declared at module scope
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct StorageDeclaration {
+    pub name: String,
+    pub value_type: Type,
+}
+
+// Event that can be emitted by the program, declared at module scope
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct EventDeclaration {
+    pub name: String,
+    pub fields: Vec<(String, Type)>,
+}
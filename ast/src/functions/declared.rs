@@ -1,6 +1,6 @@
 use xelis_types::{Type, IdentifierType};
 use crate::Statement;
-use super::Parameter;
+use super::{FunctionNames, Parameter};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct DeclaredFunction {
@@ -59,21 +59,30 @@ impl DeclaredFunction {
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct EntryFunction {
+    names: FunctionNames,
     parameters: Vec<Parameter>,
     statements: Vec<Statement>,
+    return_type: Option<Type>,
     variables_count: u16,
 }
 
 impl EntryFunction {
     // Create a new entry function
-    pub fn new(parameters: Vec<Parameter>, statements: Vec<Statement>, variables_count: u16) -> Self {
+    pub fn new(names: FunctionNames, parameters: Vec<Parameter>, statements: Vec<Statement>, return_type: Option<Type>, variables_count: u16) -> Self {
         EntryFunction {
+            names,
             parameters,
             statements,
+            return_type,
             variables_count
         }
     }
 
+    // Get the names of the function and its parameters
+    pub fn get_names(&self) -> &FunctionNames {
+        &self.names
+    }
+
     // Get the parameters of the function
     pub fn get_parameters(&self) -> &Vec<Parameter> {
         &self.parameters
@@ -84,6 +93,11 @@ impl EntryFunction {
         &self.statements
     }
 
+    // Get the type returned by the function
+    pub fn get_return_type(&self) -> &Option<Type> {
+        &self.return_type
+    }
+
     // Get the variables count of the function
     pub fn get_variables_count(&self) -> u16 {
         self.variables_count
@@ -1,9 +1,10 @@
 use xelis_types::Type;
 use crate::Statement;
-use super::Parameter;
+use super::{FunctionNames, Parameter};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct HookFunction {
+    names: FunctionNames,
     parameters: Vec<Parameter>,
     statements: Vec<Statement>,
     return_type: Option<Type>,
@@ -12,8 +13,9 @@ pub struct HookFunction {
 }
 
 impl HookFunction {
-    pub fn new(parameters: Vec<Parameter>, statements: Vec<Statement>, return_type: Option<Type>, variables_count: u16, hook_id: u8) -> Self {
+    pub fn new(names: FunctionNames, parameters: Vec<Parameter>, statements: Vec<Statement>, return_type: Option<Type>, variables_count: u16, hook_id: u8) -> Self {
         Self {
+            names,
             parameters,
             statements,
             return_type,
@@ -22,6 +24,10 @@ impl HookFunction {
         }
     }
 
+    pub fn get_names(&self) -> &FunctionNames {
+        &self.names
+    }
+
     pub fn get_statements(&self) -> &Vec<Statement> {
         &self.statements
     }
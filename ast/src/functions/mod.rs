@@ -46,6 +46,37 @@ impl Parameter {
     }
 }
 
+// Names of a function and its parameters
+// Kept on entries and hooks to describe them in the ABI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionNames {
+    name: String,
+    parameters: Vec<String>
+}
+
+impl FunctionNames {
+    // Create the names of a function
+    // Parameters names must be in the same order as the parameters
+    pub fn new(name: impl Into<String>, parameters: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            parameters
+        }
+    }
+
+    // Get the name of the function
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Get the names of the parameters
+    #[inline(always)]
+    pub fn parameters(&self) -> &[String] {
+        &self.parameters
+    }
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct Signature<'a> {
     name: Cow<'a, str>,
@@ -132,7 +163,7 @@ impl FunctionType {
         match &self {
             FunctionType::Declared(f) => f.get_return_type(),
             FunctionType::Hook(h) => h.get_return_type(),
-            FunctionType::Entry(_) => &Some(ENTRY_FN_RETURN_TYPE)
+            FunctionType::Entry(e) => e.get_return_type()
         }
     }
 
@@ -1,6 +1,6 @@
 use indexmap::IndexSet;
 use xelis_types::{EnumType, StructType};
-use crate::ConstantDeclaration;
+use crate::{ConstantDeclaration, EventDeclaration, StorageDeclaration};
 
 use super::FunctionType;
 
@@ -13,7 +13,11 @@ pub struct Program {
     // All enums declared
     enums: IndexSet<EnumType>,
     // All functions declared
-    functions: Vec<FunctionType>
+    functions: Vec<FunctionType>,
+    // All storage variables declared
+    storage: Vec<StorageDeclaration>,
+    // All events declared
+    events: Vec<EventDeclaration>
 }
 
 impl Program {
@@ -23,7 +27,9 @@ impl Program {
             constants: IndexSet::new(),
             structures: IndexSet::new(),
             enums: IndexSet::new(),
-            functions: Vec::new()
+            functions: Vec::new(),
+            storage: Vec::new(),
+            events: Vec::new()
         }
     }
 
@@ -33,7 +39,9 @@ impl Program {
             constants,
             structures,
             enums,
-            functions
+            functions,
+            storage: Vec::new(),
+            events: Vec::new()
         }
     }
 
@@ -84,4 +92,30 @@ impl Program {
     pub fn functions(&self) -> &[FunctionType] {
         &self.functions
     }
+
+    // Add a storage variable to the program
+    #[inline]
+    pub fn add_storage(&mut self, storage: StorageDeclaration) {
+        self.storage.push(storage);
+    }
+
+    // Get the storage variables declared in the program
+    // Their index is the id used to access them
+    #[inline]
+    pub fn storage(&self) -> &[StorageDeclaration] {
+        &self.storage
+    }
+
+    // Add an event to the program
+    #[inline]
+    pub fn add_event(&mut self, event: EventDeclaration) {
+        self.events.push(event);
+    }
+
+    // Get the events declared in the program
+    // Their index is the id of the emitted events
+    #[inline]
+    pub fn events(&self) -> &[EventDeclaration] {
+        &self.events
+    }
 }
\ No newline at end of file
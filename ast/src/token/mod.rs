@@ -111,6 +111,8 @@ impl<'a> fmt::Display for Token<'a> {
           Entry => "entry",
           Function => "fn",
           Hook => "hook",
+          Storage => "storage",
+          Event => "event",
           Return => "return",
           If => "if",
           Else => "else",
@@ -186,6 +188,8 @@ pub enum Token<'a> {
     Entry,
     Function,
     Hook,
+    Storage,
+    Event,
     Dot,
     Comma,
     Colon,
@@ -320,6 +324,8 @@ impl Token<'_> {
             "entry" => Entry,
             "fn" => Function,
             "hook" => Hook,
+            "storage" => Storage,
+            "event" => Event,
 
             "return" => Return,
             "if" => If,
@@ -1,9 +1,9 @@
 pub mod xstd;
 
-use std::{any::TypeId, borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap};
 use xelis_ast::Signature;
 use xelis_types::{Constant, EnumType, OpaqueType, Opaque, StructType, Type};
-use xelis_environment::{Environment, NativeFunction, OnCallFn};
+use xelis_environment::{Environment, NativeFunction, OnCall, OnCallFn, OnReentrantCallFn};
 use crate::{
     ConstFnCall,
     ConstFunction,
@@ -49,17 +49,24 @@ impl<'a> EnvironmentBuilder<'a> {
         }
     }
 
-    fn register_function_internal(&mut self, name: &'a str, on_type: Option<Type>, require_instance: bool, parameters: Vec<(&'a str, Type)>, on_call: OnCallFn, cost: u64, return_type: Option<Type>) {
+    fn register_function_internal(&mut self, name: &'a str, on_type: Option<Type>, require_instance: bool, parameters: Vec<(&'a str, Type)>, on_call: OnCall, cost: u64, return_type: Option<Type>) {
         let params: Vec<_> = parameters.iter().map(|(_, t)| t.clone()).collect();
         let _ = self.functions_mapper.register(name, on_type.clone(), require_instance, parameters, return_type.clone()).unwrap();
-        self.env.add_function(NativeFunction::new(require_instance, params, on_call, cost, return_type));
+        self.env.add_function(NativeFunction::with_on_call(require_instance, params, on_call, cost, return_type));
     }
 
     // Register a native function
     // Panic if the function signature is already registered
     pub fn register_native_function(&mut self, name: &'a str, for_type: Option<Type>, parameters: Vec<(&'a str, Type)>, on_call: OnCallFn, cost: u64, return_type: Option<Type>) {
         let instance = for_type.is_some();
-        self.register_function_internal(name, for_type, instance, parameters, on_call, cost, return_type);
+        self.register_function_internal(name, for_type, instance, parameters, OnCall::Simple(on_call), cost, return_type);
+    }
+
+    // Register a native function able to invoke chunks of the module executed
+    // Panic if the function signature is already registered
+    pub fn register_reentrant_function(&mut self, name: &'a str, for_type: Option<Type>, parameters: Vec<(&'a str, Type)>, on_call: OnReentrantCallFn, cost: u64, return_type: Option<Type>) {
+        let instance = for_type.is_some();
+        self.register_function_internal(name, for_type, instance, parameters, OnCall::Reentrant(on_call), cost, return_type);
     }
 
     // Register a native static function
@@ -67,7 +74,7 @@ impl<'a> EnvironmentBuilder<'a> {
     // Example: u64::from_be_bytes
     // Panic if the function signature is already registered
     pub fn register_static_function(&mut self, name: &'a str, for_type: Type, parameters: Vec<(&'a str, Type)>, on_call: OnCallFn, cost: u64, return_type: Option<Type>) {
-        self.register_function_internal(name, Some(for_type), false, parameters, on_call, cost, return_type);
+        self.register_function_internal(name, Some(for_type), false, parameters, OnCall::Simple(on_call), cost, return_type);
     }
 
     // Register a constant function
@@ -104,11 +111,12 @@ impl<'a> EnvironmentBuilder<'a> {
     }
 
     // Register an opaque type in the environment
+    // The name is the one used in the source code,
+    // values are always encoded using the type name of the opaque
     // Panic if the opaque name is already used
     pub fn register_opaque<T: Opaque>(&mut self, name: &'a str) -> OpaqueType {
-        let ty = TypeId::of::<T>();
         let opaque = self.opaque_manager.build(name).unwrap();
-        self.env.add_opaque(ty);
+        self.env.add_opaque::<T>();
         opaque
     }
 
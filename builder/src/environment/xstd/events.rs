@@ -0,0 +1,46 @@
+use xelis_environment::{
+    ChunkInvoker,
+    Context,
+    EnvironmentError,
+    Event,
+    EventLog,
+    FnInstance,
+    FnParams,
+    FnReturnType,
+};
+use xelis_types::{serializer::Serializer, Type};
+
+use crate::EnvironmentBuilder;
+
+// Gas charged per byte of event data emitted
+pub const EVENT_COST_PER_BYTE: u64 = 2;
+
+pub fn register(env: &mut EnvironmentBuilder) {
+    env.register_reentrant_function("emit", None, vec![("id", Type::U16), ("data", Type::Any)], emit, 50, None);
+    // Used by the declared events, `Name.emit(...)`
+    env.register_reentrant_function("emit_event", None, vec![("id", Type::U16), ("data", Type::Any)], emit_event, 50, None);
+}
+
+// Emit an event tagged with the module executing
+// Raw events are kept apart so their id never collides with a declared one
+fn emit_internal(mut parameters: FnParams, invoker: &mut dyn ChunkInvoker, context: &mut Context, raw: bool) -> FnReturnType {
+    let data = parameters.remove(1).into_owned()?;
+    let id = parameters.remove(0).as_ref()?.as_u16()?;
+
+    context.increase_gas_usage(data.to_bytes().len() as u64 * EVENT_COST_PER_BYTE)?;
+
+    let module = invoker.module_hash()?;
+    context.get_mut::<EventLog>()
+        .ok_or(EnvironmentError::EventLogUnavailable)?
+        .emit(Event { module, id, raw, data });
+
+    Ok(None)
+}
+
+fn emit<'ty, 'r>(_: FnInstance, parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    emit_internal(parameters, invoker, context, true)
+}
+
+fn emit_event<'ty, 'r>(_: FnInstance, parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    emit_internal(parameters, invoker, context, false)
+}
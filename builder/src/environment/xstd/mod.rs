@@ -5,6 +5,8 @@ mod integer;
 mod range;
 mod map;
 mod bytes;
+mod storage;
+mod events;
 
 use std::ptr;
 
@@ -15,9 +17,13 @@ use xelis_environment::{
     FnParams,
     FnReturnType,
     Context,
+    ChunkInvoker,
 };
 use super::EnvironmentBuilder;
 
+pub use storage::{STORAGE_READ_COST_PER_BYTE, STORAGE_WRITE_COST_PER_BYTE};
+pub use events::EVENT_COST_PER_BYTE;
+
 pub fn register(env: &mut EnvironmentBuilder) {
     array::register(env);
     bytes::register(env);
@@ -33,6 +39,10 @@ pub fn register(env: &mut EnvironmentBuilder) {
     env.register_native_function("assert", None, vec![("value", Type::Bool)], assert, 1, None);
     env.register_native_function("is_same_ptr", None, vec![("left", Type::Any), ("right", Type::Any)], is_same_ptr, 5, Some(Type::Bool));
     env.register_native_function("require", None, vec![("condition", Type::Bool), ("msg", Type::String)], require, 1, None);
+    env.register_reentrant_function("call_module", None, vec![("module", Type::Any), ("entry", Type::U16), ("args", Type::Array(Box::new(Type::Any)))], call_module, 100, Some(Type::Any));
+
+    storage::register(env);
+    events::register(env);
 }
 
 fn println(_: FnInstance, parameters: FnParams, _: &mut Context) -> FnReturnType {
@@ -92,4 +102,18 @@ fn require(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturn
     } else {
         Err(EnvironmentError::Expect(msg))
     }
-}
\ No newline at end of file
+}
+
+// Call an entry of another module resolved by the host
+// Arguments and the value returned are verified against the entry in the callee ABI
+// Any error of the callee is propagated to the caller
+fn call_module<'ty, 'r>(_: FnInstance, mut parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    let args = parameters.remove(2)
+        .into_owned()?
+        .to_vec()?;
+    let entry = parameters[1].as_ref()?.as_u16()?;
+    let module = parameters.remove(0).into_owned()?;
+
+    let value = invoker.invoke_module(&module, entry, args, context)?;
+    Ok(Some(value.unwrap_or_default()))
+}
@@ -0,0 +1,124 @@
+use xelis_environment::{
+    ChunkInvoker,
+    Context,
+    Environment,
+    EnvironmentError,
+    FnInstance,
+    FnParams,
+    FnReturnType,
+    StorageHandle,
+};
+use xelis_types::{serializer::Serializer, Primitive, Type, ValueCell};
+
+use crate::EnvironmentBuilder;
+
+// Gas charged per byte read from the storage
+pub const STORAGE_READ_COST_PER_BYTE: u64 = 1;
+// Gas charged per byte written in the storage
+pub const STORAGE_WRITE_COST_PER_BYTE: u64 = 5;
+
+// The functions are reentrant to know the module executing,
+// each module has its own keys in the storage
+pub fn register(env: &mut EnvironmentBuilder) {
+    env.register_reentrant_function("storage_load", None, vec![("key", Type::Any)], storage_load, 50, Some(Type::Optional(Box::new(Type::Any))));
+    // Used by the storage variables, `name.get(...)`
+    env.register_reentrant_function("storage_load_typed", None, vec![("key", Type::Any), ("type", Type::Bytes)], storage_load_typed, 50, Some(Type::Optional(Box::new(Type::Any))));
+    env.register_reentrant_function("storage_store", None, vec![("key", Type::Any), ("value", Type::Any)], storage_store, 100, None);
+    env.register_reentrant_function("storage_has", None, vec![("key", Type::Any)], storage_has, 25, Some(Type::Bool));
+    env.register_reentrant_function("storage_delete", None, vec![("key", Type::Any)], storage_delete, 50, Some(Type::Bool));
+}
+
+// Get the storage inserted in the context by the host
+// Writes must go through the handle so they are journaled
+fn get_storage<'a, 'ty>(context: &'a mut Context<'ty, '_>) -> Result<&'a mut StorageHandle<'ty>, EnvironmentError> {
+    context.get_mut::<StorageHandle>()
+        .ok_or(EnvironmentError::StorageUnavailable)
+}
+
+// Size of the value once serialized
+fn size_of(value: &ValueCell) -> u64 {
+    value.to_bytes().len() as u64
+}
+
+// Read the key parameter, maps can't be used as keys
+fn read_key(parameters: &mut FnParams) -> Result<ValueCell, EnvironmentError> {
+    let key = parameters.remove(0).into_owned()?;
+    if key.is_map() {
+        return Err(EnvironmentError::InvalidKeyType);
+    }
+
+    Ok(key)
+}
+
+// Load the value stored under the key by the module executing
+fn load(key: &ValueCell, invoker: &mut dyn ChunkInvoker, context: &mut Context) -> Result<Option<ValueCell>, EnvironmentError> {
+    let module = invoker.module_hash()?;
+    let value = get_storage(context)?.storage().load(&module, key)?;
+
+    let size = size_of(key) + value.as_ref().map_or(0, size_of);
+    context.increase_gas_usage(size * STORAGE_READ_COST_PER_BYTE)?;
+
+    Ok(value)
+}
+
+fn storage_load<'ty, 'r>(_: FnInstance, mut parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    let key = read_key(&mut parameters)?;
+    let value = load(&key, invoker, context)?;
+
+    Ok(Some(value.unwrap_or_else(|| Primitive::Null.into())))
+}
+
+// The value is verified against the encoded type
+// as the key may have been written with another type
+fn storage_load_typed<'ty, 'r>(_: FnInstance, mut parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    let ty = Type::from_bytes(parameters.remove(1).as_ref()?.as_bytes()?)
+        .map_err(|_| EnvironmentError::InvalidType)?;
+    let key = read_key(&mut parameters)?;
+
+    let Some(value) = load(&key, invoker, context)? else {
+        return Ok(Some(Primitive::Null.into()));
+    };
+
+    // Without the environment, no opaque can be verified
+    let valid = match context.get::<Environment>() {
+        Some(environment) => environment.is_value_of_type(&value, &ty),
+        None => value.is_of_type(&ty, &|_, _| false)
+    };
+
+    if !valid {
+        return Err(EnvironmentError::InvalidStorageValue);
+    }
+
+    Ok(Some(value))
+}
+
+fn storage_store<'ty, 'r>(_: FnInstance, mut parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    let value = parameters.remove(1).into_owned()?;
+    let key = read_key(&mut parameters)?;
+    let module = invoker.module_hash()?;
+
+    // Charged before writing so nothing is stored without enough gas
+    let size = size_of(&key) + size_of(&value);
+    context.increase_gas_usage(size * STORAGE_WRITE_COST_PER_BYTE)?;
+
+    get_storage(context)?.store(&module, key, value)?;
+    Ok(None)
+}
+
+fn storage_has<'ty, 'r>(_: FnInstance, mut parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    let key = read_key(&mut parameters)?;
+    context.increase_gas_usage(size_of(&key) * STORAGE_READ_COST_PER_BYTE)?;
+
+    let module = invoker.module_hash()?;
+    let has = get_storage(context)?.storage().has(&module, &key)?;
+    Ok(Some(Primitive::Boolean(has).into()))
+}
+
+fn storage_delete<'ty, 'r>(_: FnInstance, mut parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> FnReturnType {
+    let key = read_key(&mut parameters)?;
+    context.increase_gas_usage(size_of(&key) * STORAGE_WRITE_COST_PER_BYTE)?;
+
+    let module = invoker.module_hash()?;
+    let deleted = get_storage(context)?.delete(&module, &key)?;
+    Ok(Some(Primitive::Boolean(deleted).into()))
+}
@@ -1,4 +1,4 @@
-use xelis_types::{EnumType, EnumVariant, IdentifierType, Type};
+use xelis_types::{EnumNames, EnumType, EnumVariant, EnumVariantNames, IdentifierType, Type};
 use super::{Builder, BuilderType, TypeManager};
 
 #[derive(Debug)]
@@ -72,7 +72,7 @@ impl<'a> Builder<'a> for EnumBuilder<'a> {
 }
 
 impl<'a> BuilderType<EnumVariantBuilder<'a>> for EnumTypeBuilder<'a> {
-    fn with(id: IdentifierType, variants: Vec<EnumVariantBuilder<'a>>) -> Self {
+    fn with(id: IdentifierType, name: &str, names: &[&str], variants: Vec<EnumVariantBuilder<'a>>) -> Self {
         let types = variants.iter()
             .map(|v| EnumVariant::new(v.iter()
                 .map(|(_, t)| t.clone())
@@ -80,8 +80,16 @@ impl<'a> BuilderType<EnumVariantBuilder<'a>> for EnumTypeBuilder<'a> {
             ).clone())
             .collect();
 
+        let variants_names = names.iter()
+            .zip(variants.iter())
+            .map(|(name, v)| EnumVariantNames::new(*name, v.iter()
+                .map(|(field, _)| field.to_string())
+                .collect()
+            ))
+            .collect();
+
         Self {
-            inner: EnumType::new(id, types),
+            inner: EnumType::with_names(id, types, EnumNames::new(name, variants_names)),
             variants
         }
     }
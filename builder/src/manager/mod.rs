@@ -14,7 +14,8 @@ use crate::{
 };
 
 pub trait BuilderType<D> {
-    fn with(id: IdentifierType, data: Vec<D>) -> Self;
+    // Names are attached to the final type for display purposes
+    fn with(id: IdentifierType, name: &str, names: &[&str], data: Vec<D>) -> Self;
 
     fn type_id(&self) -> IdentifierType;
 }
@@ -78,8 +79,8 @@ impl<'a, T: Builder<'a>> TypeManager<'a, T> {
         }
 
         let (fields_names, fields_types) = split_vec(fields);
-        let id = self.mapper.register(name)?;
-        let inner = T::BuilderType::with(id, fields_types);
+        let id = self.mapper.register(name.clone())?;
+        let inner = T::BuilderType::with(id, &name, &fields_names, fields_types);
 
         Ok(T::new(
             inner,
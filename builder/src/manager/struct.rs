@@ -1,4 +1,4 @@
-use xelis_types::{IdentifierType, StructType, Type};
+use xelis_types::{IdentifierType, StructNames, StructType, Type};
 
 use super::{Builder, BuilderType, TypeManager};
 
@@ -44,8 +44,9 @@ impl<'a> Builder<'a> for StructBuilder<'a> {
 }
 
 impl BuilderType<Type> for StructType {
-    fn with(id: IdentifierType, fields: Vec<Type>) -> Self {
-        StructType::new(id, fields)
+    fn with(id: IdentifierType, name: &str, names: &[&str], fields: Vec<Type>) -> Self {
+        let names = StructNames::new(name, names.iter().map(|name| name.to_string()).collect());
+        StructType::with_names(id, fields, names)
     }
 
     fn type_id(&self) -> IdentifierType {
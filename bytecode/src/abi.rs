@@ -0,0 +1,491 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use xelis_types::{
+    opaque::OpaqueRegistry,
+    IdentifierType,
+    Primitive,
+    Type,
+    ValueCell,
+    U256
+};
+
+#[derive(Debug, Error)]
+pub enum AbiError {
+    #[error("unknown entry '{0}'")]
+    UnknownEntry(String),
+    #[error("unknown storage '{0}'")]
+    UnknownStorage(String),
+    #[error("unknown event {0}")]
+    UnknownEvent(u16),
+    #[error("unknown struct {0}")]
+    UnknownStruct(IdentifierType),
+    #[error("unknown enum {0}")]
+    UnknownEnum(IdentifierType),
+    #[error("unknown variant '{0}'")]
+    UnknownVariant(String),
+    #[error("ambiguous variant for enum {0}")]
+    AmbiguousVariant(IdentifierType),
+    #[error("unknown field '{0}'")]
+    UnknownField(String),
+    #[error("missing field '{0}'")]
+    MissingField(String),
+    #[error("invalid arguments count: expected {0}, got {1}")]
+    InvalidArgumentsCount(usize, usize),
+    #[error("expected a value of type {0}")]
+    ExpectedType(Type),
+    #[error("type {0} is not supported")]
+    UnsupportedType(Type),
+    #[error("opaque type {0} not registered")]
+    UnknownOpaque(IdentifierType),
+    #[error(transparent)]
+    Opaque(#[from] anyhow::Error)
+}
+
+// Named value with its type
+// Used for parameters, struct fields and enum variant fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub value_type: Type
+}
+
+// Entry function callable by the host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiEntry {
+    pub name: String,
+    pub chunk_id: u16,
+    pub parameters: Vec<AbiField>,
+    pub return_type: Option<Type>
+}
+
+// Hook implemented by the program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiHook {
+    pub name: String,
+    pub hook_id: u8,
+    pub chunk_id: u16,
+    pub parameters: Vec<AbiField>,
+    pub return_type: Option<Type>
+}
+
+// Structure declared by the program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiStruct {
+    pub id: IdentifierType,
+    pub name: String,
+    pub fields: Vec<AbiField>
+}
+
+// Variant of an enum with its fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiEnumVariant {
+    pub name: String,
+    pub fields: Vec<AbiField>
+}
+
+// Enum declared by the program
+// Variants are ordered by their id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiEnum {
+    pub id: IdentifierType,
+    pub name: String,
+    pub variants: Vec<AbiEnumVariant>
+}
+
+// Event that can be emitted by the program
+// Its fields are emitted in order as an array
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiEvent {
+    pub id: u16,
+    pub name: String,
+    pub fields: Vec<AbiField>
+}
+
+// Human readable description of a compiled program
+// It links the names used in the source code to the ids in the Module
+// so it can be used by dApps and wallets to encode calls
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Abi {
+    pub entries: Vec<AbiEntry>,
+    pub hooks: Vec<AbiHook>,
+    pub structs: Vec<AbiStruct>,
+    pub enums: Vec<AbiEnum>,
+    // Variables persisted in the storage
+    #[serde(default)]
+    pub storage: Vec<AbiField>,
+    #[serde(default)]
+    pub events: Vec<AbiEvent>
+}
+
+// Read a number that may be written as a JSON number or as a decimal string
+fn read_number<T: TryFrom<u64> + std::str::FromStr>(value: &Value, ty: &Type) -> Result<T, AbiError> {
+    match value {
+        Value::Number(n) => n.as_u64()
+            .and_then(|n| T::try_from(n).ok()),
+        Value::String(s) => s.parse().ok(),
+        _ => None
+    }.ok_or_else(|| AbiError::ExpectedType(ty.clone()))
+}
+
+impl Abi {
+    // Find an entry by its name
+    pub fn get_entry(&self, name: &str) -> Option<&AbiEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    // Find an entry by its chunk id
+    pub fn get_entry_by_chunk_id(&self, chunk_id: u16) -> Option<&AbiEntry> {
+        self.entries.iter().find(|entry| entry.chunk_id == chunk_id)
+    }
+
+    // Find a hook by its name
+    pub fn get_hook(&self, name: &str) -> Option<&AbiHook> {
+        self.hooks.iter().find(|hook| hook.name == name)
+    }
+
+    // Find a struct by its id
+    pub fn get_struct(&self, id: IdentifierType) -> Option<&AbiStruct> {
+        self.structs.iter().find(|s| s.id == id)
+    }
+
+    // Find an enum by its id
+    pub fn get_enum(&self, id: IdentifierType) -> Option<&AbiEnum> {
+        self.enums.iter().find(|e| e.id == id)
+    }
+
+    // Find an event by its id
+    pub fn get_event(&self, id: u16) -> Option<&AbiEvent> {
+        self.events.iter().find(|e| e.id == id)
+    }
+
+    // Find a storage variable by its name
+    pub fn get_storage(&self, name: &str) -> Option<&AbiField> {
+        self.storage.iter().find(|field| field.name == name)
+    }
+
+    // Build the key under which a storage variable is stored
+    // A variable is stored under its name, and each entry
+    // of a map under its name followed by the JSON key of the entry
+    // The key is scoped by the hash of the module in the storage
+    pub fn storage_key(&self, name: &str, key: Option<&Value>, registry: Option<&OpaqueRegistry>) -> Result<ValueCell, AbiError> {
+        let field = self.get_storage(name)
+            .ok_or_else(|| AbiError::UnknownStorage(name.to_owned()))?;
+
+        let name = ValueCell::from(Primitive::String(field.name.clone()));
+        match (&field.value_type, key) {
+            (Type::Map(key_type, _), Some(key)) => {
+                let key = self.value_from_json(key_type, key, registry)?;
+                Ok(ValueCell::Array(vec![name, key]))
+            },
+            (Type::Map(key_type, _), None) => Err(AbiError::ExpectedType(key_type.as_ref().clone())),
+            (_, None) => Ok(name),
+            (_, Some(_)) => Err(AbiError::InvalidArgumentsCount(0, 1))
+        }
+    }
+
+    // Convert the JSON arguments of an entry to values
+    // Returns the chunk id of the entry with its arguments
+    pub fn entry_arguments(&self, name: &str, arguments: &[Value], registry: Option<&OpaqueRegistry>) -> Result<(u16, Vec<ValueCell>), AbiError> {
+        let entry = self.get_entry(name)
+            .ok_or_else(|| AbiError::UnknownEntry(name.to_owned()))?;
+
+        if entry.parameters.len() != arguments.len() {
+            return Err(AbiError::InvalidArgumentsCount(entry.parameters.len(), arguments.len()));
+        }
+
+        let values = entry.parameters.iter()
+            .zip(arguments)
+            .map(|(param, value)| self.value_from_json(&param.value_type, value, registry))
+            .collect::<Result<_, _>>()?;
+
+        Ok((entry.chunk_id, values))
+    }
+
+    // Convert a JSON value to a value of the expected type
+    // - numbers up to u64 are JSON numbers, u128 and u256 may also be decimal strings
+    // - bytes are an array of numbers
+    // - optional is null or the inner value
+    // - range is an array of two numbers
+    // - map is an object if the key is a string, otherwise an array of [key, value]
+    // - struct is an object with all its fields
+    // - enum is an object with the variant name and an object of its fields
+    // - opaque is the JSON value of the opaque type
+    pub fn value_from_json(&self, ty: &Type, value: &Value, registry: Option<&OpaqueRegistry>) -> Result<ValueCell, AbiError> {
+        let expected = || AbiError::ExpectedType(ty.clone());
+        Ok(match ty {
+            Type::U8 => Primitive::U8(read_number(value, ty)?).into(),
+            Type::U16 => Primitive::U16(read_number(value, ty)?).into(),
+            Type::U32 => Primitive::U32(read_number(value, ty)?).into(),
+            Type::U64 => Primitive::U64(read_number(value, ty)?).into(),
+            Type::U128 => Primitive::U128(read_number(value, ty)?).into(),
+            Type::U256 => match value {
+                Value::Number(n) => Primitive::U256(U256::from(n.as_u64().ok_or_else(expected)?)).into(),
+                Value::String(s) if !s.is_empty() => Primitive::U256(U256::from_str_radix(s, 10).map_err(|_| expected())?).into(),
+                _ => return Err(expected())
+            },
+            Type::String => Primitive::String(value.as_str().ok_or_else(expected)?.to_owned()).into(),
+            Type::Bool => Primitive::Boolean(value.as_bool().ok_or_else(expected)?).into(),
+            Type::Bytes => ValueCell::Bytes(
+                value.as_array()
+                    .ok_or_else(expected)?
+                    .iter()
+                    .map(|v| read_number(v, &Type::U8))
+                    .collect::<Result<_, _>>()?
+            ),
+            Type::Array(inner) => ValueCell::Array(
+                value.as_array()
+                    .ok_or_else(expected)?
+                    .iter()
+                    .map(|v| self.value_from_json(inner, v, registry))
+                    .collect::<Result<_, _>>()?
+            ),
+            Type::Optional(inner) => match value {
+                Value::Null => Primitive::Null.into(),
+                v => self.value_from_json(inner, v, registry)?
+            },
+            Type::Range(inner) => {
+                let [start, end] = value.as_array()
+                    .and_then(|values| <&[Value; 2]>::try_from(values.as_slice()).ok())
+                    .ok_or_else(expected)?;
+
+                let start = self.value_from_json(inner, start, registry)?.into_value().map_err(|_| expected())?;
+                let end = self.value_from_json(inner, end, registry)?.into_value().map_err(|_| expected())?;
+                Primitive::Range(Box::new((start, end))).into()
+            },
+            Type::Map(key, inner) => {
+                let mut map = IndexMap::new();
+                match (key.as_ref(), value) {
+                    (Type::String, Value::Object(entries)) => for (k, v) in entries {
+                        map.insert(Primitive::String(k.clone()).into(), self.value_from_json(inner, v, registry)?);
+                    },
+                    (_, Value::Array(entries)) => for entry in entries {
+                        let [k, v] = entry.as_array()
+                            .and_then(|values| <&[Value; 2]>::try_from(values.as_slice()).ok())
+                            .ok_or_else(expected)?;
+
+                        map.insert(self.value_from_json(key, k, registry)?, self.value_from_json(inner, v, registry)?);
+                    },
+                    _ => return Err(expected())
+                };
+
+                ValueCell::Map(map)
+            },
+            Type::Struct(struct_type) => {
+                let abi = self.get_struct(struct_type.id())
+                    .ok_or(AbiError::UnknownStruct(struct_type.id()))?;
+                let object = value.as_object().ok_or_else(expected)?;
+                ValueCell::Array(self.fields_from_json(&abi.fields, object, registry)?)
+            },
+            Type::Enum(enum_type) => {
+                let abi = self.get_enum(enum_type.id())
+                    .ok_or(AbiError::UnknownEnum(enum_type.id()))?;
+                let object = value.as_object().ok_or_else(expected)?;
+                let name = object.get("variant")
+                    .and_then(Value::as_str)
+                    .ok_or_else(expected)?;
+                let variant = abi.variants.iter()
+                    .find(|v| v.name == name)
+                    .ok_or_else(|| AbiError::UnknownVariant(name.to_owned()))?;
+
+                let empty = Map::new();
+                let fields = match object.get("fields") {
+                    Some(fields) => fields.as_object().ok_or_else(expected)?,
+                    None => &empty
+                };
+
+                ValueCell::Array(self.fields_from_json(&variant.fields, fields, registry)?)
+            },
+            Type::Opaque(opaque_type) => {
+                let deserializer = registry.and_then(|r| r.get_by_type(opaque_type))
+                    .ok_or(AbiError::UnknownOpaque(opaque_type.id()))?;
+                Primitive::Opaque(deserializer.from_json(value.clone())?).into()
+            },
+            Type::Any | Type::T(_) => return Err(AbiError::UnsupportedType(ty.clone()))
+        })
+    }
+
+    // Convert the named JSON fields to values ordered like the declaration
+    fn fields_from_json(&self, fields: &[AbiField], object: &Map<String, Value>, registry: Option<&OpaqueRegistry>) -> Result<Vec<ValueCell>, AbiError> {
+        if let Some(name) = object.keys().find(|k| !fields.iter().any(|f| f.name == **k)) {
+            return Err(AbiError::UnknownField(name.clone()));
+        }
+
+        fields.iter()
+            .map(|field| {
+                let value = object.get(&field.name)
+                    .ok_or_else(|| AbiError::MissingField(field.name.clone()))?;
+                self.value_from_json(&field.value_type, value, registry)
+            })
+            .collect()
+    }
+
+    // Convert a value of the expected type to JSON
+    // This is the reverse of `value_from_json`, u128 and u256 are written as strings
+    // Enum values don't carry their variant id, so the variant is found using its fields count
+    pub fn value_to_json(&self, ty: &Type, value: &ValueCell) -> Result<Value, AbiError> {
+        let expected = || AbiError::ExpectedType(ty.clone());
+        Ok(match (ty, value) {
+            (Type::U8, ValueCell::Default(Primitive::U8(v))) => Value::from(*v),
+            (Type::U16, ValueCell::Default(Primitive::U16(v))) => Value::from(*v),
+            (Type::U32, ValueCell::Default(Primitive::U32(v))) => Value::from(*v),
+            (Type::U64, ValueCell::Default(Primitive::U64(v))) => Value::from(*v),
+            (Type::U128, ValueCell::Default(Primitive::U128(v))) => Value::String(v.to_string()),
+            (Type::U256, ValueCell::Default(Primitive::U256(v))) => Value::String(v.to_string()),
+            (Type::String, ValueCell::Default(Primitive::String(v))) => Value::String(v.clone()),
+            (Type::Bool, ValueCell::Default(Primitive::Boolean(v))) => Value::Bool(*v),
+            (Type::Bytes, ValueCell::Bytes(bytes)) => Value::Array(bytes.iter().map(|b| Value::from(*b)).collect()),
+            (Type::Array(inner), ValueCell::Array(values)) => Value::Array(
+                values.iter()
+                    .map(|v| self.value_to_json(inner, v))
+                    .collect::<Result<_, _>>()?
+            ),
+            (Type::Optional(_), ValueCell::Default(Primitive::Null)) => Value::Null,
+            (Type::Optional(inner), v) => self.value_to_json(inner, v)?,
+            (Type::Range(inner), ValueCell::Default(Primitive::Range(range))) => Value::Array(vec![
+                self.value_to_json(inner, &ValueCell::Default(range.0.clone()))?,
+                self.value_to_json(inner, &ValueCell::Default(range.1.clone()))?
+            ]),
+            (Type::Map(key, inner), ValueCell::Map(map)) => match key.as_ref() {
+                Type::String => Value::Object(
+                    map.iter()
+                        .map(|(k, v)| Ok((k.as_string().map_err(|_| expected())?.to_owned(), self.value_to_json(inner, v)?)))
+                        .collect::<Result<_, AbiError>>()?
+                ),
+                _ => Value::Array(
+                    map.iter()
+                        .map(|(k, v)| Ok(Value::Array(vec![self.value_to_json(key, k)?, self.value_to_json(inner, v)?])))
+                        .collect::<Result<_, AbiError>>()?
+                )
+            },
+            (Type::Struct(struct_type), ValueCell::Array(values)) => {
+                let abi = self.get_struct(struct_type.id())
+                    .ok_or(AbiError::UnknownStruct(struct_type.id()))?;
+                Value::Object(self.fields_to_json(&abi.fields, values).ok_or_else(expected)??)
+            },
+            (Type::Enum(enum_type), ValueCell::Array(values)) => {
+                let abi = self.get_enum(enum_type.id())
+                    .ok_or(AbiError::UnknownEnum(enum_type.id()))?;
+
+                let mut candidates = abi.variants.iter().filter(|v| v.fields.len() == values.len());
+                let variant = candidates.next().ok_or_else(expected)?;
+                if candidates.next().is_some() {
+                    return Err(AbiError::AmbiguousVariant(enum_type.id()));
+                }
+
+                let mut object = Map::new();
+                object.insert("variant".to_owned(), Value::String(variant.name.clone()));
+                object.insert("fields".to_owned(), Value::Object(self.fields_to_json(&variant.fields, values).ok_or_else(expected)??));
+                Value::Object(object)
+            },
+            (Type::Opaque(_), ValueCell::Default(Primitive::Opaque(opaque))) => opaque.inner().serialize_json()?,
+            (Type::Any | Type::T(_), _) => return Err(AbiError::UnsupportedType(ty.clone())),
+            _ => return Err(expected())
+        })
+    }
+
+    // Convert the data of an emitted event to JSON
+    // The event name is written with its named fields
+    pub fn event_to_json(&self, id: u16, data: &ValueCell) -> Result<Value, AbiError> {
+        let abi = self.get_event(id)
+            .ok_or(AbiError::UnknownEvent(id))?;
+        let ValueCell::Array(values) = data else {
+            return Err(AbiError::ExpectedType(Type::Array(Box::new(Type::Any))));
+        };
+        let fields = self.fields_to_json(&abi.fields, values)
+            .ok_or(AbiError::InvalidArgumentsCount(abi.fields.len(), values.len()))??;
+
+        let mut object = Map::new();
+        object.insert("event".to_owned(), Value::String(abi.name.clone()));
+        object.insert("fields".to_owned(), Value::Object(fields));
+        Ok(Value::Object(object))
+    }
+
+    // Convert values ordered like the declaration to named JSON fields
+    // Returns None if the count of values doesn't match the fields
+    fn fields_to_json(&self, fields: &[AbiField], values: &[ValueCell]) -> Option<Result<Map<String, Value>, AbiError>> {
+        if fields.len() != values.len() {
+            return None;
+        }
+
+        Some(fields.iter()
+            .zip(values)
+            .map(|(field, value)| Ok((field.name.clone(), self.value_to_json(&field.value_type, value)?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use xelis_types::{EnumType, EnumVariant};
+
+    use super::*;
+
+    #[track_caller]
+    fn roundtrip(abi: &Abi, ty: Type, value: Value) {
+        let cell = abi.value_from_json(&ty, &value, None).unwrap();
+        assert_eq!(abi.value_to_json(&ty, &cell).unwrap(), value);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let abi = Abi::default();
+        roundtrip(&abi, Type::U8, json!(255));
+        roundtrip(&abi, Type::U128, json!("340282366920938463463374607431768211455"));
+        roundtrip(&abi, Type::U256, json!("1000"));
+        roundtrip(&abi, Type::Bytes, json!([1, 2, 3]));
+        roundtrip(&abi, Type::Optional(Box::new(Type::String)), json!(null));
+        roundtrip(&abi, Type::Optional(Box::new(Type::String)), json!("hello"));
+        roundtrip(&abi, Type::Range(Box::new(Type::U32)), json!([0, 10]));
+        roundtrip(&abi, Type::Array(Box::new(Type::Bool)), json!([true, false]));
+        roundtrip(&abi, Type::Map(Box::new(Type::String), Box::new(Type::U64)), json!({ "a": 1 }));
+        roundtrip(&abi, Type::Map(Box::new(Type::U8), Box::new(Type::U64)), json!([[1, 2]]));
+
+        assert!(abi.value_from_json(&Type::U8, &json!(256), None).is_err());
+        assert!(abi.value_from_json(&Type::Range(Box::new(Type::U8)), &json!([1]), None).is_err());
+        assert!(abi.value_from_json(&Type::Any, &json!(1), None).is_err());
+    }
+
+    #[test]
+    fn test_json_enum() {
+        let enum_type = EnumType::new(0, vec![
+            EnumVariant::new(Vec::new()),
+            EnumVariant::new(vec![Type::U64]),
+            EnumVariant::new(vec![Type::String])
+        ]);
+
+        let field = |name: &str, value_type: Type| AbiField { name: name.to_owned(), value_type };
+        let abi = Abi {
+            enums: vec![AbiEnum {
+                id: 0,
+                name: "Action".to_owned(),
+                variants: vec![
+                    AbiEnumVariant { name: "Reset".to_owned(), fields: Vec::new() },
+                    AbiEnumVariant { name: "Add".to_owned(), fields: vec![field("amount", Type::U64)] },
+                    AbiEnumVariant { name: "Rename".to_owned(), fields: vec![field("name", Type::String)] }
+                ]
+            }],
+            ..Default::default()
+        };
+
+        let ty = Type::Enum(enum_type);
+        roundtrip(&abi, ty.clone(), json!({ "variant": "Reset", "fields": {} }));
+
+        let value = abi.value_from_json(&ty, &json!({ "variant": "Add", "fields": { "amount": 1 } }), None).unwrap();
+        assert_eq!(value, ValueCell::Array(vec![Primitive::U64(1).into()]));
+        // Both Add and Rename have a single field
+        assert!(matches!(abi.value_to_json(&ty, &value), Err(AbiError::AmbiguousVariant(0))));
+
+        assert!(matches!(
+            abi.value_from_json(&ty, &json!({ "variant": "Remove" }), None),
+            Err(AbiError::UnknownVariant(_))
+        ));
+        assert!(matches!(
+            abi.value_from_json(&ty, &json!({ "variant": "Add", "fields": { "amount": 1, "other": 2 } }), None),
+            Err(AbiError::UnknownField(_))
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+// Debug information of a module
+// It maps the instructions offsets of each chunk to a source line
+// This is never required to execute a module and is provided by the toolchain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugInfo {
+    // Chunk id => (offset, line) sorted by offset
+    lines: IndexMap<usize, Vec<(usize, usize)>>
+}
+
+impl DebugInfo {
+    // Create an empty debug info
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Register the source line of the instruction at offset
+    // All the next instructions of the chunk are mapped to it
+    // until another line is registered
+    pub fn add_line(&mut self, chunk_id: usize, offset: usize, line: usize) {
+        let lines = self.lines.entry(chunk_id).or_default();
+        match lines.binary_search_by_key(&offset, |(offset, _)| *offset) {
+            Ok(index) => lines[index].1 = line,
+            Err(index) => lines.insert(index, (offset, line))
+        };
+    }
+
+    // Get the source line of the instruction at offset
+    pub fn get_line(&self, chunk_id: usize, offset: usize) -> Option<usize> {
+        let lines = self.lines.get(&chunk_id)?;
+        let index = lines.partition_point(|(start, _)| *start <= offset);
+        index.checked_sub(1).map(|index| lines[index].1)
+    }
+
+    // Get the first instruction of every sequence mapped to the line
+    // Returns a list of (chunk id, offset)
+    pub fn get_offsets_of_line(&self, line: usize) -> Vec<(usize, usize)> {
+        self.lines.iter()
+            .flat_map(|(chunk_id, lines)| lines.iter()
+                .filter(move |(_, l)| *l == line)
+                .map(move |(offset, _)| (*chunk_id, *offset))
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let mut info = DebugInfo::new();
+        info.add_line(0, 0, 1);
+        info.add_line(0, 6, 3);
+        info.add_line(0, 3, 2);
+        info.add_line(1, 0, 3);
+
+        assert_eq!(info.get_line(0, 0), Some(1));
+        assert_eq!(info.get_line(0, 4), Some(2));
+        assert_eq!(info.get_line(0, 100), Some(3));
+        assert_eq!(info.get_line(2, 0), None);
+        assert_eq!(info.get_offsets_of_line(3), vec![(0, 6), (1, 0)]);
+    }
+}
@@ -1,7 +1,13 @@
 mod chunk;
 mod opcode;
 mod module;
+mod serializer;
+mod abi;
+mod debug;
 
 pub use chunk::Chunk;
 pub use opcode::OpCode;
-pub use module::Module;
\ No newline at end of file
+pub use module::Module;
+pub use serializer::*;
+pub use abi::*;
+pub use debug::DebugInfo;
\ No newline at end of file
@@ -1,6 +1,6 @@
 use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use xelis_types::ValueCell;
+use xelis_types::{Type, ValueCell};
 
 use super::Chunk;
 
@@ -20,6 +20,10 @@ pub struct Module {
     // Hook id => chunk id
     #[serde(default)]
     hook_chunk_ids: IndexMap<u8, usize>,
+    // Chunk id => parameters types
+    // Only set for the entries and hooks as they are invoked by the host
+    #[serde(default)]
+    chunks_signatures: IndexMap<usize, Vec<Type>>,
 }
 
 impl Module {
@@ -29,7 +33,8 @@ impl Module {
             constants: IndexSet::new(),
             chunks: Vec::new(),
             entry_chunk_ids: IndexSet::new(),
-            hook_chunk_ids: IndexMap::new()
+            hook_chunk_ids: IndexMap::new(),
+            chunks_signatures: IndexMap::new()
         }
     }
 
@@ -38,13 +43,15 @@ impl Module {
         constants: IndexSet<ValueCell>,
         chunks: Vec<Chunk>,
         entry_chunk_ids: IndexSet<usize>,
-        hook_chunk_ids: IndexMap<u8, usize>
+        hook_chunk_ids: IndexMap<u8, usize>,
+        chunks_signatures: IndexMap<usize, Vec<Type>>
     ) -> Self {
         Self {
             constants,
             chunks,
             entry_chunk_ids,
             hook_chunk_ids,
+            chunks_signatures
         }
     }
 
@@ -129,6 +136,24 @@ impl Module {
         self.chunks.push(chunk);
         self.hook_chunk_ids.insert(id, index)
     }
+
+    // Get the parameters types of all the chunks having a signature
+    pub fn chunks_signatures(&self) -> &IndexMap<usize, Vec<Type>> {
+        &self.chunks_signatures
+    }
+
+    // Get the parameters types expected by a chunk
+    #[inline]
+    pub fn get_chunk_signature(&self, index: usize) -> Option<&Vec<Type>> {
+        self.chunks_signatures.get(&index)
+    }
+
+    // Set the parameters types expected by a chunk
+    // Used by the VM to verify the arguments given by the host
+    #[inline]
+    pub fn set_chunk_signature(&mut self, index: usize, parameters: Vec<Type>) -> Option<Vec<Type>> {
+        self.chunks_signatures.insert(index, parameters)
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum OpCode {
     // load constant
@@ -141,6 +141,26 @@ pub enum OpCode {
     Inc,
     // --
     Dec,
+
+    // Superinstructions
+    // Each one replaces a sequence emitted often by the compiler
+    // The operator byte is the opcode of the binary operator applied
+
+    // read registers a and b, apply operator, push
+    // MemoryLoad a; MemoryLoad b; Operator
+    MemoryOperator,
+    // read register, read constant, apply operator, push
+    // MemoryLoad; Constant; Operator
+    MemoryConstantOperator,
+    // read register, read constant, apply comparison, jump if false
+    // MemoryLoad; Constant; Operator; JumpIfFalse
+    MemoryConstantJumpIfFalse,
+    // read register, read constant, apply operator in place
+    // MemoryLoad; Constant; AssignOperator
+    MemoryAssignConstant,
+    // increment the register by one
+    // MemoryLoad; Constant 1; AssignAdd
+    MemoryIncrement,
 }
 
 impl OpCode {
@@ -165,7 +185,7 @@ impl OpCode {
             1 => OpCode::MemoryLoad,
             2 => OpCode::MemorySet,
             3 => OpCode::MemoryPop,
-            4 => OpCode::MemoryPop,
+            4 => OpCode::MemoryLen,
             5 => OpCode::MemoryToOwned,
 
             6 => OpCode::SubLoad,
@@ -202,14 +222,15 @@ impl OpCode {
             32 => OpCode::Mod,
             33 => OpCode::Pow,
 
-            34 => OpCode::BitwiseAnd,
-            35 => OpCode::BitwiseOr,
-            36 => OpCode::BitwiseXor,
-            37 => OpCode::BitwiseShl,
-            38 => OpCode::BitwiseShr,
+            34 => OpCode::And,
+            35 => OpCode::Or,
+
+            36 => OpCode::BitwiseAnd,
+            37 => OpCode::BitwiseOr,
+            38 => OpCode::BitwiseXor,
+            39 => OpCode::BitwiseShl,
+            40 => OpCode::BitwiseShr,
 
-            39 => OpCode::And,
-            40 => OpCode::Or,
             41 => OpCode::Eq,
             42 => OpCode::Neg,
             43 => OpCode::Gt,
@@ -233,6 +254,12 @@ impl OpCode {
 
             59 => OpCode::Inc,
             60 => OpCode::Dec,
+
+            61 => OpCode::MemoryOperator,
+            62 => OpCode::MemoryConstantOperator,
+            63 => OpCode::MemoryConstantJumpIfFalse,
+            64 => OpCode::MemoryAssignConstant,
+            65 => OpCode::MemoryIncrement,
             _ => return None,
         })
     }
@@ -257,6 +284,35 @@ impl OpCode {
         })
     }
 
+    // Check if the OpCode is a binary operator that can be fused
+    // in a superinstruction
+    #[inline]
+    pub const fn is_fusable_operator(&self) -> bool {
+        matches!(self,
+            OpCode::Add
+            | OpCode::Sub
+            | OpCode::Mul
+            | OpCode::Div
+            | OpCode::Mod
+            | OpCode::BitwiseAnd
+            | OpCode::BitwiseOr
+            | OpCode::BitwiseXor
+            | OpCode::BitwiseShl
+            | OpCode::BitwiseShr
+            | OpCode::Eq
+            | OpCode::Gt
+            | OpCode::Lt
+            | OpCode::Gte
+            | OpCode::Lte
+        )
+    }
+
+    // Check if the OpCode is a comparison operator
+    #[inline]
+    pub const fn is_comparison(&self) -> bool {
+        matches!(self, OpCode::Eq | OpCode::Gt | OpCode::Lt | OpCode::Gte | OpCode::Lte)
+    }
+
     // Get how many arguments bytes (overhead) the OpCode takes
     #[inline]
     pub fn arguments_bytes(&self) -> usize {
@@ -264,6 +320,7 @@ impl OpCode {
             OpCode::Constant => 2, // u16 id
             OpCode::MemoryLoad => 2, // u16 id
             OpCode::MemorySet => 2, // u16 id
+            OpCode::MemoryToOwned => 2, // u16 id
             OpCode::SubLoad => 1, // u8 id
 
             OpCode::PopN => 1, // u8 count
@@ -281,7 +338,47 @@ impl OpCode {
             OpCode::NewObject => 1, // u8 initial values
             OpCode::NewMap => 1, // u8 initial values
 
+            OpCode::MemoryOperator => 5, // u16 register, u16 register, u8 operator
+            OpCode::MemoryConstantOperator => 5, // u16 register, u16 constant, u8 operator
+            OpCode::MemoryConstantJumpIfFalse => 9, // u16 register, u16 constant, u8 operator, u32 addr
+            OpCode::MemoryAssignConstant => 5, // u16 register, u16 constant, u8 operator
+            OpCode::MemoryIncrement => 2, // u16 register
+
             _ => 0,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The bytes 34 to 40 used to be decoded in another order than the one
+    // of the enum, so And and Or were executed as bitwise operators
+    #[test]
+    fn test_logical_and_bitwise_bytes() {
+        let expected = [
+            OpCode::And,
+            OpCode::Or,
+            OpCode::BitwiseAnd,
+            OpCode::BitwiseOr,
+            OpCode::BitwiseXor,
+            OpCode::BitwiseShl,
+            OpCode::BitwiseShr
+        ];
+
+        for (byte, op) in (34..=40).zip(expected) {
+            assert_eq!(OpCode::from_byte(byte), Some(op));
+            assert_eq!(op.as_byte(), byte);
+        }
+    }
+
+    #[test]
+    fn test_from_byte_matches_as_byte() {
+        for byte in 0..=u8::MAX {
+            if let Some(op) = OpCode::from_byte(byte) {
+                assert_eq!(op.as_byte(), byte, "{:?}", op);
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use indexmap::{IndexMap, IndexSet};
+use xelis_types::{
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    OpaqueRegistry,
+    Type,
+    ValueCell
+};
+
+use super::{Chunk, Module};
+
+// Magic header written at the start of every encoded module
+pub const MODULE_MAGIC: [u8; 4] = *b"XVMB";
+
+// Current version of the binary format
+pub const MODULE_FORMAT_VERSION: u8 = 2;
+
+// Default maximum size in bytes of an encoded module
+pub const MODULE_MAX_SIZE: usize = 1024 * 1024;
+
+impl Serializer for Module {
+    // Layout:
+    // - magic header and format version
+    // - constants: varint count, each value
+    // - chunks: varint count, each varint length and instructions
+    // - entries: varint count, each chunk id as varint in ascending order
+    // - hooks: varint count, each hook id and chunk id as varint in ascending hook id order
+    // - signatures: varint count, each chunk id as varint in ascending order and its parameters types
+    fn write(&self, writer: &mut Writer) {
+        writer.write_bytes(&MODULE_MAGIC);
+        writer.write_u8(MODULE_FORMAT_VERSION);
+
+        writer.write_varint(self.constants().len() as u64);
+        for constant in self.constants() {
+            constant.write(writer);
+        }
+
+        writer.write_varint(self.chunks().len() as u64);
+        for chunk in self.chunks() {
+            writer.write_sized_bytes(chunk.get_instructions());
+        }
+
+        let mut entries = self.chunks_entry_ids().iter().collect::<Vec<_>>();
+        entries.sort();
+        writer.write_varint(entries.len() as u64);
+        for id in entries {
+            writer.write_varint(*id as u64);
+        }
+
+        let mut hooks = self.hook_chunk_ids().iter().collect::<Vec<_>>();
+        hooks.sort_by_key(|(hook_id, _)| **hook_id);
+        writer.write_varint(hooks.len() as u64);
+        for (hook_id, chunk_id) in hooks {
+            writer.write_u8(*hook_id);
+            writer.write_varint(*chunk_id as u64);
+        }
+
+        let mut signatures = self.chunks_signatures().iter().collect::<Vec<_>>();
+        signatures.sort_by_key(|(chunk_id, _)| **chunk_id);
+        writer.write_varint(signatures.len() as u64);
+        for (chunk_id, parameters) in signatures {
+            writer.write_varint(*chunk_id as u64);
+            writer.write_varint(parameters.len() as u64);
+            for parameter in parameters {
+                parameter.write(writer);
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        if reader.read_array()? != MODULE_MAGIC {
+            return Err(ReaderError::InvalidMagic);
+        }
+
+        let version = reader.read_u8()?;
+        if version != MODULE_FORMAT_VERSION {
+            return Err(ReaderError::UnsupportedVersion(version));
+        }
+
+        let len = reader.read_len()?;
+        let mut constants = IndexSet::with_capacity(len);
+        for _ in 0..len {
+            // Constants are deduplicated by the module
+            // a duplicate would shift the indexes
+            if !constants.insert(ValueCell::read(reader)?) {
+                return Err(ReaderError::DuplicatedEntry);
+            }
+        }
+
+        let len = reader.read_len()?;
+        let mut chunks = Vec::with_capacity(len);
+        for _ in 0..len {
+            let instructions = reader.read_sized_bytes()?;
+            chunks.push(Chunk::from_instructions(instructions.to_vec()));
+        }
+
+        // Entries and hooks must be strictly ascending
+        // so a module has only one valid encoding
+        let len = reader.read_len()?;
+        let mut entry_chunk_ids = IndexSet::with_capacity(len);
+        for _ in 0..len {
+            let id = reader.read_index()?;
+            if entry_chunk_ids.last().is_some_and(|last| *last >= id) {
+                return Err(ReaderError::InvalidValue);
+            }
+            entry_chunk_ids.insert(id);
+        }
+
+        let len = reader.read_len()?;
+        let mut hook_chunk_ids = IndexMap::with_capacity(len);
+        for _ in 0..len {
+            let hook_id = reader.read_u8()?;
+            let chunk_id = reader.read_index()?;
+            if hook_chunk_ids.last().is_some_and(|(last, _)| *last >= hook_id) {
+                return Err(ReaderError::InvalidValue);
+            }
+            hook_chunk_ids.insert(hook_id, chunk_id);
+        }
+
+        let len = reader.read_len()?;
+        let mut chunks_signatures = IndexMap::with_capacity(len);
+        for _ in 0..len {
+            let chunk_id = reader.read_index()?;
+            if chunks_signatures.last().is_some_and(|(last, _)| *last >= chunk_id) {
+                return Err(ReaderError::InvalidValue);
+            }
+
+            let count = reader.read_len()?;
+            let mut parameters = Vec::with_capacity(count);
+            for _ in 0..count {
+                parameters.push(Type::read(reader)?);
+            }
+            chunks_signatures.insert(chunk_id, parameters);
+        }
+
+        Ok(Module::with(constants, chunks, entry_chunk_ids, hook_chunk_ids, chunks_signatures))
+    }
+
+    // The input is rejected if it exceeds MODULE_MAX_SIZE
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ReaderError> {
+        let mut reader = Reader::with_limit(bytes, MODULE_MAX_SIZE)?;
+        let module = Self::read(&mut reader)?;
+        reader.expect_end()?;
+        Ok(module)
+    }
+}
+
+impl Module {
+    // Decode a module from its binary format
+    // The input is rejected if it exceeds the given size
+    // Opaque constants are decoded using the registry, usually the environment one
+    pub fn from_bytes_with_limit(bytes: &[u8], max_size: usize, registry: &OpaqueRegistry) -> Result<Self, ReaderError> {
+        let mut reader = Reader::with_limit(bytes, max_size)?
+            .with_opaque_registry(registry);
+        let module = Self::read(&mut reader)?;
+        reader.expect_end()?;
+        Ok(module)
+    }
+
+    // Canonical content hash of the module
+    // This is the blake3 hash of its binary encoding
+    pub fn hash(&self) -> [u8; 32] {
+        blake3::hash(&self.to_bytes()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_types::Primitive;
+    use crate::OpCode;
+    use super::*;
+
+    fn build_module() -> Module {
+        let mut module = Module::new();
+        module.add_constant(Primitive::U64(10));
+        module.add_constant(Primitive::String("hello".to_owned()));
+
+        let mut chunk = Chunk::new();
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::Return);
+        module.add_chunk(chunk.clone());
+        module.add_entry_chunk(chunk.clone());
+        module.add_hook_chunk(3, chunk.clone());
+        module.add_hook_chunk(1, chunk);
+        module.set_chunk_signature(1, vec![Type::U64, Type::Array(Box::new(Type::String))]);
+        module.set_chunk_signature(2, Vec::new());
+
+        module
+    }
+
+    #[test]
+    fn test_module_roundtrip() {
+        let module = build_module();
+        let bytes = module.to_bytes();
+        assert_eq!(&bytes[..4], &MODULE_MAGIC);
+
+        let decoded = Module::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.constants(), module.constants());
+        assert_eq!(decoded.chunks().len(), 4);
+        assert!(decoded.is_entry_chunk(1));
+        assert_eq!(decoded.get_chunk_id_of_hook(3), Some(2));
+        assert_eq!(decoded.get_chunk_id_of_hook(1), Some(3));
+        assert_eq!(decoded.chunks_signatures(), module.chunks_signatures());
+
+        // Encoding is canonical
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.hash(), module.hash());
+    }
+
+    #[test]
+    fn test_module_invalid() {
+        let bytes = build_module().to_bytes();
+
+        let mut invalid = bytes.clone();
+        invalid[0] = 0;
+        assert_eq!(Module::from_bytes(&invalid).unwrap_err(), ReaderError::InvalidMagic);
+
+        let mut invalid = bytes.clone();
+        invalid[4] = MODULE_FORMAT_VERSION + 1;
+        assert_eq!(Module::from_bytes(&invalid).unwrap_err(), ReaderError::UnsupportedVersion(MODULE_FORMAT_VERSION + 1));
+
+        let mut invalid = bytes.clone();
+        invalid.push(0);
+        assert_eq!(Module::from_bytes(&invalid).unwrap_err(), ReaderError::TrailingBytes(1));
+
+        assert_eq!(Module::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(), ReaderError::UnexpectedEnd);
+
+        assert_eq!(
+            Module::from_bytes_with_limit(&bytes, bytes.len() - 1, &OpaqueRegistry::new()).unwrap_err(),
+            ReaderError::SizeLimit(bytes.len(), bytes.len() - 1)
+        );
+    }
+
+    #[test]
+    fn test_module_duplicated_constant() {
+        let mut writer = Writer::new();
+        writer.write_bytes(&MODULE_MAGIC);
+        writer.write_u8(MODULE_FORMAT_VERSION);
+        writer.write_varint(2);
+        ValueCell::from(Primitive::Null).write(&mut writer);
+        ValueCell::from(Primitive::Null).write(&mut writer);
+
+        assert_eq!(Module::from_bytes(writer.as_bytes()).unwrap_err(), ReaderError::DuplicatedEntry);
+    }
+
+    #[test]
+    fn test_module_nested_ranges() {
+        // A constant made of RANGE tags only must not recurse
+        let mut writer = Writer::new();
+        writer.write_bytes(&MODULE_MAGIC);
+        writer.write_u8(MODULE_FORMAT_VERSION);
+        writer.write_varint(1);
+        writer.write_bytes(&[9; 500_000]);
+
+        assert_eq!(Module::from_bytes_with_limit(writer.as_bytes(), MODULE_MAX_SIZE, &OpaqueRegistry::new()).unwrap_err(), ReaderError::InvalidValue);
+    }
+
+    #[test]
+    fn test_module_default_size_limit() {
+        let bytes = vec![0; MODULE_MAX_SIZE + 1];
+        assert_eq!(Module::from_bytes(&bytes).unwrap_err(), ReaderError::SizeLimit(MODULE_MAX_SIZE + 1, MODULE_MAX_SIZE));
+    }
+}
@@ -0,0 +1,203 @@
+use xelis_ast::{FunctionNames, FunctionType, Parameter, Program};
+use xelis_bytecode::{Abi, AbiEntry, AbiEnum, AbiEnumVariant, AbiEvent, AbiField, AbiHook, AbiStruct};
+use xelis_types::Type;
+
+use crate::CompilerError;
+
+// Zip the names with their types to build fields
+fn to_fields<'a, I: IntoIterator<Item = &'a Type>>(names: &[String], types: I) -> Vec<AbiField> {
+    names.iter()
+        .zip(types)
+        .map(|(name, value_type)| AbiField {
+            name: name.clone(),
+            value_type: value_type.clone()
+        })
+        .collect()
+}
+
+// Convert the parameters of a function to fields
+fn to_parameters(names: &FunctionNames, parameters: &[Parameter]) -> Vec<AbiField> {
+    to_fields(names.parameters(), parameters.iter().map(Parameter::get_type))
+}
+
+// Generate the ABI of a program
+// Names are the ones kept on the program by the parser
+// Chunk ids follow the order of the functions in the program like in the Compiler
+pub fn generate_abi(program: &Program) -> Result<Abi, CompilerError> {
+    let mut abi = Abi::default();
+
+    for (chunk_id, function) in program.functions().iter().enumerate() {
+        match function {
+            FunctionType::Entry(entry) => abi.entries.push(AbiEntry {
+                name: entry.get_names().name().to_owned(),
+                chunk_id: chunk_id as u16,
+                parameters: to_parameters(entry.get_names(), entry.get_parameters()),
+                return_type: entry.get_return_type().clone()
+            }),
+            FunctionType::Hook(hook) => abi.hooks.push(AbiHook {
+                name: hook.get_names().name().to_owned(),
+                hook_id: hook.hook_id(),
+                chunk_id: chunk_id as u16,
+                parameters: to_parameters(hook.get_names(), hook.get_parameters()),
+                return_type: hook.get_return_type().clone()
+            }),
+            FunctionType::Declared(_) => {}
+        };
+    }
+
+    for structure in program.structures() {
+        let names = structure.names()
+            .ok_or(CompilerError::AbiStructWithoutNames(structure.id()))?;
+
+        abi.structs.push(AbiStruct {
+            id: structure.id(),
+            name: names.name().to_owned(),
+            fields: to_fields(names.fields(), structure.fields())
+        });
+    }
+
+    for enum_type in program.enums() {
+        let names = enum_type.names()
+            .ok_or(CompilerError::AbiEnumWithoutNames(enum_type.id()))?;
+
+        abi.enums.push(AbiEnum {
+            id: enum_type.id(),
+            name: names.name().to_owned(),
+            variants: names.variants()
+                .iter()
+                .zip(enum_type.variants())
+                .map(|(names, variant)| AbiEnumVariant {
+                    name: names.name().to_owned(),
+                    fields: to_fields(names.fields(), variant.fields())
+                })
+                .collect()
+        });
+    }
+
+    abi.storage = program.storage()
+        .iter()
+        .map(|storage| AbiField {
+            name: storage.name.clone(),
+            value_type: storage.value_type.clone()
+        })
+        .collect();
+
+    abi.events = program.events()
+        .iter()
+        .enumerate()
+        .map(|(id, event)| AbiEvent {
+            id: id as u16,
+            name: event.name.clone(),
+            fields: event.fields.iter()
+                .map(|(name, value_type)| AbiField {
+                    name: name.clone(),
+                    value_type: value_type.clone()
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(abi)
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_builder::EnvironmentBuilder;
+    use xelis_lexer::Lexer;
+    use xelis_parser::Parser;
+
+    use xelis_types::{Primitive, ValueCell};
+
+    use super::*;
+
+    #[test]
+    fn test_abi() {
+        let code = r#"
+            struct Point { x: u64, y: u64 }
+            enum Shape { Empty, Circle { center: Point, radius: u64 } }
+            storage supply: u64
+            storage balances: map<string, u64>
+            event Transfer { to: string, amount: u64 }
+            fn double(value: u64) -> u64 { return value * 2 }
+            hook on_init(data: u8) -> bool { return true }
+            entry transfer(to: string, amount: u64) { return double(amount) }
+        "#;
+
+        let mut env = EnvironmentBuilder::default();
+        env.register_hook("on_init", vec![("data", Type::U8)], Some(Type::Bool));
+
+        let tokens = Lexer::new(code).get().unwrap();
+        let (program, _) = Parser::new(tokens, &env).parse().unwrap();
+        let abi = generate_abi(&program).unwrap();
+
+        let point = program.structures()[0].clone();
+        // Names registered by the parser are kept on the type
+        let names = point.names().unwrap();
+        assert_eq!((names.name(), names.fields()), ("Point", ["x".to_owned(), "y".to_owned()].as_slice()));
+        assert_eq!(abi.structs, vec![AbiStruct {
+            id: point.id(),
+            name: "Point".to_owned(),
+            fields: vec![
+                AbiField { name: "x".to_owned(), value_type: Type::U64 },
+                AbiField { name: "y".to_owned(), value_type: Type::U64 }
+            ]
+        }]);
+
+        assert_eq!(abi.enums.len(), 1);
+        let shape = &abi.enums[0];
+        assert_eq!(shape.name, "Shape");
+        assert_eq!(shape.variants, vec![
+            AbiEnumVariant { name: "Empty".to_owned(), fields: Vec::new() },
+            AbiEnumVariant {
+                name: "Circle".to_owned(),
+                fields: vec![
+                    AbiField { name: "center".to_owned(), value_type: Type::Struct(point) },
+                    AbiField { name: "radius".to_owned(), value_type: Type::U64 }
+                ]
+            }
+        ]);
+
+        assert_eq!(abi.hooks, vec![AbiHook {
+            name: "on_init".to_owned(),
+            hook_id: 0,
+            chunk_id: 1,
+            parameters: vec![AbiField { name: "data".to_owned(), value_type: Type::U8 }],
+            return_type: Some(Type::Bool)
+        }]);
+
+        let entry = abi.get_entry("transfer").unwrap();
+        assert_eq!(entry.chunk_id, 2);
+        assert_eq!(entry.return_type, Some(Type::U64));
+        assert_eq!(entry.parameters, vec![
+            AbiField { name: "to".to_owned(), value_type: Type::String },
+            AbiField { name: "amount".to_owned(), value_type: Type::U64 }
+        ]);
+
+        assert_eq!(abi.storage, vec![
+            AbiField { name: "supply".to_owned(), value_type: Type::U64 },
+            AbiField { name: "balances".to_owned(), value_type: Type::Map(Box::new(Type::String), Box::new(Type::U64)) }
+        ]);
+        assert_eq!(abi.storage_key("supply", None, None).unwrap(), Primitive::String("supply".to_owned()).into());
+        assert_eq!(
+            abi.storage_key("balances", Some(&serde_json::json!("alice")), None).unwrap(),
+            ValueCell::Array(vec![Primitive::String("balances".to_owned()).into(), Primitive::String("alice".to_owned()).into()])
+        );
+        assert!(abi.storage_key("balances", None, None).is_err());
+
+        assert_eq!(abi.events, vec![AbiEvent {
+            id: 0,
+            name: "Transfer".to_owned(),
+            fields: vec![
+                AbiField { name: "to".to_owned(), value_type: Type::String },
+                AbiField { name: "amount".to_owned(), value_type: Type::U64 }
+            ]
+        }]);
+        let data = ValueCell::Array(vec![Primitive::String("alice".to_owned()).into(), Primitive::U64(5).into()]);
+        assert_eq!(abi.event_to_json(0, &data).unwrap(), serde_json::json!({ "event": "Transfer", "fields": { "to": "alice", "amount": 5 } }));
+        assert!(abi.event_to_json(1, &data).is_err());
+
+        // The ABI can be shared as JSON
+        let json = serde_json::to_string(&abi).unwrap();
+        assert_eq!(serde_json::from_str::<Abi>(&json).unwrap(), abi);
+    }
+}
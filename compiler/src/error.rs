@@ -1,5 +1,6 @@
 use thiserror::Error;
 use xelis_ast::Operator;
+use xelis_types::IdentifierType;
 
 #[derive(Debug, Error)]
 pub enum CompilerError {
@@ -45,4 +46,14 @@ pub enum CompilerError {
     ExpectedMemoryScope,
     #[error("Hook {0} is already registered")]
     HookAlreadyRegistered(u8),
+    #[error("struct {0} has no names")]
+    AbiStructWithoutNames(IdentifierType),
+    #[error("enum {0} has no names")]
+    AbiEnumWithoutNames(IdentifierType),
+    #[error("storage variable {0} not found")]
+    StorageNotFound(IdentifierType),
+    #[error("missing the key of an entry of the storage map {0}")]
+    MissingStorageKey(IdentifierType),
+    #[error("event {0} not found")]
+    EventNotFound(IdentifierType),
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 mod error;
+mod abi;
 
 use std::{collections::HashSet, iter};
 use log::{trace, warn};
@@ -7,12 +8,17 @@ use xelis_ast::{
     FunctionType,
     Operator,
     Program,
-    Statement
+    Statement,
+    EventEmission,
+    StorageAccess,
+    StorageOperation
 };
 use xelis_environment::Environment;
-use xelis_bytecode::{Chunk, Module, OpCode};
+use xelis_bytecode::{Chunk, DebugInfo, Module, OpCode};
+use xelis_types::{serializer::Serializer, Constant, Primitive, Type, U256};
 
 pub use error::CompilerError;
+pub use abi::*;
 
 // Temporary invalid address to patch jumps
 const INVALID_ADDR: u32 = 0xDEADBEEF;
@@ -39,6 +45,10 @@ pub struct Compiler<'a> {
     // We must track function parameters
     // and clone them on first assignation
     parameters_ids: HashSet<u16>,
+    // Id of the chunk being compiled
+    chunk_id: usize,
+    // Source lines of the instructions
+    debug_info: DebugInfo,
 }
 
 impl<'a> Compiler<'a> {
@@ -52,7 +62,9 @@ impl<'a> Compiler<'a> {
             loop_continue_patch: Vec::new(),
             memstore_ids: Vec::new(),
             values_on_stack: Vec::new(),
-            parameters_ids: HashSet::new()
+            parameters_ids: HashSet::new(),
+            chunk_id: 0,
+            debug_info: DebugInfo::new()
         }
     }
 
@@ -135,6 +147,94 @@ impl<'a> Compiler<'a> {
         Ok(())
     }
 
+    // Compile an access to a storage variable as a system call
+    // The variable is stored under its name,
+    // and each entry of a map under its name followed by the entry key
+    // A value loaded is verified against the type given after the key
+    fn compile_storage_access(&mut self, chunk: &mut Chunk, access: &StorageAccess) -> Result<(), CompilerError> {
+        let declaration = self.program.storage()
+            .get(access.id as usize)
+            .ok_or(CompilerError::StorageNotFound(access.id))?;
+
+        let name = Constant::Default(Primitive::String(declaration.name.clone()));
+        let index = self.module.add_constant(name);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(index as u16);
+        self.add_value_on_stack(chunk.last_index())?;
+
+        let mut parameters = access.parameters.iter();
+        let stored_type = match &declaration.value_type {
+            Type::Map(_, value) => {
+                let key = parameters.next()
+                    .ok_or(CompilerError::MissingStorageKey(access.id))?;
+                self.compile_expr(chunk, key)?;
+
+                chunk.emit_opcode(OpCode::NewObject);
+                chunk.write_u8(2);
+                self.decrease_values_on_stack_by(2)?;
+                self.add_value_on_stack(chunk.last_index())?;
+                value.as_ref()
+            },
+            value_type => value_type
+        };
+
+        let mut args = 1;
+        for param in parameters {
+            self.compile_expr(chunk, param)?;
+            args += 1;
+        }
+
+        if access.operation == StorageOperation::Get {
+            let index = self.module.add_constant(Constant::Bytes(stored_type.to_bytes()));
+            chunk.emit_opcode(OpCode::Constant);
+            chunk.write_u16(index as u16);
+            self.add_value_on_stack(chunk.last_index())?;
+            args += 1;
+        }
+
+        chunk.emit_opcode(OpCode::SysCall);
+        chunk.write_u16(access.syscall);
+        chunk.write_bool(false);
+        chunk.write_u8(args);
+        self.decrease_values_on_stack_by(args as usize)?;
+
+        if access.operation != StorageOperation::Set {
+            self.add_value_on_stack(chunk.last_index())?;
+        }
+
+        Ok(())
+    }
+
+    // Compile the emission of an event as a system call
+    // The event id is given with its fields packed in order
+    fn compile_event_emission(&mut self, chunk: &mut Chunk, emission: &EventEmission) -> Result<(), CompilerError> {
+        if self.program.events().len() <= emission.id as usize {
+            return Err(CompilerError::EventNotFound(emission.id));
+        }
+
+        let id = self.module.add_constant(Constant::Default(Primitive::U16(emission.id)));
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(id as u16);
+        self.add_value_on_stack(chunk.last_index())?;
+
+        for field in emission.fields.iter() {
+            self.compile_expr(chunk, field)?;
+        }
+
+        chunk.emit_opcode(OpCode::NewObject);
+        chunk.write_u8(emission.fields.len() as u8);
+        self.decrease_values_on_stack_by(emission.fields.len())?;
+        self.add_value_on_stack(chunk.last_index())?;
+
+        chunk.emit_opcode(OpCode::SysCall);
+        chunk.write_u16(emission.syscall);
+        chunk.write_bool(false);
+        chunk.write_u8(2);
+        self.decrease_values_on_stack_by(2)?;
+
+        Ok(())
+    }
+
     // Compile the expression
     fn compile_expr(&mut self, chunk: &mut Chunk, expr: &Expression) -> Result<(), CompilerError> {
         trace!("Compiling expression: {:?}", expr);
@@ -289,6 +389,8 @@ impl<'a> Compiler<'a> {
             Expression::ForceType(expr, _) => {
                 self.compile_expr(chunk, expr)?;
             },
+            Expression::Storage(access) => self.compile_storage_access(chunk, access)?,
+            Expression::Emit(emission) => self.compile_event_emission(chunk, emission)?,
             Expression::FunctionCall(expr_on, id, params) => {
                 if let Some(expr_on) = expr_on {
                     self.compile_expr(chunk, expr_on)?;
@@ -405,6 +507,11 @@ impl<'a> Compiler<'a> {
                         if op.is_assignation() {
                             self.function_param_copy_on_assign(chunk, left);
                         }
+
+                        if self.compile_fused_operator(chunk, op, left, right)? {
+                            return Ok(());
+                        }
+
                         self.compile_expr(chunk, left)?;
 
                         self.compile_expr(chunk, right)?;
@@ -424,6 +531,112 @@ impl<'a> Compiler<'a> {
         Ok(())
     }
 
+    // Check if the constant is the number one
+    fn is_constant_one(constant: &Constant) -> bool {
+        match constant {
+            Constant::Default(Primitive::U8(v)) => *v == 1,
+            Constant::Default(Primitive::U16(v)) => *v == 1,
+            Constant::Default(Primitive::U32(v)) => *v == 1,
+            Constant::Default(Primitive::U64(v)) => *v == 1,
+            Constant::Default(Primitive::U128(v)) => *v == 1,
+            Constant::Default(Primitive::U256(v)) => *v == U256::ONE,
+            _ => false
+        }
+    }
+
+    // Compile an operator on a variable with a superinstruction
+    // Returns false if the expression can't be fused
+    fn compile_fused_operator(&mut self, chunk: &mut Chunk, op: &Operator, left: &Expression, right: &Expression) -> Result<bool, CompilerError> {
+        let Expression::Variable(register) = left else {
+            return Ok(false);
+        };
+
+        match (op, right) {
+            // var op= constant
+            (Operator::Assign(Some(inner)), Expression::Constant(constant)) => {
+                let opcode = Self::map_operator_to_opcode(inner)?;
+                if !opcode.is_fusable_operator() || opcode.as_assign_operator().is_none() {
+                    return Ok(false);
+                }
+
+                if matches!(opcode, OpCode::Add) && Self::is_constant_one(constant) {
+                    chunk.emit_opcode(OpCode::MemoryIncrement);
+                    chunk.write_u16(*register);
+                } else {
+                    let index = self.module.add_constant(constant.clone());
+                    chunk.emit_opcode(OpCode::MemoryAssignConstant);
+                    chunk.write_u16(*register);
+                    chunk.write_u16(index as u16);
+                    chunk.write_u8(opcode.as_byte());
+                }
+            },
+            // var op var
+            (op, Expression::Variable(right)) if !op.is_assignation() => {
+                let opcode = Self::map_operator_to_opcode(op)?;
+                if !opcode.is_fusable_operator() {
+                    return Ok(false);
+                }
+
+                chunk.emit_opcode(OpCode::MemoryOperator);
+                chunk.write_u16(*register);
+                chunk.write_u16(*right);
+                chunk.write_u8(opcode.as_byte());
+
+                self.add_value_on_stack(chunk.last_index())?;
+            },
+            // var op constant
+            (op, Expression::Constant(constant)) if !op.is_assignation() => {
+                let opcode = Self::map_operator_to_opcode(op)?;
+                if !opcode.is_fusable_operator() {
+                    return Ok(false);
+                }
+
+                let index = self.module.add_constant(constant.clone());
+                chunk.emit_opcode(OpCode::MemoryConstantOperator);
+                chunk.write_u16(*register);
+                chunk.write_u16(index as u16);
+                chunk.write_u8(opcode.as_byte());
+
+                self.add_value_on_stack(chunk.last_index())?;
+            },
+            _ => return Ok(false)
+        };
+
+        Ok(true)
+    }
+
+    // Compile a condition followed by a jump if false
+    // A comparison of a variable with a constant is fused in one instruction
+    // Returns the index of the jump address to patch
+    fn compile_condition_jump(&mut self, chunk: &mut Chunk, condition: &Expression) -> Result<usize, CompilerError> {
+        if let Expression::Operator(op @ (Operator::Eq | Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte), left, right) = condition {
+            if let (Expression::Variable(register), Expression::Constant(constant)) = (left.as_ref(), right.as_ref()) {
+                let opcode = Self::map_operator_to_opcode(op)?;
+                let index = self.module.add_constant(constant.clone());
+                chunk.emit_opcode(OpCode::MemoryConstantJumpIfFalse);
+                chunk.write_u16(*register);
+                chunk.write_u16(index as u16);
+                chunk.write_u8(opcode.as_byte());
+                chunk.write_u32(INVALID_ADDR);
+
+                return Ok(chunk.last_index());
+            }
+        }
+
+        self.compile_expr(chunk, condition)?;
+
+        // Emit the jump if false
+        // We will overwrite the addr later
+        chunk.emit_opcode(OpCode::JumpIfFalse);
+        chunk.write_u32(INVALID_ADDR);
+        let jump_addr = chunk.last_index();
+
+        // One is used for the jump if false
+        self.decrease_values_on_stack()?;
+
+        Ok(jump_addr)
+    }
+
     // To prevent any change in function caller variables
     // Because VM allow us to changes values from one to another chunk invoke
     fn function_param_copy_on_assign(&mut self, chunk: &mut Chunk, expr: &Expression) {
@@ -537,16 +750,8 @@ impl<'a> Compiler<'a> {
                 },
                 Statement::If(condition, statements, else_statements) => {
                     self.push_mem_scope();
-                    self.compile_expr(chunk, condition)?;
-
-                    // Emit the jump if false
                     // We will overwrite the addr later
-                    chunk.emit_opcode(OpCode::JumpIfFalse);
-                    chunk.write_u32(INVALID_ADDR);
-                    let jump_addr = chunk.last_index();
-
-                    // One is used for the jump if false
-                    self.decrease_values_on_stack()?;
+                    let jump_addr = self.compile_condition_jump(chunk, condition)?;
 
                     // Compile the valid condition
                     self.compile_statements(chunk, statements)?;
@@ -582,16 +787,8 @@ impl<'a> Compiler<'a> {
                 },
                 Statement::While(expr, statements) => {
                     let start_index = chunk.index();
-                    self.compile_expr(chunk, expr)?;
-
-                    // Emit the jump if false
                     // We will overwrite the addr later
-                    chunk.emit_opcode(OpCode::JumpIfFalse);
-                    chunk.write_u32(INVALID_ADDR);
-                    let jump_addr = chunk.last_index();
-
-                    // One is used for the jump if false
-                    self.decrease_values_on_stack()?;
+                    let jump_addr = self.compile_condition_jump(chunk, expr)?;
 
                     self.start_loop();
                     // Compile the valid condition
@@ -654,16 +851,8 @@ impl<'a> Compiler<'a> {
 
                     // Compile the condition
                     let start_index = chunk.index();
-                    self.compile_expr(chunk, expr_condition)?;
-
-                    // Emit the jump if false
                     // We will overwrite the addr later
-                    chunk.emit_opcode(OpCode::JumpIfFalse);
-                    chunk.write_u32(INVALID_ADDR);
-                    let jump_addr = chunk.last_index();
-
-                    // One is used for the jump if false
-                    self.decrease_values_on_stack()?;
+                    let jump_addr = self.compile_condition_jump(chunk, expr_condition)?;
 
                     self.start_loop();
                     // Compile the valid condition
@@ -684,6 +873,7 @@ impl<'a> Compiler<'a> {
 
                     self.end_loop(chunk, continue_index, jump_false_addr)?;
                 },
+                Statement::Line(line) => self.debug_info.add_line(self.chunk_id, chunk.index(), *line),
                 Statement::Break => {
                     chunk.emit_opcode(OpCode::Jump);
                     chunk.write_u32(INVALID_ADDR);
@@ -710,6 +900,7 @@ impl<'a> Compiler<'a> {
     fn compile_function(&mut self, function: &FunctionType) -> Result<(), CompilerError> {
         trace!("Compiling function: {:?}", function);
         let mut chunk = Chunk::new();
+        self.chunk_id = self.module.chunks().len();
 
         // Push the new scope for ids
         self.push_mem_scope();
@@ -739,6 +930,16 @@ impl<'a> Compiler<'a> {
         // Pop the scope for ids
         self.pop_mem_scope(&mut chunk)?;
 
+        // Entries and hooks are invoked by the host
+        // keep their parameters types so the VM can verify the arguments
+        if !matches!(function, FunctionType::Declared(_)) {
+            let parameters = function.get_parameters()
+                .iter()
+                .map(|parameter| parameter.get_type().clone())
+                .collect();
+            self.module.set_chunk_signature(self.module.chunks().len(), parameters);
+        }
+
         // Add the chunk to the module
         match function {
             FunctionType::Declared(_) => self.module.add_chunk(chunk),
@@ -754,7 +955,14 @@ impl<'a> Compiler<'a> {
     }
 
     // Compile the program
-    pub fn compile(mut self) -> Result<Module, CompilerError> {
+    pub fn compile(self) -> Result<Module, CompilerError> {
+        self.compile_with_debug_info()
+            .map(|(module, _)| module)
+    }
+
+    // Compile the program and map each instruction to its source line
+    // Lines are only known if the parser received the tokens with their position
+    pub fn compile_with_debug_info(mut self) -> Result<(Module, DebugInfo), CompilerError> {
         // Compile the program
         for function in self.program.functions() {
             self.compile_function(function)?;
@@ -778,12 +986,13 @@ impl<'a> Compiler<'a> {
         }
 
         // Return the module
-        Ok(self.module)
+        Ok((self.module, self.debug_info))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use xelis_ast::{EntryFunction, FunctionNames, StorageDeclaration};
     use xelis_builder::EnvironmentBuilder;
     use xelis_lexer::Lexer;
     use xelis_parser::Parser;
@@ -791,6 +1000,28 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_storage_missing_key() {
+        let mut program = Program::new();
+        program.add_storage(StorageDeclaration {
+            name: "balances".to_owned(),
+            value_type: Type::Map(Box::new(Type::String), Box::new(Type::U64))
+        });
+
+        // An access to an entry of the map without its key
+        let access = Expression::Storage(StorageAccess {
+            id: 0,
+            operation: StorageOperation::Has,
+            syscall: 0,
+            parameters: Vec::new()
+        });
+        program.add_function(FunctionType::Entry(EntryFunction::new(FunctionNames::new("read", Vec::new()), Vec::new(), vec![Statement::Return(Some(access))], Some(Type::Bool), 0)));
+
+        let environment = EnvironmentBuilder::default().build();
+        let err = Compiler::new(&program, &environment).compile().unwrap_err();
+        assert!(matches!(err, CompilerError::MissingStorageKey(0)));
+    }
+
     #[test]
     fn test_empty_program() {
         let program = Program::new();
@@ -829,6 +1060,21 @@ mod tests {
         (program, environment.build())
     }
 
+    #[test]
+    fn test_debug_info() {
+        let code = "entry main() {\n    let a: u64 = 1;\n\n    return a\n}";
+        let environment = EnvironmentBuilder::default();
+        let tokens = Lexer::new(code).collect::<Result<Vec<_>, _>>().unwrap();
+        let (program, _) = Parser::with(tokens.into_iter(), &environment).parse().unwrap();
+        let environment = environment.build();
+
+        let (_, debug_info) = Compiler::new(&program, &environment).compile_with_debug_info().unwrap();
+        assert_eq!(debug_info.get_offsets_of_line(2), vec![(0, 0)]);
+        assert_eq!(debug_info.get_line(0, 0), Some(2));
+        assert_eq!(debug_info.get_offsets_of_line(3), Vec::new());
+        assert_eq!(debug_info.get_offsets_of_line(4).len(), 1);
+    }
+
     #[test]
     fn test_program_with_constants() {
         let (program, environment) = prepare_program_with_const_enabled("const A: u64 = 1; const B: u64 = 2; entry main() { return A + B }");
@@ -979,13 +1225,10 @@ mod tests {
             &[
                 OpCode::Constant.as_byte(), 0, 0,
                 OpCode::MemorySet.as_byte(), 0, 0,
-                OpCode::MemoryLoad.as_byte(), 0, 0,
-                OpCode::Constant.as_byte(), 1, 0,
-                OpCode::Lt.as_byte(),
-                OpCode::JumpIfFalse.as_byte(), 30, 0, 0, 0,
-                OpCode::MemoryLoad.as_byte(), 0, 0,
-                OpCode::Constant.as_byte(), 2, 0,
-                OpCode::AssignAdd.as_byte(),
+                // i < 10
+                OpCode::MemoryConstantJumpIfFalse.as_byte(), 0, 0, 1, 0, OpCode::Lt.as_byte(), 24, 0, 0, 0,
+                // i += 1
+                OpCode::MemoryIncrement.as_byte(), 0, 0,
                 OpCode::Jump.as_byte(), 6, 0, 0, 0,
                 OpCode::MemoryLoad.as_byte(), 0, 0,
                 OpCode::Return.as_byte()
@@ -1036,15 +1279,10 @@ mod tests {
             &[
                 OpCode::Constant.as_byte(), 0, 0,
                 OpCode::MemorySet.as_byte(), 0, 0,
-                OpCode::MemoryLoad.as_byte(), 0, 0,
-                OpCode::Constant.as_byte(), 1, 0,
-                OpCode::Lt.as_byte(),
-                OpCode::JumpIfFalse.as_byte(), 34, 0, 0, 0,
+                OpCode::MemoryConstantJumpIfFalse.as_byte(), 0, 0, 1, 0, OpCode::Lt.as_byte(), 28, 0, 0, 0,
                 OpCode::MemoryLoad.as_byte(), 0, 0,
                 OpCode::Return.as_byte(),
-                OpCode::MemoryLoad.as_byte(), 0, 0,
-                OpCode::Constant.as_byte(), 2, 0,
-                OpCode::AssignAdd.as_byte(),
+                OpCode::MemoryIncrement.as_byte(), 0, 0,
                 OpCode::Jump.as_byte(), 6, 0, 0, 0,
                 OpCode::Constant.as_byte(), 2, 0,
                 OpCode::Return.as_byte()
@@ -1151,16 +1389,11 @@ mod tests {
             &[
                 OpCode::Constant.as_byte(), 0, 0,
                 OpCode::MemorySet.as_byte(), 0, 0,
-                OpCode::MemoryLoad.as_byte(), 0, 0,
-                OpCode::Constant.as_byte(), 1, 0,
-                OpCode::Lt.as_byte(),
-                OpCode::JumpIfFalse.as_byte(), 35, 0, 0, 0,
+                OpCode::MemoryConstantJumpIfFalse.as_byte(), 0, 0, 1, 0, OpCode::Lt.as_byte(), 29, 0, 0, 0,
                 // Jump by the continue
                 // It must jump to the increment instruction
-                OpCode::Jump.as_byte(), 23, 0, 0, 0,
-                OpCode::MemoryLoad.as_byte(), 0, 0,
-                OpCode::Constant.as_byte(), 2, 0,
-                OpCode::AssignAdd.as_byte(),
+                OpCode::Jump.as_byte(), 21, 0, 0, 0,
+                OpCode::MemoryIncrement.as_byte(), 0, 0,
                 OpCode::Jump.as_byte(), 6, 0, 0, 0,
                 OpCode::Constant.as_byte(), 0, 0,
                 OpCode::Return.as_byte()
@@ -8,6 +8,7 @@ use crate::EnvironmentError;
 
 use better_any::Tid;
 pub use data::Data;
+use xelis_types::ValueCell;
 use hashbrown::HashMap;
 
 // A hasher for `TypeId`s that takes advantage of its known characteristics.
@@ -46,6 +47,9 @@ pub struct Context<'ty, 'r> {
     current_gas: u64,
     // Current memory used in the execution
     current_memory: usize,
+    // Payload given by a native function
+    // to suspend the execution until the host resumes it
+    yield_request: Option<ValueCell>,
 }
 
 impl Default for Context<'_, '_> {
@@ -65,6 +69,7 @@ impl<'ty, 'r> Context<'ty, 'r> {
             max_value_depth: 16,
             max_memory_usage: 1024 * 1024 * 128, // 128 MB
             current_memory: 0,
+            yield_request: None,
         }
     }
 
@@ -92,6 +97,13 @@ impl<'ty, 'r> Context<'ty, 'r> {
         self.current_gas
     }
 
+    // Set the current gas usage
+    // This is used to restore a previous execution
+    #[inline(always)]
+    pub fn set_current_gas_usage(&mut self, gas: u64) {
+        self.current_gas = gas;
+    }
+
     // Get the max value depth allowed
     #[inline(always)]
     pub fn max_value_depth(&self) -> usize {
@@ -124,6 +136,13 @@ impl<'ty, 'r> Context<'ty, 'r> {
         self.current_memory
     }
 
+    // Set the current memory usage
+    // This is used to restore a previous execution
+    #[inline(always)]
+    pub fn set_current_memory_usage(&mut self, memory: usize) {
+        self.current_memory = memory;
+    }
+
     // Get the max memory usage allowed
     #[inline(always)]
     pub fn max_memory_usage(&self) -> usize {
@@ -163,6 +182,26 @@ impl<'ty, 'r> Context<'ty, 'r> {
         self.current_memory = self.current_memory.saturating_sub(memory);
     }
 
+    // Request the VM to suspend the execution once the current native function returns
+    // The payload is given to the host with the pending call informations
+    // The native function must not return any value, the host provides it on resume
+    #[inline]
+    pub fn request_yield(&mut self, payload: ValueCell) {
+        self.yield_request = Some(payload);
+    }
+
+    // Check if a native function requested to suspend the execution
+    #[inline(always)]
+    pub fn has_yield_request(&self) -> bool {
+        self.yield_request.is_some()
+    }
+
+    // Take the payload of the suspension requested
+    #[inline]
+    pub fn take_yield_request(&mut self) -> Option<ValueCell> {
+        self.yield_request.take()
+    }
+
     // Insert a value into the Context without checking the type
     #[inline]
     pub fn insert_unchecked(&mut self, key: TypeId, data: Data<'ty, 'r>) {
@@ -42,6 +42,22 @@ pub enum EnvironmentError {
     Expect(String),
     #[error("Invalid expect message, require alphanumeric chars only")]
     InvalidExpect,
+    #[error("No VM available to invoke a chunk")]
+    InvokerUnavailable,
+    #[error("Reentrancy depth limit of {0} reached")]
+    ReentrancyDepthExceeded(usize),
+    #[error("Chunk {0} is already active and can't be invoked again")]
+    ReentrantCall(u16),
+    #[error("No module found for the given key")]
+    UnknownModule,
+    #[error("No state checkpoint is open")]
+    NoStateCheckpoint,
+    #[error("No storage available in the context")]
+    StorageUnavailable,
+    #[error("Value stored doesn't match the type of the storage variable")]
+    InvalidStorageValue,
+    #[error("No event log available in the context")]
+    EventLogUnavailable,
     #[error("{0}")]
     Static(&'static str)
 }
@@ -0,0 +1,121 @@
+use better_any::tid;
+use xelis_types::ValueCell;
+
+use crate::{EnvironmentError, Journaled};
+
+// Event emitted by a program to notify the host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    // Hash of the module emitting the event
+    pub module: [u8; 32],
+    // Id of the event, declared events use their index in the ABI
+    pub id: u16,
+    // Set for the events emitted with the raw `emit` function
+    // Their id is chosen by the program and doesn't refer to the ABI
+    pub raw: bool,
+    // Fields of the event
+    pub data: ValueCell
+}
+
+// Log collecting the events emitted during an execution
+// A checkpoint is opened for each call frame like the JournaledState,
+// so the events emitted by a frame are dropped if the VM stops on an error
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: Vec<Event>,
+    // Count of events when each checkpoint was opened
+    checkpoints: Vec<usize>
+}
+
+tid!(EventLog);
+
+impl EventLog {
+    // Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Append an event to the log
+    #[inline]
+    pub fn emit(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    // How many checkpoints are currently open
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    // Get all the events emitted
+    #[inline]
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // Take the events emitted, the log can be reused for the next execution
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.checkpoints.clear();
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Journaled for EventLog {
+    fn checkpoint(&mut self) {
+        self.checkpoints.push(self.events.len());
+    }
+
+    fn commit(&mut self) -> Result<(), EnvironmentError> {
+        self.checkpoints.pop()
+            .ok_or(EnvironmentError::NoStateCheckpoint)?;
+
+        Ok(())
+    }
+
+    // Events emitted since the checkpoint are dropped
+    fn rollback(&mut self) -> Result<(), EnvironmentError> {
+        let len = self.checkpoints.pop()
+            .ok_or(EnvironmentError::NoStateCheckpoint)?;
+
+        self.events.truncate(len);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_types::Primitive;
+    use super::*;
+
+    fn event(id: u16) -> Event {
+        Event {
+            module: [0; 32],
+            id,
+            raw: false,
+            data: Primitive::Null.into()
+        }
+    }
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let mut log = EventLog::new();
+        log.emit(event(0));
+
+        log.checkpoint();
+        log.emit(event(1));
+
+        log.checkpoint();
+        log.emit(event(2));
+        log.rollback().unwrap();
+
+        log.checkpoint();
+        log.emit(event(3));
+        log.commit().unwrap();
+        assert_eq!(log.events(), [event(0), event(1), event(3)].as_slice());
+
+        // The parent checkpoint drops the committed events too
+        log.rollback().unwrap();
+        assert_eq!(log.take_events(), vec![event(0)]);
+        assert!(matches!(log.rollback(), Err(EnvironmentError::NoStateCheckpoint)));
+    }
+}
@@ -1,5 +1,5 @@
 use xelis_types::{StackValue, Type, ValueCell};
-use crate::Context;
+use crate::{ChunkInvoker, Context, NoInvoker};
 
 use super::EnvironmentError;
 
@@ -9,6 +9,15 @@ pub type FnReturnType = Result<Option<ValueCell>, EnvironmentError>;
 pub type FnInstance<'a> = Result<&'a mut ValueCell, EnvironmentError>;
 pub type FnParams = Vec<StackValue>;
 pub type OnCallFn = fn(FnInstance, FnParams, &mut Context) -> FnReturnType;
+// Native function able to invoke chunks of the module executed
+pub type OnReentrantCallFn = for<'ty, 'r> fn(FnInstance, FnParams, &mut dyn ChunkInvoker<'ty, 'r>, &mut Context<'ty, 'r>) -> FnReturnType;
+
+// Callback executed by a native function
+#[derive(Debug, Clone, Copy)]
+pub enum OnCall {
+    Simple(OnCallFn),
+    Reentrant(OnReentrantCallFn)
+}
 
 // Native function that is implemented in Rust
 // This is used to register functions in the environment
@@ -17,7 +26,7 @@ pub struct NativeFunction {
     // function on type
     require_instance: bool,
     parameters: Vec<Type>,
-    on_call: OnCallFn,
+    on_call: OnCall,
     // cost for each call
     cost: u64,
     // expected type of the returned value
@@ -27,6 +36,16 @@ pub struct NativeFunction {
 impl NativeFunction {
     // Create a new instance of the NativeFunction
     pub fn new(require_instance: bool, parameters: Vec<Type>, on_call: OnCallFn, cost: u64, return_type: Option<Type>) -> Self {
+        Self::with_on_call(require_instance, parameters, OnCall::Simple(on_call), cost, return_type)
+    }
+
+    // Create a new instance of a NativeFunction able to invoke chunks
+    pub fn new_reentrant(require_instance: bool, parameters: Vec<Type>, on_call: OnReentrantCallFn, cost: u64, return_type: Option<Type>) -> Self {
+        Self::with_on_call(require_instance, parameters, OnCall::Reentrant(on_call), cost, return_type)
+    }
+
+    // Create a new instance of the NativeFunction with any kind of callback
+    pub fn with_on_call(require_instance: bool, parameters: Vec<Type>, on_call: OnCall, cost: u64, return_type: Option<Type>) -> Self {
         Self {
             require_instance,
             parameters,
@@ -37,7 +56,13 @@ impl NativeFunction {
     }
 
     // Execute the function
+    // A reentrant function can't invoke any chunk from here
     pub fn call_function(&self, instance_value: Option<&mut ValueCell>, parameters: FnParams, context: &mut Context) -> Result<Option<ValueCell>, EnvironmentError> {
+        self.call_function_with_invoker(instance_value, parameters, &mut NoInvoker, context)
+    }
+
+    // Execute the function, a reentrant function invokes the chunks through the invoker
+    pub fn call_function_with_invoker<'ty, 'r>(&self, instance_value: Option<&mut ValueCell>, parameters: FnParams, invoker: &mut dyn ChunkInvoker<'ty, 'r>, context: &mut Context<'ty, 'r>) -> Result<Option<ValueCell>, EnvironmentError> {
         if parameters.len() != self.parameters.len() || (instance_value.is_some() != self.require_instance) {
             return Err(EnvironmentError::InvalidFnCall(parameters.len(), self.parameters.len(), instance_value.is_some(), self.require_instance));
         }
@@ -46,12 +71,28 @@ impl NativeFunction {
             Some(v) => Ok(v),
             None => Err(EnvironmentError::FnExpectedInstance)
         };
-        (self.on_call)(instance, parameters, context)
+
+        match self.on_call {
+            OnCall::Simple(on_call) => on_call(instance, parameters, context),
+            OnCall::Reentrant(on_call) => on_call(instance, parameters, invoker, context)
+        }
     }
 
     // Set the function on call
     pub fn set_on_call(&mut self, on_call: OnCallFn) {
-        self.on_call = on_call;
+        self.on_call = OnCall::Simple(on_call);
+    }
+
+    // Check if the function can invoke chunks
+    #[inline]
+    pub fn is_reentrant(&self) -> bool {
+        matches!(self.on_call, OnCall::Reentrant(_))
+    }
+
+    // Check if the function is called on an instance
+    #[inline]
+    pub fn require_instance(&self) -> bool {
+        self.require_instance
     }
 
     // Get parameters of the function
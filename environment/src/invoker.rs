@@ -0,0 +1,52 @@
+use xelis_types::ValueCell;
+
+use crate::{Context, EnvironmentError};
+
+// Allow a native function to invoke a chunk of the module being executed
+// The chunk is executed with the same context, so gas and memory are shared
+pub trait ChunkInvoker<'ty, 'r> {
+    // Invoke the chunk with its arguments, first parameter first,
+    // and run it until it returns
+    fn invoke_chunk(&mut self, id: u16, args: Vec<ValueCell>, context: &mut Context<'ty, 'r>) -> Result<Option<ValueCell>, EnvironmentError>;
+
+    // Invoke an entry of another module resolved by the host using its key
+    // The callee has its own stack but shares the same context,
+    // so the gas limit applies to both executions
+    fn invoke_module(&mut self, _module: &ValueCell, _entry: u16, _args: Vec<ValueCell>, _context: &mut Context<'ty, 'r>) -> Result<Option<ValueCell>, EnvironmentError> {
+        Err(EnvironmentError::InvokerUnavailable)
+    }
+
+    // Hash of the module executing the native function
+    fn module_hash(&self) -> Result<[u8; 32], EnvironmentError> {
+        Err(EnvironmentError::InvokerUnavailable)
+    }
+}
+
+// Invoker used when no VM is available to execute a chunk
+pub struct NoInvoker;
+
+impl<'ty, 'r> ChunkInvoker<'ty, 'r> for NoInvoker {
+    fn invoke_chunk(&mut self, _: u16, _: Vec<ValueCell>, _: &mut Context<'ty, 'r>) -> Result<Option<ValueCell>, EnvironmentError> {
+        Err(EnvironmentError::InvokerUnavailable)
+    }
+}
+
+// Rules applied to the chunks invoked by native functions
+#[derive(Debug, Clone)]
+pub struct ReentrancyGuard {
+    // How many nested invocations can be active at the same time
+    // Zero disables the invocations from native functions
+    pub max_depth: usize,
+    // Allow to invoke a chunk that is already in the call stack
+    // of the VM executing the native function or of its parents
+    pub allow_active_chunks: bool
+}
+
+impl Default for ReentrancyGuard {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            allow_active_chunks: false
+        }
+    }
+}
@@ -1,17 +1,25 @@
 mod error;
 mod function;
 mod context;
+mod invoker;
+mod state;
+mod events;
+mod storage;
 
 use std::any::TypeId;
 
 use indexmap::IndexSet;
-use xelis_types::{EnumType, StructType};
+use xelis_types::{EnumType, Opaque, OpaqueRegistry, OpaqueType, OpaqueWrapper, StructType, Type, ValueCell};
 
 // Also re-export the necessary macro
 pub use better_any::tid;
 pub use error::EnvironmentError;
 pub use function::*;
 pub use context::*;
+pub use invoker::*;
+pub use state::{Journaled, JournaledState};
+pub use events::{Event, EventLog};
+pub use storage::*;
 
 /// Environment is used to store all the registered functions and structures
 /// It is used to give a context/std library to the parser / interpreter / VM
@@ -25,8 +33,12 @@ pub struct Environment {
     enums: IndexSet<EnumType>,
     // All opaques types provided by the Environment
     opaques: IndexSet<TypeId>,
+    // Registry to deserialize the opaques by their name or id
+    opaque_registry: OpaqueRegistry,
     // Number of hooks registered
-    hooks: u8
+    hooks: u8,
+    // Rules for the chunks invoked by native functions
+    reentrancy_guard: ReentrancyGuard
 }
 
 tid!(Environment);
@@ -38,7 +50,9 @@ impl Default for Environment {
             structures: IndexSet::new(),
             enums: IndexSet::new(),
             opaques: IndexSet::new(),
-            hooks: 0
+            opaque_registry: OpaqueRegistry::new(),
+            hooks: 0,
+            reentrancy_guard: ReentrancyGuard::default()
         }
     }
 }
@@ -73,6 +87,21 @@ impl Environment {
         &self.opaques
     }
 
+    // Check if a value matches the expected type
+    // Opaque types are resolved using the registered opaques
+    pub fn is_value_of_type(&self, value: &ValueCell, ty: &Type) -> bool {
+        let is_opaque = |ty: &OpaqueType, opaque: &OpaqueWrapper| self.opaques.get_index(ty.id() as usize)
+            .is_some_and(|type_id| *type_id == opaque.get_type_id());
+
+        value.is_of_type(ty, &is_opaque)
+    }
+
+    // Get the registry used to deserialize the opaques
+    #[inline(always)]
+    pub fn get_opaque_registry(&self) -> &OpaqueRegistry {
+        &self.opaque_registry
+    }
+
     // Add a new function to the environment
     #[inline(always)]
     pub fn add_function(&mut self, function: NativeFunction) {
@@ -97,9 +126,11 @@ impl Environment {
     }
 
     // Add a new opaque type to the environment
+    // It is also registered to be deserialized under its type name
     #[inline(always)]
-    pub fn add_opaque(&mut self, ty: TypeId) {
-        self.opaques.insert(ty);
+    pub fn add_opaque<T: Opaque>(&mut self) {
+        self.opaques.insert(TypeId::of::<T>());
+        self.opaque_registry.register::<T>();
     }
 
     // Allow to change the cost of a function
@@ -118,4 +149,15 @@ impl Environment {
     pub fn hooks(&self) -> u8 {
         self.hooks
     }
+
+    // Get the rules for the chunks invoked by native functions
+    #[inline(always)]
+    pub fn get_reentrancy_guard(&self) -> &ReentrancyGuard {
+        &self.reentrancy_guard
+    }
+
+    // Set the rules for the chunks invoked by native functions
+    pub fn set_reentrancy_guard(&mut self, guard: ReentrancyGuard) {
+        self.reentrancy_guard = guard;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,173 @@
+use better_any::tid;
+use indexmap::IndexMap;
+use xelis_types::ValueCell;
+
+use crate::EnvironmentError;
+
+// Host data reverted if the execution fails
+// The VM opens a checkpoint for each call frame: it is committed when the
+// frame returns and rolled back if the VM stops on an error
+pub trait Journaled {
+    // Open a new checkpoint
+    fn checkpoint(&mut self);
+
+    // Close the last checkpoint and keep its changes
+    // They can still be reverted by a parent checkpoint
+    fn commit(&mut self) -> Result<(), EnvironmentError>;
+
+    // Close the last checkpoint and revert all its changes
+    fn rollback(&mut self) -> Result<(), EnvironmentError>;
+}
+
+// Key/value state shared with the host and written by the native functions
+// Every change is journaled so it can be reverted if the execution fails
+// A checkpoint is opened for each call frame: it is committed when the frame
+// returns and rolled back if the VM stops on an error
+#[derive(Debug, Default)]
+pub struct JournaledState {
+    values: IndexMap<ValueCell, ValueCell>,
+    // Previous value of each key changed since the first checkpoint
+    journal: Vec<(ValueCell, Option<ValueCell>)>,
+    // Length of the journal when each checkpoint was opened
+    checkpoints: Vec<usize>
+}
+
+tid!(JournaledState);
+
+impl JournaledState {
+    // Create an empty state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Create a state from the values provided by the host
+    pub fn with_values(values: IndexMap<ValueCell, ValueCell>) -> Self {
+        Self {
+            values,
+            journal: Vec::new(),
+            checkpoints: Vec::new()
+        }
+    }
+
+    // Get the value stored under the key
+    #[inline]
+    pub fn get(&self, key: &ValueCell) -> Option<&ValueCell> {
+        self.values.get(key)
+    }
+
+    // Check if a value is stored under the key
+    #[inline]
+    pub fn contains_key(&self, key: &ValueCell) -> bool {
+        self.values.contains_key(key)
+    }
+
+    // Store a value under the key and return the previous one
+    pub fn set(&mut self, key: ValueCell, value: ValueCell) -> Option<ValueCell> {
+        let previous = self.values.insert(key.clone(), value);
+        self.record(key, previous.clone());
+        previous
+    }
+
+    // Delete the value stored under the key and return it
+    pub fn remove(&mut self, key: &ValueCell) -> Option<ValueCell> {
+        let previous = self.values.shift_remove(key)?;
+        self.record(key.clone(), Some(previous.clone()));
+        Some(previous)
+    }
+
+    // Keep the previous value of a key if a checkpoint can revert it
+    fn record(&mut self, key: ValueCell, previous: Option<ValueCell>) {
+        if !self.checkpoints.is_empty() {
+            self.journal.push((key, previous));
+        }
+    }
+
+    // How many checkpoints are currently open
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    // Get all the values stored
+    #[inline]
+    pub fn values(&self) -> &IndexMap<ValueCell, ValueCell> {
+        &self.values
+    }
+
+    // Get the values stored to be persisted by the host
+    pub fn into_values(self) -> IndexMap<ValueCell, ValueCell> {
+        self.values
+    }
+}
+
+impl Journaled for JournaledState {
+    fn checkpoint(&mut self) {
+        self.checkpoints.push(self.journal.len());
+    }
+
+    fn commit(&mut self) -> Result<(), EnvironmentError> {
+        self.checkpoints.pop()
+            .ok_or(EnvironmentError::NoStateCheckpoint)?;
+
+        // Nothing can revert the changes anymore
+        if self.checkpoints.is_empty() {
+            self.journal.clear();
+        }
+
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), EnvironmentError> {
+        let len = self.checkpoints.pop()
+            .ok_or(EnvironmentError::NoStateCheckpoint)?;
+
+        // Changes are reverted from the most recent one
+        for (key, previous) in self.journal.drain(len..).rev() {
+            match previous {
+                Some(value) => self.values.insert(key, value),
+                None => self.values.shift_remove(&key)
+            };
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_types::Primitive;
+    use super::*;
+
+    fn key(value: u8) -> ValueCell {
+        Primitive::U8(value).into()
+    }
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let mut state = JournaledState::new();
+        state.set(key(0), key(0));
+
+        state.checkpoint();
+        state.set(key(0), key(1));
+        state.set(key(1), key(1));
+
+        state.checkpoint();
+        state.remove(&key(0));
+        state.set(key(2), key(2));
+        state.rollback().unwrap();
+
+        assert_eq!(state.get(&key(0)), Some(&key(1)));
+        assert!(!state.contains_key(&key(2)));
+
+        state.checkpoint();
+        state.set(key(2), key(2));
+        state.commit().unwrap();
+        assert_eq!(state.depth(), 1);
+
+        // The parent checkpoint reverts the committed changes too
+        state.rollback().unwrap();
+        assert_eq!(state.values().len(), 1);
+        assert_eq!(state.get(&key(0)), Some(&key(0)));
+        assert!(matches!(state.rollback(), Err(EnvironmentError::NoStateCheckpoint)));
+    }
+}
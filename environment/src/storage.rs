@@ -0,0 +1,161 @@
+use better_any::tid;
+use indexmap::IndexMap;
+use xelis_types::ValueCell;
+
+use crate::{EnvironmentError, Journaled};
+
+// Persistent key/value storage implemented by the host
+// Values stored are kept across the executions
+// Each module has its own keys, identified by the hash of the module
+pub trait Storage {
+    // Load the value stored under the key
+    fn load(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<Option<ValueCell>, EnvironmentError>;
+
+    // Check if a value is stored under the key
+    fn has(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError>;
+
+    // Store the value under the key, replacing the previous one
+    fn store(&mut self, module: &[u8; 32], key: ValueCell, value: ValueCell) -> Result<(), EnvironmentError>;
+
+    // Delete the value stored under the key
+    // Return true if a value was stored
+    fn delete(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError>;
+}
+
+// Storage inserted in the context to be used by the native functions
+// Writes are journaled so they can be reverted if the execution fails:
+// the previous value of each key changed is kept until the first checkpoint is closed
+pub struct StorageHandle<'a> {
+    storage: &'a mut dyn Storage,
+    // Previous value of each key changed since the first checkpoint
+    journal: Vec<([u8; 32], ValueCell, Option<ValueCell>)>,
+    // Length of the journal when each checkpoint was opened
+    checkpoints: Vec<usize>
+}
+
+tid!(StorageHandle<'_>);
+
+impl<'a> StorageHandle<'a> {
+    pub fn new(storage: &'a mut dyn Storage) -> Self {
+        Self {
+            storage,
+            journal: Vec::new(),
+            checkpoints: Vec::new()
+        }
+    }
+
+    // Get the storage of the host
+    // Writes done directly on it are not journaled
+    #[inline]
+    pub fn storage(&mut self) -> &mut dyn Storage {
+        self.storage
+    }
+
+    // Store the value under the key, replacing the previous one
+    pub fn store(&mut self, module: &[u8; 32], key: ValueCell, value: ValueCell) -> Result<(), EnvironmentError> {
+        self.record(module, &key)?;
+        self.storage.store(module, key, value)
+    }
+
+    // Delete the value stored under the key
+    // Return true if a value was stored
+    pub fn delete(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError> {
+        self.record(module, key)?;
+        self.storage.delete(module, key)
+    }
+
+    // Keep the previous value of a key if a checkpoint can revert it
+    fn record(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<(), EnvironmentError> {
+        if !self.checkpoints.is_empty() {
+            let previous = self.storage.load(module, key)?;
+            self.journal.push((*module, key.clone(), previous));
+        }
+
+        Ok(())
+    }
+
+    // How many checkpoints are currently open
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.checkpoints.len()
+    }
+}
+
+impl Journaled for StorageHandle<'_> {
+    fn checkpoint(&mut self) {
+        self.checkpoints.push(self.journal.len());
+    }
+
+    fn commit(&mut self) -> Result<(), EnvironmentError> {
+        self.checkpoints.pop()
+            .ok_or(EnvironmentError::NoStateCheckpoint)?;
+
+        // Nothing can revert the changes anymore
+        if self.checkpoints.is_empty() {
+            self.journal.clear();
+        }
+
+        Ok(())
+    }
+
+    // Previous values are written back from the most recent change
+    fn rollback(&mut self) -> Result<(), EnvironmentError> {
+        let len = self.checkpoints.pop()
+            .ok_or(EnvironmentError::NoStateCheckpoint)?;
+
+        for (module, key, previous) in self.journal.drain(len..).rev() {
+            match previous {
+                Some(value) => self.storage.store(&module, key, value)?,
+                None => {
+                    self.storage.delete(&module, &key)?;
+                }
+            };
+        }
+
+        Ok(())
+    }
+}
+
+// Storage keeping all the values in memory
+// Mostly useful for tests
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStorage {
+    values: IndexMap<([u8; 32], ValueCell), ValueCell>
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Get all the values stored with the module owning them
+    #[inline]
+    pub fn values(&self) -> &IndexMap<([u8; 32], ValueCell), ValueCell> {
+        &self.values
+    }
+
+    // Get the value stored under the key of a module
+    #[inline]
+    pub fn get(&self, module: &[u8; 32], key: &ValueCell) -> Option<&ValueCell> {
+        self.values.get(&(*module, key.clone()))
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<Option<ValueCell>, EnvironmentError> {
+        Ok(self.get(module, key).cloned())
+    }
+
+    fn has(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError> {
+        Ok(self.get(module, key).is_some())
+    }
+
+    fn store(&mut self, module: &[u8; 32], key: ValueCell, value: ValueCell) -> Result<(), EnvironmentError> {
+        self.values.insert((*module, key), value);
+        Ok(())
+    }
+
+    fn delete(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError> {
+        Ok(self.values.shift_remove(&(*module, key.clone())).is_some())
+    }
+}
@@ -92,6 +92,18 @@ pub enum ParserErrorKind<'a> {
     InvalidHookParameters(&'a str, usize, usize),
     #[error("Invalid return type for hook '{0}': got '{1:?}' but expected '{2:?}'")]
     InvalidHookReturnType(&'a str, Option<Type>, Option<Type>),
+    #[error("Unknown storage operation '{0}'")]
+    UnknownStorageOperation(&'a str),
+    #[error("Invalid parameters len for storage operation '{0}': got '{1}' but expected '{2}'")]
+    InvalidStorageParameters(&'a str, usize, usize),
+    #[error("Invalid event name '{0}', it must start with an uppercase character")]
+    InvalidEventName(&'a str),
+    #[error("Unknown event operation '{0}'")]
+    UnknownEventOperation(&'a str),
+    #[error("Invalid parameters len for event '{0}': got '{1}' but expected '{2}'")]
+    InvalidEventParameters(&'a str, usize, usize),
+    #[error("Function 'emit_event' can't be called directly, use 'Name.emit(...)' on a declared event")]
+    EventSyscallNotCallable,
     #[error("Hook '{0}' (id {1}) is already registered")]
     DuplicatedHook(&'a str, u8),
     #[error("unexpected token '{0:?}'")]
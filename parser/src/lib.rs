@@ -127,6 +127,10 @@ pub struct Parser<'a> {
     tokens: VecDeque<TokenResult<'a>>,
     // All constants declared
     constants: HashMap<&'a str, ConstantDeclaration>,
+    // All storage variables declared
+    storage: IndexMap<&'a str, StorageDeclaration>,
+    // All events declared
+    events: IndexMap<&'a str, EventDeclaration>,
     // All functions registered by the program
     functions: Vec<FunctionType>,
     global_mapper: GlobalMapper<'a>,
@@ -166,6 +170,8 @@ impl<'a> Parser<'a> {
         Self {
             tokens: tokens.collect(),
             constants: HashMap::new(),
+            storage: IndexMap::new(),
+            events: IndexMap::new(),
             functions: Vec::new(),
             global_mapper: GlobalMapper::with(environment),
             environment,
@@ -507,6 +513,22 @@ impl<'a> Parser<'a> {
             Expression::IsNot(_) => Cow::Owned(Type::Bool),
             Expression::Ternary(_, expr, _) => self.get_type_from_expression(on_type, expr, context)?,
             Expression::Cast(_, _type) => Cow::Borrowed(_type),
+            Expression::Storage(access) => match access.operation {
+                StorageOperation::Get => {
+                    let declaration = self.storage.get_index(access.id as usize)
+                        .map(|(_, declaration)| declaration)
+                        .ok_or_else(|| err!(self, ParserErrorKind::UnexpectedMappedVariableId(access.id)))?;
+
+                    let stored_type = match &declaration.value_type {
+                        Type::Map(_, value) => value.as_ref().clone(),
+                        ty => ty.clone()
+                    };
+                    Cow::Owned(Type::Optional(Box::new(stored_type)))
+                },
+                StorageOperation::Has | StorageOperation::Delete => Cow::Owned(Type::Bool),
+                StorageOperation::Set => return Err(err!(self, ParserErrorKind::FunctionNoReturnType))
+            },
+            Expression::Emit(_) => return Err(err!(self, ParserErrorKind::FunctionNoReturnType)),
             Expression::RangeConstructor(start, _) => Cow::Owned(Type::Range(Box::new(self.get_type_from_expression(on_type, start, context)?.into_owned()))),
         };
 
@@ -557,6 +579,12 @@ impl<'a> Parser<'a> {
             return Err(err!(self, ParserErrorKind::FunctionIsEntry))
         }
 
+        // Declared events are only emitted through `Name.emit(...)`
+        // so their fields are always type checked
+        if self.get_event_syscall().is_ok_and(|syscall| syscall == id) {
+            return Err(err!(self, ParserErrorKind::EventSyscallNotCallable))
+        }
+
         Ok(Expression::FunctionCall(path.map(Box::new), id, parameters))
     }
 
@@ -1156,6 +1184,10 @@ impl<'a> Parser<'a> {
                                         Expression::Variable(num_id)
                                     } else if let Some(constant) = self.constants.get(id) {
                                         Expression::Constant(constant.value.clone())
+                                    } else if self.storage.contains_key(id) {
+                                        self.read_storage_access(id, context)?
+                                    } else if self.events.contains_key(id) {
+                                        self.read_event_emission(id, context)?
                                     } else if let Ok(builder) = self.global_mapper.structs().get_by_name(&id) {
                                         self.read_struct_constructor(builder.get_type().clone(), context)?
                                     } else if let Ok(builder) = self.global_mapper.enums().get_by_name(&id) {
@@ -1636,6 +1668,161 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    // Read a storage variable declaration with the following syntax:
+    // storage name: type
+    fn read_storage(&mut self) -> Result<(), ParserError<'a>> {
+        let name = self.next_identifier()?;
+        trace!("Read storage: {}", name);
+
+        if !name.starts_with(char::is_alphabetic) {
+            return Err(err!(self, ParserErrorKind::VariableMustStartWithAlphabetic(name)))
+        }
+
+        if self.storage.contains_key(name) || self.constants.contains_key(name) || self.events.contains_key(name) {
+            return Err(err!(self, ParserErrorKind::VariableNameAlreadyUsed(name)))
+        }
+
+        self.expect_token(Token::Colon)?;
+        let value_type = self.read_type()?;
+
+        self.storage.insert(name, StorageDeclaration {
+            name: name.to_owned(),
+            value_type
+        });
+
+        Ok(())
+    }
+
+    // Read an access to a storage variable with the following syntax:
+    // name.get(), name.set(value), name.has() or name.delete()
+    // If the variable is a map, the entry key is given first: name.get(key)
+    fn read_storage_access(&mut self, name: &'a str, context: &mut Context<'a>) -> Result<Expression, ParserError<'a>> {
+        let (id, _, declaration) = self.storage.get_full(name)
+            .ok_or_else(|| err!(self, ParserErrorKind::UnexpectedVariable(name)))?;
+        let value_type = declaration.value_type.clone();
+
+        self.expect_token(Token::Dot)?;
+        let method = self.next_identifier()?;
+        let (operation, syscall) = match method {
+            "get" => (StorageOperation::Get, "storage_load_typed"),
+            "set" => (StorageOperation::Set, "storage_store"),
+            "has" => (StorageOperation::Has, "storage_has"),
+            "delete" => (StorageOperation::Delete, "storage_delete"),
+            _ => return Err(err!(self, ParserErrorKind::UnknownStorageOperation(method)))
+        };
+
+        // Each entry of a map is stored under its own key
+        let (key_type, stored_type) = match &value_type {
+            Type::Map(key, value) => (Some(key.as_ref().clone()), value.as_ref().clone()),
+            _ => (None, value_type)
+        };
+
+        let mut expected = Vec::with_capacity(2);
+        expected.extend(key_type);
+        if operation == StorageOperation::Set {
+            expected.push(stored_type);
+        }
+
+        let (mut parameters, types) = self.read_function_params(context)?;
+        if parameters.len() != expected.len() {
+            return Err(err!(self, ParserErrorKind::InvalidStorageParameters(method, parameters.len(), expected.len())))
+        }
+
+        for ((param, ty), expected) in parameters.iter_mut().zip(types.iter()).zip(expected.iter()) {
+            self.verify_type_compatibility(param, ty.as_ref(), expected, true)?;
+        }
+
+        // The storage natives take any value as key and value,
+        // a value loaded is verified against its type encoded as bytes
+        let syscall_parameters = match operation {
+            StorageOperation::Get => vec![Type::Any, Type::Bytes],
+            StorageOperation::Set => vec![Type::Any, Type::Any],
+            _ => vec![Type::Any]
+        };
+        let syscall = self.global_mapper.functions()
+            .get(&Signature::new(Cow::Borrowed(syscall), None, Cow::Owned(syscall_parameters)))
+            .map_err(|e| err!(self, e.into()))?;
+
+        Ok(Expression::Storage(StorageAccess {
+            id: id as IdentifierType,
+            operation,
+            syscall,
+            parameters
+        }))
+    }
+
+    // Read an event declaration with the following syntax:
+    // event Name { field: type, ... }
+    // Its id is the declaration order
+    fn read_event(&mut self) -> Result<(), ParserError<'a>> {
+        let name = self.next_identifier()?;
+        trace!("Read event: {}", name);
+
+        if !self.is_name_available(name) {
+            return Err(err!(self, ParserErrorKind::TypeNameAlreadyUsed(name)))
+        }
+
+        if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return Err(err!(self, ParserErrorKind::InvalidEventName(name)))
+        }
+
+        self.expect_token(Token::BraceOpen)?;
+        let params = self.read_parameters()?;
+        if params.len() > u8::MAX as usize {
+            return Err(err!(self, ParserErrorKind::TooManyParameters))
+        }
+        self.expect_token(Token::BraceClose)?;
+
+        self.events.insert(name, EventDeclaration {
+            name: name.to_owned(),
+            fields: params.into_iter()
+                .map(|(name, value_type)| (name.to_owned(), value_type))
+                .collect()
+        });
+
+        Ok(())
+    }
+
+    // Read the emission of an event with the following syntax:
+    // Name.emit(field, ...)
+    fn read_event_emission(&mut self, name: &'a str, context: &mut Context<'a>) -> Result<Expression, ParserError<'a>> {
+        let (id, _, declaration) = self.events.get_full(name)
+            .ok_or_else(|| err!(self, ParserErrorKind::UnexpectedVariable(name)))?;
+        let expected: Vec<Type> = declaration.fields.iter()
+            .map(|(_, value_type)| value_type.clone())
+            .collect();
+
+        self.expect_token(Token::Dot)?;
+        let method = self.next_identifier()?;
+        if method != "emit" {
+            return Err(err!(self, ParserErrorKind::UnknownEventOperation(method)))
+        }
+
+        let (mut fields, types) = self.read_function_params(context)?;
+        if fields.len() != expected.len() {
+            return Err(err!(self, ParserErrorKind::InvalidEventParameters(name, fields.len(), expected.len())))
+        }
+
+        for ((field, ty), expected) in fields.iter_mut().zip(types.iter()).zip(expected.iter()) {
+            self.verify_type_compatibility(field, ty.as_ref(), expected, true)?;
+        }
+
+        let syscall = self.get_event_syscall()?;
+
+        Ok(Expression::Emit(EventEmission {
+            id: id as IdentifierType,
+            syscall,
+            fields
+        }))
+    }
+
+    // Get the native function used to emit a declared event
+    fn get_event_syscall(&self) -> Result<IdentifierType, ParserError<'a>> {
+        self.global_mapper.functions()
+            .get(&Signature::new(Cow::Borrowed("emit_event"), None, Cow::Owned(vec![Type::U16, Type::Any])))
+            .map_err(|e| err!(self, e.into()))
+    }
+
     fn read_loop_body(&mut self, context: &mut Context<'a>, return_type: &Option<Type>) -> Result<Vec<Statement>, ParserError<'a>> {
         // support nested loop
         let old_value = context.is_in_a_loop();
@@ -1793,8 +1980,20 @@ impl<'a> Parser<'a> {
     fn read_statements(&mut self, context: &mut Context<'a>, return_type: &Option<Type>) -> Result<Vec<Statement>, ParserError<'a>> {
         trace!("Read statements");
         let mut statements: Vec<Statement> = Vec::new();
-        while let Some(statement) = self.read_statement(context, return_type)? {
+        // Tokens without a position have their line set to 0
+        let mut last_line = 0;
+        loop {
+            let line = self.tokens.front().map_or(0, |token| token.line);
+            let Some(statement) = self.read_statement(context, return_type)? else {
+                break;
+            };
+
             trace!("statement: {:?}", statement);
+            // Mark each statement starting on a new line
+            if line != last_line {
+                statements.push(Statement::Line(line));
+                last_line = line;
+            }
             statements.push(statement);
         }
 
@@ -1963,6 +2162,7 @@ impl<'a> Parser<'a> {
         }
 
         let has_return_type = return_type.is_some();
+        let names = FunctionNames::new(name, parameters.iter().map(|(name, _)| name.to_string()).collect());
         let mut new_params = Vec::with_capacity(parameters.len());
         for (name, param_type) in parameters {
             let id = context.register_variable(name, param_type.clone())
@@ -1971,7 +2171,7 @@ impl<'a> Parser<'a> {
         }
 
         let function = match kind {
-            FunctionKind::Entry => FunctionType::Entry(EntryFunction::new(new_params, Vec::new(), context.max_variables_count() as u16)),
+            FunctionKind::Entry => FunctionType::Entry(EntryFunction::new(names, new_params, Vec::new(), return_type.clone(), context.max_variables_count() as u16)),
             FunctionKind::Declared => FunctionType::Declared(DeclaredFunction::new(
                 for_type,
                 instance_name,
@@ -1981,6 +2181,7 @@ impl<'a> Parser<'a> {
                 0
             )),
             FunctionKind::Hook => FunctionType::Hook(HookFunction::new(
+                names,
                 new_params,
                 Vec::new(),
                 return_type.clone(),
@@ -2063,6 +2264,7 @@ impl<'a> Parser<'a> {
         trace!("Check if name is available: {}", name);
         self.global_mapper.structs().get_by_name(name).is_err()
             && self.global_mapper.enums().get_by_name(name).is_err()
+            && !self.events.contains_key(name)
     }
 
     /**
@@ -2191,6 +2393,8 @@ impl<'a> Parser<'a> {
                     continue;
                 }
                 Token::Const => self.read_const(&mut context)?,
+                Token::Storage => self.read_storage()?,
+                Token::Event => self.read_event()?,
                 Token::Function => self.read_function(FunctionKind::Declared, &mut context)?,
                 Token::Entry => self.read_function(FunctionKind::Entry, &mut context)?,
                 Token::Hook => self.read_function(FunctionKind::Hook, &mut context)?,
@@ -2200,7 +2404,15 @@ impl<'a> Parser<'a> {
             };
         }
 
-        let program = Program::with(self.constants.into_iter().map(|(_, v)| v).collect(), self.global_mapper.structs().finalize(), self.global_mapper.enums().finalize(), self.functions);
+        let mut program = Program::with(self.constants.into_iter().map(|(_, v)| v).collect(), self.global_mapper.structs().finalize(), self.global_mapper.enums().finalize(), self.functions);
+        for storage in self.storage.into_values() {
+            program.add_storage(storage);
+        }
+
+        for event in self.events.into_values() {
+            program.add_event(event);
+        }
+
         Ok((program, self.global_mapper))
     }
 }
@@ -2876,6 +3088,112 @@ mod tests {
         assert_eq!(program.functions().len(), 1);
     }
 
+    #[test]
+    fn test_storage() {
+        // storage supply: u64
+        // entry foo() { supply.set(supply.get().unwrap() + 1); return 0 }
+        let mut tokens = vec![
+            Token::Storage,
+            Token::Identifier("supply"),
+            Token::Colon,
+            Token::Number(NumberType::U64),
+            Token::Entry,
+            Token::Identifier("foo"),
+            Token::ParenthesisOpen,
+            Token::ParenthesisClose,
+            Token::BraceOpen,
+            Token::Identifier("supply"),
+            Token::Dot,
+            Token::Identifier("set"),
+            Token::ParenthesisOpen,
+            Token::Identifier("supply"),
+            Token::Dot,
+            Token::Identifier("get"),
+            Token::ParenthesisOpen,
+            Token::ParenthesisClose,
+            Token::Dot,
+            Token::Identifier("unwrap"),
+            Token::ParenthesisOpen,
+            Token::ParenthesisClose,
+            Token::OperatorPlus,
+            Token::Value(Literal::U64(1)),
+            Token::ParenthesisClose,
+            Token::Return,
+            Token::Value(Literal::U64(0)),
+            Token::BraceClose
+        ];
+
+        let program = test_parser(tokens.clone());
+        assert_eq!(program.storage(), [StorageDeclaration { name: "supply".to_owned(), value_type: Type::U64 }].as_slice());
+
+        // Only the storage operations can be used
+        tokens[11] = Token::Identifier("push");
+        let env = EnvironmentBuilder::default();
+        let err = Parser::new(tokens, &env).parse().unwrap_err();
+        assert!(matches!(err.kind, ParserErrorKind::UnknownStorageOperation("push")));
+    }
+
+    #[test]
+    fn test_event() {
+        // event Paused { at: u64 }
+        // entry foo() { Paused.emit(1); return 0 }
+        let mut tokens = vec![
+            Token::Event,
+            Token::Identifier("Paused"),
+            Token::BraceOpen,
+            Token::Identifier("at"),
+            Token::Colon,
+            Token::Number(NumberType::U64),
+            Token::BraceClose,
+            Token::Entry,
+            Token::Identifier("foo"),
+            Token::ParenthesisOpen,
+            Token::ParenthesisClose,
+            Token::BraceOpen,
+            Token::Identifier("Paused"),
+            Token::Dot,
+            Token::Identifier("emit"),
+            Token::ParenthesisOpen,
+            Token::Value(Literal::U64(1)),
+            Token::ParenthesisClose,
+            Token::Return,
+            Token::Value(Literal::U64(0)),
+            Token::BraceClose
+        ];
+
+        let program = test_parser(tokens.clone());
+        assert_eq!(program.events(), [EventDeclaration { name: "Paused".to_owned(), fields: vec![("at".to_owned(), Type::U64)] }].as_slice());
+
+        // All the fields must be given
+        tokens.remove(16);
+        let env = EnvironmentBuilder::default();
+        let err = Parser::new(tokens, &env).parse().unwrap_err();
+        assert!(matches!(err.kind, ParserErrorKind::InvalidEventParameters("Paused", 0, 1)));
+
+        // The native behind declared events can't be called directly
+        // entry foo() { emit_event(0, [true]); return 0 }
+        let tokens = vec![
+            Token::Entry,
+            Token::Identifier("foo"),
+            Token::ParenthesisOpen,
+            Token::ParenthesisClose,
+            Token::BraceOpen,
+            Token::Identifier("emit_event"),
+            Token::ParenthesisOpen,
+            Token::Value(Literal::U16(0)),
+            Token::Comma,
+            Token::BracketOpen,
+            Token::Value(Literal::Bool(true)),
+            Token::BracketClose,
+            Token::ParenthesisClose,
+            Token::Return,
+            Token::Value(Literal::U64(0)),
+            Token::BraceClose
+        ];
+        let err = Parser::new(tokens, &env).parse().unwrap_err();
+        assert!(matches!(err.kind, ParserErrorKind::EventSyscallNotCallable));
+    }
+
 
     #[test]
     fn test_hook_function() {
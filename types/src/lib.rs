@@ -2,6 +2,8 @@ mod types;
 mod values;
 mod u256;
 
+pub mod serializer;
+
 use std::{
     collections::HashMap,
     hash::{
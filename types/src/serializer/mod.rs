@@ -0,0 +1,36 @@
+mod reader;
+mod writer;
+mod values;
+mod types;
+
+pub use reader::*;
+pub use writer::*;
+pub use values::*;
+pub use types::*;
+
+// Canonical binary encoding of a type
+// Decoding must be strict: any value that was not produced
+// by `write` must be rejected by `read`
+pub trait Serializer: Sized {
+    // Write the value into the writer
+    fn write(&self, writer: &mut Writer);
+
+    // Read a value from the reader
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError>;
+
+    // Serialize the value into bytes
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    // Deserialize a value from bytes
+    // All the bytes must be consumed
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ReaderError> {
+        let mut reader = Reader::new(bytes);
+        let value = Self::read(&mut reader)?;
+        reader.expect_end()?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,254 @@
+use thiserror::Error;
+use crate::opaque::OpaqueRegistry;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReaderError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    #[error("input of {0} bytes exceeds the limit of {1} bytes")]
+    SizeLimit(usize, usize),
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("invalid boolean value {0}")]
+    InvalidBool(u8),
+    #[error("invalid varint")]
+    InvalidVarint,
+    #[error("invalid UTF-8 string")]
+    InvalidString,
+    #[error("invalid tag {0}")]
+    InvalidTag(u8),
+    #[error("invalid value")]
+    InvalidValue,
+    #[error("duplicated entry")]
+    DuplicatedEntry,
+    #[error("max depth reached")]
+    MaxDepthReached,
+    #[error("max memory reached: {0} > {1}")]
+    MaxMemoryReached(usize, usize),
+    #[error("invalid magic header")]
+    InvalidMagic,
+    #[error("unsupported format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("unknown opaque type {0}")]
+    UnknownOpaque(String),
+    #[error("invalid opaque {0}")]
+    InvalidOpaque(String),
+}
+
+// Reader over a byte slice used to decode values
+// Every read is bounds checked and lengths are verified
+// against the remaining bytes before allocating
+#[derive(Debug)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    // Registry used to decode the opaques
+    // If not set, any opaque fails to decode with UnknownOpaque
+    opaque_registry: Option<&'a OpaqueRegistry>
+}
+
+impl<'a> Reader<'a> {
+    // Create a new reader over the given bytes
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            opaque_registry: None
+        }
+    }
+
+    // Set the registry used to decode the opaques
+    pub fn with_opaque_registry(mut self, registry: &'a OpaqueRegistry) -> Self {
+        self.opaque_registry = Some(registry);
+        self
+    }
+
+    // Get the registry used to decode the opaques
+    #[inline]
+    pub fn opaque_registry(&self) -> Option<&'a OpaqueRegistry> {
+        self.opaque_registry
+    }
+
+    // Create a new reader only if the input doesn't exceed the limit
+    pub fn with_limit(bytes: &'a [u8], max_size: usize) -> Result<Self, ReaderError> {
+        if bytes.len() > max_size {
+            return Err(ReaderError::SizeLimit(bytes.len(), max_size));
+        }
+
+        Ok(Self::new(bytes))
+    }
+
+    // Count of bytes read so far
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Count of bytes not yet read
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    // Verify that all the bytes have been consumed
+    pub fn expect_end(&self) -> Result<(), ReaderError> {
+        match self.remaining() {
+            0 => Ok(()),
+            n => Err(ReaderError::TrailingBytes(n))
+        }
+    }
+
+    // Read N bytes
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], ReaderError> {
+        if n > self.remaining() {
+            return Err(ReaderError::UnexpectedEnd);
+        }
+
+        let bytes = &self.bytes[self.position..self.position + n];
+        self.position += n;
+        Ok(bytes)
+    }
+
+    // Read a fixed size array
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], ReaderError> {
+        let bytes = self.read_bytes(N)?;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok(array)
+    }
+
+    #[inline]
+    pub fn read_u8(&mut self) -> Result<u8, ReaderError> {
+        self.read_array::<1>().map(|v| v[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, ReaderError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(ReaderError::InvalidBool(v))
+        }
+    }
+
+    #[inline]
+    pub fn read_u16(&mut self) -> Result<u16, ReaderError> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    #[inline]
+    pub fn read_u32(&mut self) -> Result<u32, ReaderError> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    #[inline]
+    pub fn read_u64(&mut self) -> Result<u64, ReaderError> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    #[inline]
+    pub fn read_u128(&mut self) -> Result<u128, ReaderError> {
+        self.read_array().map(u128::from_le_bytes)
+    }
+
+    // Read an unsigned LEB128 variable length integer
+    // Overlong encodings and overflows are rejected
+    // so each value has exactly one valid encoding
+    pub fn read_varint(&mut self) -> Result<u64, ReaderError> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            let bits = (byte & 0x7F) as u64;
+            if shift == 63 && bits > 1 {
+                return Err(ReaderError::InvalidVarint);
+            }
+
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                // A trailing zero byte is an overlong encoding
+                if byte == 0 && shift > 0 {
+                    return Err(ReaderError::InvalidVarint);
+                }
+
+                return Ok(value);
+            }
+
+            shift += 7;
+            if shift > 63 {
+                return Err(ReaderError::InvalidVarint);
+            }
+        }
+    }
+
+    // Read a varint used as a length or an index
+    // A length can't be bigger than the remaining bytes
+    // as each element takes at least one byte
+    pub fn read_len(&mut self) -> Result<usize, ReaderError> {
+        let len = self.read_varint()?;
+        if len > self.remaining() as u64 {
+            return Err(ReaderError::UnexpectedEnd);
+        }
+
+        Ok(len as usize)
+    }
+
+    // Read a varint and convert it to usize
+    pub fn read_index(&mut self) -> Result<usize, ReaderError> {
+        self.read_varint()?
+            .try_into()
+            .map_err(|_| ReaderError::InvalidVarint)
+    }
+
+    // Read bytes prefixed by their length
+    pub fn read_sized_bytes(&mut self) -> Result<&'a [u8], ReaderError> {
+        let len = self.read_len()?;
+        self.read_bytes(len)
+    }
+
+    // Read an UTF-8 string prefixed by its length
+    pub fn read_string(&mut self) -> Result<String, ReaderError> {
+        let bytes = self.read_sized_bytes()?;
+        std::str::from_utf8(bytes)
+            .map(str::to_owned)
+            .map_err(|_| ReaderError::InvalidString)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serializer::Writer;
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut writer = Writer::new();
+            writer.write_varint(value);
+
+            let bytes = writer.into_bytes();
+            let mut reader = Reader::new(&bytes);
+            assert_eq!(reader.read_varint(), Ok(value));
+            assert!(reader.expect_end().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_invalid_varint() {
+        // Overlong encoding of 0
+        assert_eq!(Reader::new(&[0x80, 0x00]).read_varint(), Err(ReaderError::InvalidVarint));
+        // Overflow
+        assert_eq!(Reader::new(&[0xFF; 10]).read_varint(), Err(ReaderError::InvalidVarint));
+        assert_eq!(Reader::new(&[0xFF; 11]).read_varint(), Err(ReaderError::InvalidVarint));
+        // Missing bytes
+        assert_eq!(Reader::new(&[0x80]).read_varint(), Err(ReaderError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_strict_reads() {
+        assert_eq!(Reader::new(&[2]).read_bool(), Err(ReaderError::InvalidBool(2)));
+        assert_eq!(Reader::new(&[5, 1]).read_sized_bytes(), Err(ReaderError::UnexpectedEnd));
+        assert_eq!(Reader::new(&[2, 0xFF, 0xFF]).read_string(), Err(ReaderError::InvalidString));
+        assert_eq!(Reader::with_limit(&[0; 4], 3).unwrap_err(), ReaderError::SizeLimit(4, 3));
+        assert_eq!(Reader::new(&[0]).expect_end(), Err(ReaderError::TrailingBytes(1)));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{EnumType, EnumVariant, OpaqueType, StructType, Type};
+use super::{Reader, ReaderError, Serializer, Writer};
+
+// Maximum depth allowed while decoding a type
+pub const MAX_TYPE_DEPTH: usize = 32;
+
+// Tags used to identify each type
+const ANY: u8 = 0;
+const GENERIC: u8 = 1;
+const U8: u8 = 2;
+const U16: u8 = 3;
+const U32: u8 = 4;
+const U64: u8 = 5;
+const U128: u8 = 6;
+const U256: u8 = 7;
+const STRING: u8 = 8;
+const BOOL: u8 = 9;
+const BYTES: u8 = 10;
+const ARRAY: u8 = 11;
+const OPTIONAL: u8 = 12;
+const RANGE: u8 = 13;
+const MAP: u8 = 14;
+const STRUCT: u8 = 15;
+const ENUM: u8 = 16;
+const OPAQUE: u8 = 17;
+
+// Write a list of types prefixed by its length
+fn write_types(types: &[Type], writer: &mut Writer) {
+    writer.write_varint(types.len() as u64);
+    for ty in types {
+        ty.write(writer);
+    }
+}
+
+// Read a list of types prefixed by its length
+fn read_types(reader: &mut Reader, depth: usize) -> Result<Vec<Type>, ReaderError> {
+    let len = reader.read_len()?;
+    let mut types = Vec::with_capacity(len);
+    for _ in 0..len {
+        types.push(read_type(reader, depth)?);
+    }
+
+    Ok(types)
+}
+
+fn read_type(reader: &mut Reader, depth: usize) -> Result<Type, ReaderError> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err(ReaderError::MaxDepthReached);
+    }
+
+    Ok(match reader.read_u8()? {
+        ANY => Type::Any,
+        GENERIC => Type::T(reader.read_u8()?),
+        U8 => Type::U8,
+        U16 => Type::U16,
+        U32 => Type::U32,
+        U64 => Type::U64,
+        U128 => Type::U128,
+        U256 => Type::U256,
+        STRING => Type::String,
+        BOOL => Type::Bool,
+        BYTES => Type::Bytes,
+        ARRAY => Type::Array(Box::new(read_type(reader, depth + 1)?)),
+        OPTIONAL => Type::Optional(Box::new(read_type(reader, depth + 1)?)),
+        RANGE => Type::Range(Box::new(read_type(reader, depth + 1)?)),
+        MAP => {
+            let key = read_type(reader, depth + 1)?;
+            let value = read_type(reader, depth + 1)?;
+            Type::Map(Box::new(key), Box::new(value))
+        },
+        STRUCT => {
+            let id = reader.read_u16()?;
+            let fields = read_types(reader, depth + 1)?;
+            Type::Struct(StructType::new(id, fields))
+        },
+        ENUM => {
+            let id = reader.read_u16()?;
+            let len = reader.read_len()?;
+            // An enum can't have more than 256 variants
+            if len > u8::MAX as usize + 1 {
+                return Err(ReaderError::InvalidValue);
+            }
+
+            let mut variants = Vec::with_capacity(len);
+            for _ in 0..len {
+                variants.push(EnumVariant::new(read_types(reader, depth + 1)?));
+            }
+
+            Type::Enum(EnumType::new(id, variants))
+        },
+        OPAQUE => Type::Opaque(OpaqueType::new(reader.read_u16()?)),
+        tag => return Err(ReaderError::InvalidTag(tag))
+    })
+}
+
+impl Serializer for Type {
+    // Structs and enums are fully encoded with their fields
+    // so the type can be checked without any other context
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            Type::Any => writer.write_u8(ANY),
+            Type::T(position) => {
+                writer.write_u8(GENERIC);
+                writer.write_u8(*position);
+            },
+            Type::U8 => writer.write_u8(U8),
+            Type::U16 => writer.write_u8(U16),
+            Type::U32 => writer.write_u8(U32),
+            Type::U64 => writer.write_u8(U64),
+            Type::U128 => writer.write_u8(U128),
+            Type::U256 => writer.write_u8(U256),
+            Type::String => writer.write_u8(STRING),
+            Type::Bool => writer.write_u8(BOOL),
+            Type::Bytes => writer.write_u8(BYTES),
+            Type::Array(inner) => {
+                writer.write_u8(ARRAY);
+                inner.write(writer);
+            },
+            Type::Optional(inner) => {
+                writer.write_u8(OPTIONAL);
+                inner.write(writer);
+            },
+            Type::Range(inner) => {
+                writer.write_u8(RANGE);
+                inner.write(writer);
+            },
+            Type::Map(key, value) => {
+                writer.write_u8(MAP);
+                key.write(writer);
+                value.write(writer);
+            },
+            Type::Struct(structure) => {
+                writer.write_u8(STRUCT);
+                writer.write_u16(structure.id());
+                write_types(structure.fields(), writer);
+            },
+            Type::Enum(enum_type) => {
+                writer.write_u8(ENUM);
+                writer.write_u16(enum_type.id());
+                writer.write_varint(enum_type.variants().len() as u64);
+                for variant in enum_type.variants() {
+                    write_types(variant.fields(), writer);
+                }
+            },
+            Type::Opaque(opaque) => {
+                writer.write_u8(OPAQUE);
+                writer.write_u16(opaque.id());
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        read_type(reader, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_types_roundtrip() {
+        let point = StructType::new(0, vec![Type::U64, Type::U64]);
+        let shape = EnumType::new(1, vec![
+            EnumVariant::new(Vec::new()),
+            EnumVariant::new(vec![Type::Struct(point.clone()), Type::Optional(Box::new(Type::U8))])
+        ]);
+
+        let types = [
+            Type::Any,
+            Type::T(1),
+            Type::U256,
+            Type::Bytes,
+            Type::Array(Box::new(Type::Range(Box::new(Type::U32)))),
+            Type::Map(Box::new(Type::String), Box::new(Type::Array(Box::new(Type::Bool)))),
+            Type::Struct(point),
+            Type::Enum(shape.clone()),
+            Type::Opaque(OpaqueType::new(3))
+        ];
+
+        for ty in types {
+            let bytes = ty.to_bytes();
+            assert_eq!(Type::from_bytes(&bytes).unwrap(), ty);
+        }
+
+        // Equality only compares the ids, check the variants too
+        let Type::Enum(decoded) = Type::from_bytes(&Type::Enum(shape.clone()).to_bytes()).unwrap() else {
+            panic!("expected an enum");
+        };
+        assert_eq!(decoded.variants(), shape.variants());
+    }
+
+    #[test]
+    fn test_invalid_types() {
+        assert_eq!(Type::from_bytes(&[255]), Err(ReaderError::InvalidTag(255)));
+        assert_eq!(Type::from_bytes(&[MAP, U8]), Err(ReaderError::UnexpectedEnd));
+
+        let bytes = [[ARRAY]; MAX_TYPE_DEPTH + 1].concat();
+        assert_eq!(Type::from_bytes(&[bytes.as_slice(), &[U8]].concat()), Err(ReaderError::MaxDepthReached));
+    }
+}
@@ -0,0 +1,392 @@
+use std::mem;
+
+use indexmap::IndexMap;
+use crate::{Primitive, ValueCell, U256};
+use super::{Reader, ReaderError, Serializer, Writer};
+
+// Default maximum depth allowed while decoding a value
+pub const DEFAULT_MAX_VALUE_DEPTH: usize = 16;
+
+// Default maximum memory usage allowed while decoding a value
+pub const DEFAULT_MAX_VALUE_MEMORY: usize = 1024 * 1024;
+
+// Tags used to identify each variant
+// ValueCell::Default is flattened to the Primitive tags
+const NULL: u8 = 0;
+const BOOL: u8 = 1;
+const U8: u8 = 2;
+const U16: u8 = 3;
+const U32: u8 = 4;
+const U64: u8 = 5;
+const U128: u8 = 6;
+const U256_TAG: u8 = 7;
+const STRING: u8 = 8;
+const RANGE: u8 = 9;
+const OPAQUE: u8 = 10;
+const BYTES: u8 = 11;
+const ARRAY: u8 = 12;
+const MAP: u8 = 13;
+
+// Read a bound of a range, only numbers are accepted
+// The tag is checked first so nested ranges are never decoded
+fn read_range_bound(reader: &mut Reader) -> Result<Primitive, ReaderError> {
+    let tag = reader.read_u8()?;
+    if !(U8..=U256_TAG).contains(&tag) {
+        return Err(ReaderError::InvalidValue);
+    }
+
+    read_primitive_with_tag(tag, reader)
+}
+
+fn read_primitive_with_tag(tag: u8, reader: &mut Reader) -> Result<Primitive, ReaderError> {
+    Ok(match tag {
+        NULL => Primitive::Null,
+        BOOL => Primitive::Boolean(reader.read_bool()?),
+        U8 => Primitive::U8(reader.read_u8()?),
+        U16 => Primitive::U16(reader.read_u16()?),
+        U32 => Primitive::U32(reader.read_u32()?),
+        U64 => Primitive::U64(reader.read_u64()?),
+        U128 => Primitive::U128(reader.read_u128()?),
+        U256_TAG => Primitive::U256(U256::from_le_bytes(reader.read_array()?)),
+        STRING => Primitive::String(reader.read_string()?),
+        RANGE => {
+            let start = read_range_bound(reader)?;
+            let end = read_range_bound(reader)?;
+            // Only ranges of the same number type can be created
+            if mem::discriminant(&start) != mem::discriminant(&end) {
+                return Err(ReaderError::InvalidValue);
+            }
+
+            Primitive::Range(Box::new((start, end)))
+        },
+        OPAQUE => {
+            let name = reader.read_string()?;
+            let bytes = reader.read_sized_bytes()?;
+
+            // Opaques can only be decoded using the registry of the reader
+            let result = reader.opaque_registry()
+                .and_then(|registry| registry.get_by_name(&name))
+                .ok_or_else(|| ReaderError::UnknownOpaque(name.clone()))?
+                .from_bytes(bytes);
+
+            // Re-encoding must produce the same bytes
+            // otherwise the encoding is not canonical
+            match result {
+                Ok(opaque) => {
+                    let mut buffer = Vec::with_capacity(bytes.len());
+                    opaque.inner().serialize(&mut buffer);
+                    if buffer != bytes {
+                        return Err(ReaderError::InvalidOpaque(name));
+                    }
+
+                    Primitive::Opaque(opaque)
+                },
+                Err(_) => return Err(ReaderError::InvalidOpaque(name))
+            }
+        },
+        tag => return Err(ReaderError::InvalidTag(tag))
+    })
+}
+
+impl Serializer for Primitive {
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            Primitive::Null => writer.write_u8(NULL),
+            Primitive::Boolean(v) => {
+                writer.write_u8(BOOL);
+                writer.write_bool(*v);
+            },
+            Primitive::U8(v) => {
+                writer.write_u8(U8);
+                writer.write_u8(*v);
+            },
+            Primitive::U16(v) => {
+                writer.write_u8(U16);
+                writer.write_u16(*v);
+            },
+            Primitive::U32(v) => {
+                writer.write_u8(U32);
+                writer.write_u32(*v);
+            },
+            Primitive::U64(v) => {
+                writer.write_u8(U64);
+                writer.write_u64(*v);
+            },
+            Primitive::U128(v) => {
+                writer.write_u8(U128);
+                writer.write_u128(*v);
+            },
+            Primitive::U256(v) => {
+                writer.write_u8(U256_TAG);
+                writer.write_bytes(&v.to_le_bytes());
+            },
+            Primitive::String(v) => {
+                writer.write_u8(STRING);
+                writer.write_string(v);
+            },
+            Primitive::Range(range) => {
+                writer.write_u8(RANGE);
+                range.0.write(writer);
+                range.1.write(writer);
+            },
+            Primitive::Opaque(opaque) => {
+                writer.write_u8(OPAQUE);
+                let inner = opaque.inner();
+                writer.write_string(inner.get_type_name());
+
+                let mut bytes = Vec::with_capacity(inner.get_size());
+                inner.serialize(&mut bytes);
+                writer.write_sized_bytes(&bytes);
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let tag = reader.read_u8()?;
+        read_primitive_with_tag(tag, reader)
+    }
+}
+
+// Decoder enforcing the depth and memory limits of a value
+// Memory is accounted the same way as `ValueCell::calculate_memory_usage`
+struct ValueDecoder {
+    max_depth: usize,
+    max_memory: usize,
+    memory: usize
+}
+
+impl ValueDecoder {
+    fn increase_memory(&mut self, amount: usize) -> Result<(), ReaderError> {
+        self.memory += amount;
+        if self.memory > self.max_memory {
+            return Err(ReaderError::MaxMemoryReached(self.memory, self.max_memory));
+        }
+
+        Ok(())
+    }
+
+    fn read_value(&mut self, reader: &mut Reader, depth: usize) -> Result<ValueCell, ReaderError> {
+        if depth > self.max_depth {
+            return Err(ReaderError::MaxDepthReached);
+        }
+
+        Ok(match reader.read_u8()? {
+            BYTES => {
+                let bytes = reader.read_sized_bytes()?;
+                self.increase_memory(32 + bytes.len())?;
+                ValueCell::Bytes(bytes.to_vec())
+            },
+            ARRAY => {
+                self.increase_memory(32)?;
+                let len = reader.read_len()?;
+                let mut values = Vec::with_capacity(len);
+                for _ in 0..len {
+                    values.push(self.read_value(reader, depth + 1)?);
+                }
+
+                ValueCell::Array(values)
+            },
+            MAP => {
+                self.increase_memory(64)?;
+                let len = reader.read_len()?;
+                let mut map = IndexMap::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.read_value(reader, depth + 1)?;
+                    if key.is_map() {
+                        return Err(ReaderError::InvalidValue);
+                    }
+
+                    let value = self.read_value(reader, depth + 1)?;
+                    if map.insert(key, value).is_some() {
+                        return Err(ReaderError::DuplicatedEntry);
+                    }
+                }
+
+                ValueCell::Map(map)
+            },
+            tag => {
+                let value = read_primitive_with_tag(tag, reader)?;
+                self.increase_memory(1 + value.get_memory_usage())?;
+                ValueCell::Default(value)
+            }
+        })
+    }
+}
+
+impl ValueCell {
+    // Read a value enforcing the given depth and memory limits
+    pub fn read_with_limits(reader: &mut Reader, max_depth: usize, max_memory: usize) -> Result<Self, ReaderError> {
+        let mut decoder = ValueDecoder {
+            max_depth,
+            max_memory,
+            memory: 0
+        };
+
+        decoder.read_value(reader, 0)
+    }
+
+    // Decode a value from bytes enforcing the given depth and memory limits
+    // All the bytes must be consumed
+    pub fn from_bytes_with_limits(bytes: &[u8], max_depth: usize, max_memory: usize) -> Result<Self, ReaderError> {
+        let mut reader = Reader::new(bytes);
+        let value = Self::read_with_limits(&mut reader, max_depth, max_memory)?;
+        reader.expect_end()?;
+        Ok(value)
+    }
+}
+
+impl Serializer for ValueCell {
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            ValueCell::Default(v) => v.write(writer),
+            ValueCell::Bytes(bytes) => {
+                writer.write_u8(BYTES);
+                writer.write_sized_bytes(bytes);
+            },
+            ValueCell::Array(values) => {
+                writer.write_u8(ARRAY);
+                writer.write_varint(values.len() as u64);
+                for value in values {
+                    value.write(writer);
+                }
+            },
+            ValueCell::Map(map) => {
+                writer.write_u8(MAP);
+                writer.write_varint(map.len() as u64);
+                for (key, value) in map {
+                    key.write(writer);
+                    value.write(writer);
+                }
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Self::read_with_limits(reader, DEFAULT_MAX_VALUE_DEPTH, DEFAULT_MAX_VALUE_MEMORY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use serde::{Deserialize, Serialize};
+    use crate::{impl_opaque, opaque::{traits::Serializable, OpaqueRegistry, OpaqueWrapper}};
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    struct Counter(u32);
+
+    impl_opaque!("Counter", Counter, display, json);
+
+    impl fmt::Display for Counter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Counter({})", self.0)
+        }
+    }
+
+    impl Serializable for Counter {
+        fn get_size(&self) -> usize {
+            4
+        }
+
+        fn serialize(&self, buffer: &mut Vec<u8>) -> usize {
+            buffer.extend_from_slice(&self.0.to_le_bytes());
+            4
+        }
+
+        fn is_serializable(&self) -> bool {
+            true
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+            Ok(Self(u32::from_le_bytes(bytes.try_into()?)))
+        }
+    }
+
+    #[test]
+    fn test_values_roundtrip() {
+        let mut map = IndexMap::new();
+        map.insert(Primitive::String("a".to_owned()).into(), ValueCell::Bytes(vec![1, 2, 3]));
+        map.insert(Primitive::U8(1).into(), ValueCell::Array(vec![Primitive::Null.into()]));
+
+        let values = [
+            Primitive::Null.into(),
+            Primitive::Boolean(true).into(),
+            Primitive::U64(42).into(),
+            Primitive::U256(U256::MAX).into(),
+            Primitive::Range(Box::new((Primitive::U32(1), Primitive::U32(5)))).into(),
+            ValueCell::Map(map)
+        ];
+
+        for value in values {
+            let bytes = value.to_bytes();
+            assert_eq!(ValueCell::from_bytes(&bytes).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        // Unknown tag
+        assert_eq!(ValueCell::from_bytes(&[255]), Err(ReaderError::InvalidTag(255)));
+        // Range of mixed types
+        assert_eq!(ValueCell::from_bytes(&[RANGE, U8, 1, BOOL, 1]), Err(ReaderError::InvalidValue));
+        // Duplicated map key
+        assert_eq!(
+            ValueCell::from_bytes(&[MAP, 2, NULL, NULL, NULL, NULL]),
+            Err(ReaderError::DuplicatedEntry)
+        );
+        // Nested ranges are rejected without recursing
+        let bytes = vec![RANGE; 500_000];
+        assert_eq!(ValueCell::from_bytes(&bytes), Err(ReaderError::InvalidValue));
+        assert_eq!(Primitive::from_bytes(&bytes), Err(ReaderError::InvalidValue));
+        // Too deep
+        let bytes = [[ARRAY, 1]; DEFAULT_MAX_VALUE_DEPTH + 1].concat();
+        assert_eq!(ValueCell::from_bytes(&[bytes.as_slice(), &[NULL]].concat()), Err(ReaderError::MaxDepthReached));
+    }
+
+    #[test]
+    fn test_limits() {
+        let value = ValueCell::Array(vec![ValueCell::Array(vec![Primitive::U64(1).into()])]);
+        let bytes = value.to_bytes();
+
+        assert_eq!(ValueCell::from_bytes_with_limits(&bytes, 1, 1024), Err(ReaderError::MaxDepthReached));
+        assert_eq!(ValueCell::from_bytes_with_limits(&bytes, 2, 64), Err(ReaderError::MaxMemoryReached(73, 64)));
+        assert_eq!(ValueCell::from_bytes_with_limits(&bytes, 2, 73), Ok(value));
+    }
+
+    #[test]
+    fn test_opaque_with_registry() {
+        let value: ValueCell = Primitive::Opaque(OpaqueWrapper::new(Counter(7))).into();
+        let bytes = value.to_bytes();
+
+        let mut registry = OpaqueRegistry::new();
+        registry.register::<Counter>();
+
+        let mut reader = Reader::new(&bytes).with_opaque_registry(&registry);
+        assert_eq!(ValueCell::read(&mut reader), Ok(value));
+
+        let empty = OpaqueRegistry::new();
+        let mut reader = Reader::new(&bytes).with_opaque_registry(&empty);
+        assert_eq!(ValueCell::read(&mut reader), Err(ReaderError::UnknownOpaque("Counter".to_owned())));
+    }
+
+    #[test]
+    fn test_opaque() {
+        let value: ValueCell = Primitive::Opaque(OpaqueWrapper::new(Counter(42))).into();
+        let bytes = value.to_bytes();
+
+        // No registry to decode it
+        assert_eq!(ValueCell::from_bytes(&bytes), Err(ReaderError::UnknownOpaque("Counter".to_owned())));
+
+        let mut registry = OpaqueRegistry::new();
+        registry.register::<Counter>();
+
+        // Invalid opaque bytes
+        let mut invalid = bytes.clone();
+        invalid.pop();
+        let len = invalid.len();
+        invalid[len - 4] = 3;
+        let mut reader = Reader::new(&invalid).with_opaque_registry(&registry);
+        assert_eq!(ValueCell::read(&mut reader), Err(ReaderError::InvalidOpaque("Counter".to_owned())));
+    }
+}
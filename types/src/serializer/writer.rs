@@ -0,0 +1,102 @@
+// Writer used to encode values in their canonical binary format
+// All fixed-size integers are written in little endian
+#[derive(Debug, Default)]
+pub struct Writer {
+    bytes: Vec<u8>
+}
+
+impl Writer {
+    // Create a new empty writer
+    pub fn new() -> Self {
+        Self {
+            bytes: Vec::new()
+        }
+    }
+
+    // Create a new writer with a pre-allocated capacity
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bytes: Vec::with_capacity(capacity)
+        }
+    }
+
+    #[inline]
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    #[inline]
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    #[inline]
+    pub fn write_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn write_u128(&mut self, value: u128) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // Write an unsigned LEB128 variable length integer
+    pub fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    // Write raw bytes without any length prefix
+    #[inline]
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    // Write bytes prefixed by their length as a varint
+    pub fn write_sized_bytes(&mut self, bytes: &[u8]) {
+        self.write_varint(bytes.len() as u64);
+        self.write_bytes(bytes);
+    }
+
+    // Write an UTF-8 string prefixed by its length
+    #[inline]
+    pub fn write_string(&mut self, value: &str) {
+        self.write_sized_bytes(value.as_bytes());
+    }
+
+    // Get the bytes written so far
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    // Count of bytes written
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    // Consume the writer and return the bytes
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
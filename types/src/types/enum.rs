@@ -22,12 +22,76 @@ impl EnumVariant {
     }
 }
 
+// Names of an enum variant and its fields
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct EnumVariantNames {
+    name: String,
+    fields: Vec<String>
+}
+
+impl EnumVariantNames {
+    // Create the names of a variant
+    // Fields names must be in the same order as the fields
+    pub fn new(name: impl Into<String>, fields: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields
+        }
+    }
+
+    // Get the name of the variant
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Get the names of the fields
+    #[inline(always)]
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+}
+
+// Names of an enum and its variants
+// This is only used for debugging and display purposes
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct EnumNames {
+    name: String,
+    variants: Vec<EnumVariantNames>
+}
+
+impl EnumNames {
+    // Create the names of an enum
+    // Variants names must be in the same order as the variants
+    pub fn new(name: impl Into<String>, variants: Vec<EnumVariantNames>) -> Self {
+        Self {
+            name: name.into(),
+            variants
+        }
+    }
+
+    // Get the name of the enum
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Get the names of the variants
+    #[inline(always)]
+    pub fn variants(&self) -> &[EnumVariantNames] {
+        &self.variants
+    }
+}
+
 // Represents an enum like in Rust with variants
 // Support up to 255 variants
 #[derive(Clone, Eq, Debug, Serialize, Deserialize)]
 pub struct Enum {
     id: IdentifierType,
     variants: Vec<EnumVariant>,
+    // Optional names, not used for the identity of the enum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    names: Option<EnumNames>
 }
 
 impl Hash for Enum {
@@ -57,7 +121,12 @@ pub struct EnumValueType {
 impl EnumType {
     // Create a new enum type
     pub fn new(id: IdentifierType, variants: Vec<EnumVariant>) -> Self {
-        Self(Arc::new(Enum { id, variants }))
+        Self(Arc::new(Enum { id, variants, names: None }))
+    }
+
+    // Create a new enum type with its names
+    pub fn with_names(id: IdentifierType, variants: Vec<EnumVariant>, names: EnumNames) -> Self {
+        Self(Arc::new(Enum { id, variants, names: Some(names) }))
     }
 
     // Get the unique identifier of the enum
@@ -77,6 +146,12 @@ impl EnumType {
     pub fn get_variant(&self, id: u8) -> Option<&EnumVariant> {
         self.0.variants.get(id as usize)
     }
+
+    // Get the names of the enum and its variants if registered
+    #[inline(always)]
+    pub fn names(&self) -> Option<&EnumNames> {
+        self.0.names.as_ref()
+    }
 }
 
 impl EnumValueType {
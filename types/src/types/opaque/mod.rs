@@ -1,4 +1,5 @@
 pub mod traits;
+mod registry;
 
 use core::fmt;
 use std::{
@@ -11,6 +12,8 @@ use serde_json::Value;
 use crate::{IdentifierType, ValueError};
 use traits::*;
 
+pub use registry::*;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct OpaqueType(IdentifierType);
 
@@ -243,6 +246,25 @@ mod tests {
         assert_eq!(json, r#"{"type":"opaque","value":42}"#);
     }
 
+    #[test]
+    fn test_opaque_type_name() {
+        struct Manual;
+
+        // Implemented without `impl_opaque!`
+        impl DynType for Manual {
+            fn get_type_name(&self) -> &'static str {
+                "Manual"
+            }
+
+            fn get_type(&self) -> TypeId {
+                TypeId::of::<Manual>()
+            }
+        }
+
+        assert!(Manual::type_name().ends_with("Manual"));
+        assert_eq!(CustomOpaque::type_name(), "CustomOpaque");
+    }
+
     #[test]
     fn test_opaque_downcast() {
         let mut opaque = OpaqueWrapper::new(CustomOpaque { value: 42 });
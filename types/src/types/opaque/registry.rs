@@ -0,0 +1,183 @@
+use std::any::TypeId;
+use anyhow::{anyhow, Context};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::IdentifierType;
+use super::{traits::{JSONHelper, Serializable}, Opaque, OpaqueType, OpaqueWrapper};
+
+pub type BinaryOpaqueFn = fn(&[u8]) -> Result<OpaqueWrapper, anyhow::Error>;
+pub type JSONOpaqueFn = fn(Value) -> Result<OpaqueWrapper, anyhow::Error>;
+
+fn deserialize_binary<T: Opaque>(bytes: &[u8]) -> Result<OpaqueWrapper, anyhow::Error> {
+    <T as Serializable>::deserialize(bytes).map(OpaqueWrapper::new)
+}
+
+fn deserialize_json<T: Opaque>(value: Value) -> Result<OpaqueWrapper, anyhow::Error> {
+    <T as JSONHelper>::deserialize_json(value).map(OpaqueWrapper::new)
+}
+
+// Deserialization functions of a registered opaque type
+#[derive(Debug, Clone)]
+pub struct OpaqueDeserializer {
+    type_id: TypeId,
+    binary: BinaryOpaqueFn,
+    json: JSONOpaqueFn
+}
+
+impl OpaqueDeserializer {
+    // TypeId of the registered opaque
+    #[inline]
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    // Deserialize the opaque from its binary format
+    #[inline]
+    pub fn from_bytes(&self, bytes: &[u8]) -> Result<OpaqueWrapper, anyhow::Error> {
+        (self.binary)(bytes)
+    }
+
+    // Deserialize the opaque from its JSON value
+    #[inline]
+    pub fn from_json(&self, value: Value) -> Result<OpaqueWrapper, anyhow::Error> {
+        (self.json)(value)
+    }
+}
+
+// Registry of all the opaque types that can be deserialized
+// Each opaque is keyed by its type name, the one written in the binary and JSON formats,
+// and its id is the registration order
+// which is the same as the OpaqueType id given by the builder
+#[derive(Debug, Clone, Default)]
+pub struct OpaqueRegistry {
+    entries: IndexMap<String, OpaqueDeserializer>
+}
+
+impl OpaqueRegistry {
+    // Create a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Register an opaque type under its type name
+    // Returns false if the name is already registered
+    pub fn register<T: Opaque>(&mut self) -> bool {
+        let name = T::type_name();
+        if self.entries.contains_key(name) {
+            return false;
+        }
+
+        self.entries.insert(name.to_owned(), OpaqueDeserializer {
+            type_id: TypeId::of::<T>(),
+            binary: deserialize_binary::<T>,
+            json: deserialize_json::<T>
+        });
+
+        true
+    }
+
+    // Get the deserializer of an opaque by its type name
+    #[inline]
+    pub fn get_by_name(&self, name: &str) -> Option<&OpaqueDeserializer> {
+        self.entries.get(name)
+    }
+
+    // Get the deserializer of an opaque by its id
+    #[inline]
+    pub fn get_by_id(&self, id: IdentifierType) -> Option<&OpaqueDeserializer> {
+        self.entries.get_index(id as usize)
+            .map(|(_, v)| v)
+    }
+
+    // Get the deserializer of an opaque type
+    #[inline]
+    pub fn get_by_type(&self, ty: &OpaqueType) -> Option<&OpaqueDeserializer> {
+        self.get_by_id(ty.id())
+    }
+
+    // Deserialize an opaque from its binary format
+    pub fn from_bytes(&self, name: &str, bytes: &[u8]) -> Result<OpaqueWrapper, anyhow::Error> {
+        self.get_by_name(name)
+            .ok_or_else(|| anyhow!("Unknown opaque type {}", name))?
+            .from_bytes(bytes)
+    }
+
+    // Deserialize an opaque from the JSON produced by the OpaqueWrapper serialization
+    // Expected format is {"type": name, "value": value}
+    pub fn from_json(&self, value: Value) -> Result<OpaqueWrapper, anyhow::Error> {
+        let Value::Object(mut map) = value else {
+            return Err(anyhow!("Expected a JSON object"));
+        };
+
+        let name = map.remove("type")
+            .context("Missing opaque type")?;
+        let name = name.as_str()
+            .context("Invalid opaque type")?;
+        let value = map.remove("value")
+            .context("Missing opaque value")?;
+
+        self.get_by_name(name)
+            .ok_or_else(|| anyhow!("Unknown opaque type {}", name))?
+            .from_json(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use serde::{Deserialize, Serialize};
+    use crate::impl_opaque;
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    struct Balance {
+        amount: u64
+    }
+
+    impl_opaque!("Balance", Balance, display, json);
+
+    impl fmt::Display for Balance {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Balance({})", self.amount)
+        }
+    }
+
+    impl Serializable for Balance {
+        fn serialize(&self, buffer: &mut Vec<u8>) -> usize {
+            buffer.extend_from_slice(&self.amount.to_le_bytes());
+            8
+        }
+
+        fn is_serializable(&self) -> bool {
+            true
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+            Ok(Self { amount: u64::from_le_bytes(bytes.try_into()?) })
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = OpaqueRegistry::new();
+        assert!(registry.register::<Balance>());
+        assert!(!registry.register::<Balance>());
+
+        let opaque = OpaqueWrapper::new(Balance { amount: 42 });
+        assert_eq!(registry.get_by_id(0).unwrap().type_id(), TypeId::of::<Balance>());
+        assert!(registry.get_by_id(1).is_none());
+
+        // Binary
+        let mut bytes = Vec::new();
+        opaque.inner().serialize(&mut bytes);
+        assert_eq!(registry.from_bytes("Balance", &bytes).unwrap(), opaque);
+        assert!(registry.from_bytes("Balance", &bytes[1..]).is_err());
+        assert!(registry.from_bytes("Unknown", &bytes).is_err());
+
+        // JSON
+        let json = serde_json::to_value(&opaque).unwrap();
+        assert_eq!(registry.from_json(json).unwrap(), opaque);
+        assert!(registry.from_json(serde_json::json!({ "type": "Unknown", "value": 0 })).is_err());
+    }
+}
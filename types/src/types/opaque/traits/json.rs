@@ -35,4 +35,13 @@ pub trait JSONHelper {
     fn is_json_supported(&self) -> bool {
         false
     }
+
+    // Deserialize the type from the JSON written by `serialize_json`
+    // By default, deserialization is not supported
+    fn deserialize_json(_: Value) -> Result<Self, anyhow::Error>
+    where
+        Self: Sized
+    {
+        Err(anyhow!("Deserialization not supported for this type"))
+    }
 }
\ No newline at end of file
@@ -19,6 +19,10 @@ macro_rules! impl_opaque {
             fn get_type_name(&self) -> &'static str {
                 $name
             }
+
+            fn type_name() -> &'static str {
+                $name
+            }
     
             fn get_type(&self) -> std::any::TypeId {
                 std::any::TypeId::of::<$type>()
@@ -61,6 +65,10 @@ macro_rules! impl_opaque {
             fn is_json_supported(&self) -> bool {
                 true
             }
+
+            fn deserialize_json(value: serde_json::Value) -> Result<Self, anyhow::Error> {
+                Ok(serde_json::from_value(value)?)
+            }
         }
     };
     // Combination: both display and json
@@ -1,3 +1,5 @@
+use anyhow::anyhow;
+
 pub trait Serializable {
     // Expected size of the type when serialized
     // Even if is_serializable returns false, this function should return the size of the type
@@ -16,4 +18,13 @@ pub trait Serializable {
     fn is_serializable(&self) -> bool {
         false
     }
-}
\ No newline at end of file
+
+    // Deserialize the type from the bytes written by `serialize`
+    // By default, deserialization is not supported
+    fn deserialize(_: &[u8]) -> Result<Self, anyhow::Error>
+    where
+        Self: Sized
+    {
+        Err(anyhow!("Deserialization not supported for this type"))
+    }
+}
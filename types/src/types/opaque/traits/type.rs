@@ -3,5 +3,11 @@ use std::any::TypeId;
 pub trait DynType {
     fn get_type_name(&self) -> &'static str;
 
+    // Name under which the type is registered to be decoded
+    // Types implemented with `impl_opaque!` use the same name as `get_type_name`
+    fn type_name() -> &'static str where Self: Sized {
+        std::any::type_name::<Self>()
+    }
+
     fn get_type(&self) -> TypeId;
 }
\ No newline at end of file
@@ -4,13 +4,47 @@ use serde::{Deserialize, Serialize};
 use crate::IdentifierType;
 use super::Type;
 
+// Names of a struct and its fields
+// This is only used for debugging and display purposes
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct StructNames {
+    name: String,
+    fields: Vec<String>
+}
+
+impl StructNames {
+    // Create the names of a struct
+    // Fields names must be in the same order as the fields
+    pub fn new(name: impl Into<String>, fields: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields
+        }
+    }
+
+    // Get the name of the struct
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Get the names of the fields
+    #[inline(always)]
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+}
+
 // Represents a struct in the language
 #[derive(Clone, Eq, Debug, Serialize, Deserialize)]
 pub struct Struct {
     // Unique identifier for serialization
     id: IdentifierType,
     // Fields of the struct
-    fields: Vec<Type>
+    fields: Vec<Type>,
+    // Optional names, not used for the identity of the struct
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    names: Option<StructNames>
 }
 
 impl Hash for Struct {
@@ -31,7 +65,12 @@ pub struct StructType(Arc<Struct>);
 impl StructType {
     /// Create a new struct type
     pub fn new(id: IdentifierType, fields: Vec<Type>) -> Self {
-        Self(Arc::new(Struct { id, fields }))
+        Self(Arc::new(Struct { id, fields, names: None }))
+    }
+
+    /// Create a new struct type with its names
+    pub fn with_names(id: IdentifierType, fields: Vec<Type>, names: StructNames) -> Self {
+        Self(Arc::new(Struct { id, fields, names: Some(names) }))
     }
 
     /// Get the unique identifier of the struct
@@ -45,6 +84,12 @@ impl StructType {
     pub fn fields(&self) -> &Vec<Type> {
         &self.0.fields
     }
+
+    /// Get the names of the struct and its fields if registered
+    #[inline(always)]
+    pub fn names(&self) -> Option<&StructNames> {
+        self.0.names.as_ref()
+    }
 }
 
 impl Serialize for StructType {
@@ -1,4 +1,5 @@
 mod stack_value;
+mod typed;
 
 use std::{
     borrow::Cow,
@@ -9,10 +10,11 @@ use std::{
 use indexmap::IndexMap;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use crate::{opaque::OpaqueWrapper, Opaque, Type, U256};
+use crate::{opaque::OpaqueWrapper, Opaque, OpaqueType, Type, U256};
 use super::{Constant, Primitive, ValueError};
 
 pub use stack_value::*;
+pub use typed::*;
 
 // Give inner mutability for values with inner types.
 // This is NOT thread-safe due to the RefCell usage.
@@ -219,6 +221,59 @@ impl ValueCell {
         Ok(memory)
     }
 
+    // Check if the value matches the expected type
+    // Structs and enums are represented as an array of their fields
+    // An enum value matches if any variant with the same fields count matches
+    // Opaque values are verified using the provided function
+    pub fn is_of_type<F: Fn(&OpaqueType, &OpaqueWrapper) -> bool>(&self, expected: &Type, is_opaque: &F) -> bool {
+        let mut stack = vec![(self, expected)];
+        while let Some((value, expected)) = stack.pop() {
+            let valid = match (value, expected) {
+                (_, Type::Any | Type::T(_)) => true,
+                (ValueCell::Default(Primitive::Null), Type::Optional(_)) => true,
+                (_, Type::Optional(inner)) => {
+                    stack.push((value, inner));
+                    true
+                },
+                (ValueCell::Default(Primitive::Range(range)), Type::Range(inner)) => [&range.0, &range.1].into_iter()
+                    .all(|bound| Type::from_value(bound).is_some_and(|ty| ty == **inner)),
+                (ValueCell::Default(Primitive::Opaque(opaque)), Type::Opaque(ty)) => is_opaque(ty, opaque),
+                (ValueCell::Default(v), _) => expected.is_primitive()
+                    && Type::from_value(v).is_some_and(|ty| ty == *expected),
+                (ValueCell::Bytes(_), Type::Bytes) => true,
+                (ValueCell::Array(values), Type::Array(inner)) => {
+                    stack.extend(values.iter().map(|value| (value, inner.as_ref())));
+                    true
+                },
+                (ValueCell::Array(values), Type::Struct(structure)) => {
+                    let fields = structure.fields();
+                    if values.len() == fields.len() {
+                        stack.extend(values.iter().zip(fields));
+                    }
+                    values.len() == fields.len()
+                },
+                (ValueCell::Array(values), Type::Enum(enum_type)) => enum_type.variants()
+                    .iter()
+                    .filter(|variant| variant.fields().len() == values.len())
+                    .any(|variant| values.iter()
+                        .zip(variant.fields())
+                        .all(|(value, ty)| value.is_of_type(ty, is_opaque))
+                    ),
+                (ValueCell::Map(map), Type::Map(key, inner)) => {
+                    stack.extend(map.iter().flat_map(|(k, v)| [(k, key.as_ref()), (v, inner.as_ref())]));
+                    true
+                },
+                _ => false
+            };
+
+            if !valid {
+                return false;
+            }
+        }
+
+        true
+    }
+
     #[inline]
     pub fn is_null(&self) -> bool {
         match &self {
@@ -767,6 +822,52 @@ impl Clone for ValueCell {
     }
 }
 
+#[cfg(test)]
+mod type_tests {
+    use crate::{EnumType, EnumVariant, StructType};
+    use super::*;
+
+    #[test]
+    fn test_is_of_type() {
+        let no_opaque = |_: &OpaqueType, _: &OpaqueWrapper| false;
+        let point = StructType::new(0, vec![Type::U64, Type::Optional(Box::new(Type::String))]);
+        let shape = EnumType::new(1, vec![
+            EnumVariant::new(Vec::new()),
+            EnumVariant::new(vec![Type::Struct(point.clone()), Type::U8])
+        ]);
+
+        let value = ValueCell::Array(vec![Primitive::U64(1).into(), Primitive::Null.into()]);
+        assert!(value.is_of_type(&Type::Struct(point.clone()), &no_opaque));
+        assert!(!value.is_of_type(&Type::Array(Box::new(Type::U64)), &no_opaque));
+        assert!(!ValueCell::Array(vec![Primitive::U64(1).into()]).is_of_type(&Type::Struct(point.clone()), &no_opaque));
+
+        assert!(ValueCell::Array(Vec::new()).is_of_type(&Type::Enum(shape.clone()), &no_opaque));
+        let circle = ValueCell::Array(vec![value, Primitive::U8(3).into()]);
+        assert!(circle.is_of_type(&Type::Enum(shape.clone()), &no_opaque));
+        let invalid = ValueCell::Array(vec![Primitive::U8(3).into(), Primitive::U8(3).into()]);
+        assert!(!invalid.is_of_type(&Type::Enum(shape), &no_opaque));
+
+        let mut map = IndexMap::new();
+        map.insert(Primitive::String("a".to_owned()).into(), Primitive::U32(1).into());
+        let map_type = Type::Map(Box::new(Type::String), Box::new(Type::U32));
+        assert!(ValueCell::Map(map.clone()).is_of_type(&map_type, &no_opaque));
+        map.insert(Primitive::String("b".to_owned()).into(), Primitive::U64(1).into());
+        assert!(!ValueCell::Map(map).is_of_type(&map_type, &no_opaque));
+
+        let range: ValueCell = Primitive::Range(Box::new((Primitive::U8(0), Primitive::U8(2)))).into();
+        assert!(range.is_of_type(&Type::Range(Box::new(Type::U8)), &no_opaque));
+        assert!(!range.is_of_type(&Type::Range(Box::new(Type::U16)), &no_opaque));
+
+        let range: ValueCell = Primitive::Range(Box::new((Primitive::U64(0), Primitive::U8(5)))).into();
+        assert!(!range.is_of_type(&Type::Range(Box::new(Type::U64)), &no_opaque));
+
+        assert!(!ValueCell::from(Primitive::Null).is_of_type(&Type::U64, &no_opaque));
+        assert!(ValueCell::from(Primitive::Null).is_of_type(&Type::Any, &no_opaque));
+        assert!(ValueCell::Bytes(vec![1]).is_of_type(&Type::Bytes, &no_opaque));
+        assert!(!ValueCell::from(Primitive::Boolean(true)).is_of_type(&Type::Bytes, &no_opaque));
+    }
+}
+
 #[cfg(all(feature = "infinite-cell-depth", test))]
 mod tests {
     use super::*;
@@ -0,0 +1,314 @@
+use std::fmt;
+
+use serde::{
+    ser::{Error, SerializeMap, SerializeSeq},
+    Serialize,
+    Serializer
+};
+use crate::{EnumType, EnumVariantNames, Primitive, Type};
+use super::ValueCell;
+
+// A value viewed through its declared type
+// Structs and enums are displayed and serialized using their names
+// when they are registered, otherwise the value is rendered as is
+#[derive(Debug, Clone, Copy)]
+pub struct TypedValue<'a> {
+    value: &'a ValueCell,
+    ty: &'a Type
+}
+
+impl<'a> TypedValue<'a> {
+    // Create a new typed view of a value
+    pub fn new(value: &'a ValueCell, ty: &'a Type) -> Self {
+        Self { value, ty }
+    }
+
+    // Get the value
+    #[inline]
+    pub fn value(&self) -> &'a ValueCell {
+        self.value
+    }
+
+    // Get the type used to render the value
+    #[inline]
+    pub fn get_type(&self) -> &'a Type {
+        self.ty
+    }
+
+    // View an inner value with its own type
+    #[inline]
+    fn inner(&self, value: &'a ValueCell, ty: &'a Type) -> Self {
+        Self::new(value, ty)
+    }
+
+    // Find the variant of an enum value
+    // Enum values don't carry their variant id, so the first variant
+    // with the same fields count and matching types is selected
+    fn find_variant(enum_type: &'a EnumType, values: &[ValueCell]) -> Option<(&'a [Type], &'a EnumVariantNames)> {
+        let names = enum_type.names()?;
+        enum_type.variants()
+            .iter()
+            .zip(names.variants())
+            .find(|(variant, _)| variant.fields().len() == values.len()
+                && values.iter()
+                    .zip(variant.fields())
+                    .all(|(value, ty)| value.is_of_type(ty, &|_, _| true))
+            )
+            .map(|(variant, names)| (variant.fields(), names))
+    }
+}
+
+// Write named fields like `{ x: 1, y: 2 }`
+fn fmt_fields(f: &mut fmt::Formatter<'_>, names: &[String], values: &[ValueCell], types: &[Type]) -> fmt::Result {
+    if values.is_empty() {
+        return write!(f, " {{}}");
+    }
+
+    write!(f, " {{ ")?;
+    for (i, ((name, value), ty)) in names.iter().zip(values).zip(types).enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}: {}", name, TypedValue::new(value, ty))?;
+    }
+    write!(f, " }}")
+}
+
+impl fmt::Display for TypedValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.ty, self.value) {
+            (Type::Optional(inner), value) => write!(f, "{}", self.inner(value, inner)),
+            (Type::Array(inner), ValueCell::Array(values)) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", self.inner(value, inner))?;
+                }
+                write!(f, "]")
+            },
+            (Type::Map(key, inner), ValueCell::Map(map)) => {
+                write!(f, "map{{")?;
+                for (i, (k, v)) in map.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", self.inner(k, key), self.inner(v, inner))?;
+                }
+                write!(f, "}}")
+            },
+            (Type::Struct(structure), ValueCell::Array(values)) => match structure.names() {
+                Some(names) if values.len() == structure.fields().len() => {
+                    write!(f, "{}", names.name())?;
+                    fmt_fields(f, names.fields(), values, structure.fields())
+                },
+                _ => write!(f, "{}", self.value)
+            },
+            (Type::Enum(enum_type), ValueCell::Array(values)) => match (enum_type.names(), Self::find_variant(enum_type, values)) {
+                (Some(names), Some((types, variant))) => {
+                    write!(f, "{}::{}", names.name(), variant.name())?;
+                    if values.is_empty() {
+                        return Ok(());
+                    }
+                    fmt_fields(f, variant.fields(), values, types)
+                },
+                _ => write!(f, "{}", self.value)
+            },
+            _ => write!(f, "{}", self.value)
+        }
+    }
+}
+
+// Named fields serialized as a JSON object
+struct Fields<'a> {
+    names: &'a [String],
+    values: &'a [ValueCell],
+    types: &'a [Type]
+}
+
+impl Serialize for Fields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len()))?;
+        for ((name, value), ty) in self.names.iter().zip(self.values).zip(self.types) {
+            map.serialize_entry(name, &TypedValue::new(value, ty))?;
+        }
+        map.end()
+    }
+}
+
+// JSON representation follows the ABI one:
+// u128 and u256 are written as strings, a struct is an object of its fields,
+// an enum is an object with its variant name and fields,
+// a map is an object if its keys are strings otherwise a list of pairs
+impl Serialize for TypedValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.ty, self.value) {
+            (_, ValueCell::Default(Primitive::Null)) => serializer.serialize_none(),
+            (_, ValueCell::Default(Primitive::U8(v))) => serializer.serialize_u8(*v),
+            (_, ValueCell::Default(Primitive::U16(v))) => serializer.serialize_u16(*v),
+            (_, ValueCell::Default(Primitive::U32(v))) => serializer.serialize_u32(*v),
+            (_, ValueCell::Default(Primitive::U64(v))) => serializer.serialize_u64(*v),
+            (_, ValueCell::Default(Primitive::U128(v))) => serializer.serialize_str(&v.to_string()),
+            (_, ValueCell::Default(Primitive::U256(v))) => serializer.serialize_str(&v.to_string()),
+            (_, ValueCell::Default(Primitive::String(v))) => serializer.serialize_str(v),
+            (_, ValueCell::Default(Primitive::Boolean(v))) => serializer.serialize_bool(*v),
+            (_, ValueCell::Default(Primitive::Range(range))) => {
+                let inner = match self.ty {
+                    Type::Range(inner) => inner.as_ref(),
+                    _ => &Type::Any
+                };
+                let start = ValueCell::Default(range.0.clone());
+                let end = ValueCell::Default(range.1.clone());
+                let mut seq = serializer.serialize_seq(Some(2))?;
+                seq.serialize_element(&self.inner(&start, inner))?;
+                seq.serialize_element(&self.inner(&end, inner))?;
+                seq.end()
+            },
+            (_, ValueCell::Default(Primitive::Opaque(opaque))) => opaque.inner()
+                .serialize_json()
+                .map_err(S::Error::custom)?
+                .serialize(serializer),
+            (_, ValueCell::Bytes(bytes)) => bytes.serialize(serializer),
+            (Type::Optional(inner), value) => self.inner(value, inner).serialize(serializer),
+            (Type::Struct(structure), ValueCell::Array(values)) => match structure.names() {
+                Some(names) if values.len() == structure.fields().len() => Fields {
+                    names: names.fields(),
+                    values,
+                    types: structure.fields()
+                }.serialize(serializer),
+                _ => self.serialize_array(values, serializer)
+            },
+            (Type::Enum(enum_type), ValueCell::Array(values)) => match Self::find_variant(enum_type, values) {
+                Some((types, variant)) => {
+                    let mut map = serializer.serialize_map(Some(2))?;
+                    map.serialize_entry("variant", variant.name())?;
+                    map.serialize_entry("fields", &Fields {
+                        names: variant.fields(),
+                        values,
+                        types
+                    })?;
+                    map.end()
+                },
+                None => self.serialize_array(values, serializer)
+            },
+            (_, ValueCell::Array(values)) => self.serialize_array(values, serializer),
+            (_, ValueCell::Map(map)) => {
+                let (key_type, value_type) = match self.ty {
+                    Type::Map(key, value) => (key.as_ref(), value.as_ref()),
+                    _ => (&Type::Any, &Type::Any)
+                };
+
+                if map.keys().all(ValueCell::is_string) {
+                    let mut object = serializer.serialize_map(Some(map.len()))?;
+                    for (k, v) in map {
+                        object.serialize_entry(&self.inner(k, key_type), &self.inner(v, value_type))?;
+                    }
+                    object.end()
+                } else {
+                    let mut seq = serializer.serialize_seq(Some(map.len()))?;
+                    for (k, v) in map {
+                        seq.serialize_element(&(self.inner(k, key_type), self.inner(v, value_type)))?;
+                    }
+                    seq.end()
+                }
+            }
+        }
+    }
+}
+
+impl TypedValue<'_> {
+    // Serialize the values of an array using the inner type if known
+    fn serialize_array<S: Serializer>(&self, values: &[ValueCell], serializer: S) -> Result<S::Ok, S::Error> {
+        let inner = match self.ty {
+            Type::Array(inner) => inner.as_ref(),
+            _ => &Type::Any
+        };
+
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&self.inner(value, inner))?;
+        }
+        seq.end()
+    }
+}
+
+impl ValueCell {
+    // View the value through its declared type
+    // This allows to display or serialize it with the struct and enum names
+    #[inline]
+    pub fn with_type<'a>(&'a self, ty: &'a Type) -> TypedValue<'a> {
+        TypedValue::new(self, ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use serde_json::json;
+    use crate::{EnumNames, EnumVariant, StructNames, StructType};
+    use super::*;
+
+    fn point_type() -> StructType {
+        StructType::with_names(0, vec![Type::U64, Type::U64], StructNames::new("Point", vec!["x".to_owned(), "y".to_owned()]))
+    }
+
+    fn point(x: u64, y: u64) -> ValueCell {
+        ValueCell::Array(vec![Primitive::U64(x).into(), Primitive::U64(y).into()])
+    }
+
+    #[test]
+    fn test_display_struct() {
+        let ty = Type::Struct(point_type());
+        assert_eq!(point(1, 2).with_type(&ty).to_string(), "Point { x: 1, y: 2 }");
+
+        // Without names the value is displayed as is
+        let unnamed = Type::Struct(StructType::new(0, vec![Type::U64, Type::U64]));
+        assert_eq!(point(1, 2).with_type(&unnamed).to_string(), "[1, 2]");
+
+        let array = Type::Array(Box::new(ty));
+        let value = ValueCell::Array(vec![point(1, 2), point(3, 4)]);
+        assert_eq!(value.with_type(&array).to_string(), "[Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]");
+    }
+
+    #[test]
+    fn test_display_enum() {
+        let shape = EnumType::with_names(1, vec![
+            EnumVariant::new(Vec::new()),
+            EnumVariant::new(vec![Type::Struct(point_type()), Type::U64])
+        ], EnumNames::new("Shape", vec![
+            EnumVariantNames::new("Empty", Vec::new()),
+            EnumVariantNames::new("Circle", vec!["center".to_owned(), "radius".to_owned()])
+        ]));
+        let ty = Type::Enum(shape);
+
+        assert_eq!(ValueCell::Array(Vec::new()).with_type(&ty).to_string(), "Shape::Empty");
+
+        let circle = ValueCell::Array(vec![point(1, 2), Primitive::U64(3).into()]);
+        assert_eq!(circle.with_type(&ty).to_string(), "Shape::Circle { center: Point { x: 1, y: 2 }, radius: 3 }");
+        assert_eq!(
+            serde_json::to_value(circle.with_type(&ty)).unwrap(),
+            json!({ "variant": "Circle", "fields": { "center": { "x": 1, "y": 2 }, "radius": 3 } })
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let ty = Type::Struct(point_type());
+        assert_eq!(serde_json::to_value(point(1, 2).with_type(&ty)).unwrap(), json!({ "x": 1, "y": 2 }));
+
+        let mut map = IndexMap::new();
+        map.insert(Primitive::String("a".to_owned()).into(), point(1, 2));
+        let map_type = Type::Map(Box::new(Type::String), Box::new(ty));
+        assert_eq!(
+            serde_json::to_value(ValueCell::Map(map).with_type(&map_type)).unwrap(),
+            json!({ "a": { "x": 1, "y": 2 } })
+        );
+
+        let value: ValueCell = Primitive::U128(u128::MAX).into();
+        assert_eq!(serde_json::to_value(value.with_type(&Type::U128)).unwrap(), json!(u128::MAX.to_string()));
+
+        let optional = Type::Optional(Box::new(Type::U8));
+        assert_eq!(serde_json::to_value(ValueCell::from(Primitive::Null).with_type(&optional)).unwrap(), json!(null));
+    }
+}
@@ -11,7 +11,7 @@ macro_rules! bench {
     ($group: expr, $name: expr, $code: expr, $id: expr) => {
         $group.bench_function($name, |b| {    
             let (module, env) = prepare($code);
-            let mut vm = VM::new(&module, &env);
+            let mut vm = VM::new(&module, &env).unwrap();
             b.iter(|| {
                 vm.invoke_entry_chunk($id).unwrap();
                 vm.run().unwrap();
@@ -209,6 +209,21 @@ fn bench_for(c: &mut Criterion) {
         "#
     );
 
+    bench!(
+        group,
+        "range hot",
+        r#"
+        entry main() {
+            let sum: u64 = 0;
+            for i: u64 = 0; i < 1000; i += 1 {
+                sum += i;
+                sum %= 7919;
+            }
+            return 0;
+        }
+        "#
+    );
+
     bench!(
         group,
         "nested",
@@ -263,6 +278,22 @@ fn bench_while(c: &mut Criterion) {
         }
         "#
     );
+
+    bench!(
+        group,
+        "countdown",
+        r#"
+        entry main() {
+            let i: u64 = 1000;
+            let steps: u64 = 0;
+            while i > 0 {
+                i -= 1;
+                steps += 1;
+            }
+            return 0;
+        }
+        "#
+    );
 }
 
 fn bench_function_call(c: &mut Criterion) {
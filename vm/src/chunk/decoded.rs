@@ -0,0 +1,270 @@
+use xelis_bytecode::{Chunk, OpCode};
+use xelis_types::Type;
+
+use crate::VMError;
+use super::ChunkReader;
+
+// Operands of an instruction, decoded from the bytes following its opcode
+// Jump addresses are resolved to the index of the targeted instruction
+#[derive(Debug, Clone)]
+pub enum Operands {
+    None,
+    U8(u8),
+    U16(u16),
+    // Swap2 stack indexes
+    Pair(u8, u8),
+    Address(usize),
+    // Cast target type
+    Type(Type),
+    // InvokeChunk and SysCall
+    Call {
+        id: u16,
+        on_value: bool,
+        args: u8
+    },
+    // MemoryOperator
+    Registers {
+        left: u16,
+        right: u16,
+        op: OpCode
+    },
+    // MemoryConstantOperator and MemoryAssignConstant
+    RegisterConstant {
+        register: u16,
+        constant: u16,
+        op: OpCode
+    },
+    // MemoryConstantJumpIfFalse
+    RegisterConstantJump {
+        register: u16,
+        constant: u16,
+        op: OpCode,
+        addr: usize
+    }
+}
+
+impl Operands {
+    #[inline(always)]
+    pub fn as_u8(&self) -> Result<u8, VMError> {
+        match self {
+            Self::U8(value) => Ok(*value),
+            _ => Err(VMError::InvalidOperands)
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_u16(&self) -> Result<u16, VMError> {
+        match self {
+            Self::U16(value) => Ok(*value),
+            _ => Err(VMError::InvalidOperands)
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_pair(&self) -> Result<(u8, u8), VMError> {
+        match self {
+            Self::Pair(a, b) => Ok((*a, *b)),
+            _ => Err(VMError::InvalidOperands)
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_address(&self) -> Result<usize, VMError> {
+        match self {
+            Self::Address(addr) => Ok(*addr),
+            _ => Err(VMError::InvalidOperands)
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_type(&self) -> Result<&Type, VMError> {
+        match self {
+            Self::Type(ty) => Ok(ty),
+            _ => Err(VMError::InvalidOperands)
+        }
+    }
+
+    // Returns the chunk or syscall id, on_value flag and arguments count
+    #[inline(always)]
+    pub fn as_call(&self) -> Result<(u16, bool, u8), VMError> {
+        match self {
+            Self::Call { id, on_value, args } => Ok((*id, *on_value, *args)),
+            _ => Err(VMError::InvalidOperands)
+        }
+    }
+}
+
+// Instruction ready to be executed
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub opcode: u8,
+    pub operands: Operands
+}
+
+// Chunk decoded once to be executed without reading its bytes again
+// The bytes of the chunk stay the canonical format,
+// offsets are kept to map each instruction to its position in it
+#[derive(Debug)]
+pub struct DecodedChunk {
+    instructions: Vec<Instruction>,
+    // Offset of each instruction in the chunk bytes
+    // The chunk length is appended to map the end of the chunk
+    offsets: Vec<usize>
+}
+
+impl DecodedChunk {
+    // Decode all the instructions of a chunk
+    // Fails if an opcode or its operands are invalid,
+    // or if a jump doesn't target the start of an instruction
+    pub fn decode(chunk: &Chunk) -> Result<Self, VMError> {
+        let len = chunk.get_instructions().len();
+        let mut reader = ChunkReader::new(chunk);
+        let mut instructions = Vec::new();
+        let mut offsets = Vec::new();
+
+        while reader.index() < len {
+            offsets.push(reader.index());
+
+            let op = reader.read_op_code()?;
+            let operands = match op {
+                OpCode::Constant
+                | OpCode::MemoryLoad
+                | OpCode::MemorySet
+                | OpCode::MemoryToOwned
+                | OpCode::MemoryIncrement => Operands::U16(reader.read_u16()?),
+                OpCode::SubLoad
+                | OpCode::PopN
+                | OpCode::CopyN
+                | OpCode::Swap
+                | OpCode::NewObject
+                | OpCode::NewMap => Operands::U8(reader.read_u8()?),
+                OpCode::Swap2 => Operands::Pair(reader.read_u8()?, reader.read_u8()?),
+                OpCode::Jump
+                | OpCode::JumpIfFalse
+                | OpCode::IteratorNext => Operands::Address(reader.read_u32()? as usize),
+                OpCode::Cast => Operands::Type(reader.read_type()?),
+                OpCode::InvokeChunk
+                | OpCode::SysCall => Operands::Call {
+                    id: reader.read_u16()?,
+                    on_value: reader.read_bool()?,
+                    args: reader.read_u8()?
+                },
+                OpCode::MemoryOperator => Operands::Registers {
+                    left: reader.read_u16()?,
+                    right: reader.read_u16()?,
+                    op: read_fused_operator(&mut reader)?
+                },
+                OpCode::MemoryConstantOperator
+                | OpCode::MemoryAssignConstant => Operands::RegisterConstant {
+                    register: reader.read_u16()?,
+                    constant: reader.read_u16()?,
+                    op: read_fused_operator(&mut reader)?
+                },
+                OpCode::MemoryConstantJumpIfFalse => Operands::RegisterConstantJump {
+                    register: reader.read_u16()?,
+                    constant: reader.read_u16()?,
+                    op: read_fused_operator(&mut reader)?,
+                    addr: reader.read_u32()? as usize
+                },
+                OpCode::MemoryPop
+                | OpCode::MemoryLen
+                | OpCode::Pop
+                | OpCode::Copy
+                | OpCode::ToOwned
+                | OpCode::IterableLength
+                | OpCode::IteratorBegin
+                | OpCode::IteratorEnd
+                | OpCode::Return
+                | OpCode::ArrayCall
+                | OpCode::NewRange
+                | OpCode::Add
+                | OpCode::Sub
+                | OpCode::Mul
+                | OpCode::Div
+                | OpCode::Mod
+                | OpCode::Pow
+                | OpCode::And
+                | OpCode::Or
+                | OpCode::BitwiseAnd
+                | OpCode::BitwiseOr
+                | OpCode::BitwiseXor
+                | OpCode::BitwiseShl
+                | OpCode::BitwiseShr
+                | OpCode::Eq
+                | OpCode::Neg
+                | OpCode::Gt
+                | OpCode::Lt
+                | OpCode::Gte
+                | OpCode::Lte
+                | OpCode::Assign
+                | OpCode::AssignAdd
+                | OpCode::AssignSub
+                | OpCode::AssignMul
+                | OpCode::AssignDiv
+                | OpCode::AssignMod
+                | OpCode::AssignPow
+                | OpCode::AssignBitwiseAnd
+                | OpCode::AssignBitwiseOr
+                | OpCode::AssignBitwiseXor
+                | OpCode::AssignBitwiseShl
+                | OpCode::AssignBitwiseShr
+                | OpCode::Inc
+                | OpCode::Dec => Operands::None,
+            };
+
+            instructions.push(Instruction {
+                opcode: op.as_byte(),
+                operands
+            });
+        }
+        offsets.push(len);
+
+        // All the offsets are known, resolve the jumps
+        for instruction in instructions.iter_mut() {
+            if let Operands::Address(addr) | Operands::RegisterConstantJump { addr, .. } = &mut instruction.operands {
+                *addr = offsets.binary_search(addr)
+                    .map_err(|_| VMError::InvalidJumpAddress(*addr))?;
+            }
+        }
+
+        Ok(Self {
+            instructions,
+            offsets
+        })
+    }
+
+    // Get the decoded instructions
+    #[inline]
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    // Get the instruction at index
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Option<&Instruction> {
+        self.instructions.get(index)
+    }
+
+    // Get the offset in the chunk bytes of the instruction at index
+    // The index after the last instruction maps to the chunk length
+    #[inline]
+    pub fn offset_of(&self, index: usize) -> Option<usize> {
+        self.offsets.get(index).copied()
+    }
+
+    // Get the index of the instruction starting at offset in the chunk bytes
+    #[inline]
+    pub fn index_of(&self, offset: usize) -> Option<usize> {
+        self.offsets.binary_search(&offset).ok()
+    }
+}
+
+// Read the operator of a superinstruction
+fn read_fused_operator(reader: &mut ChunkReader) -> Result<OpCode, VMError> {
+    let op = reader.read_op_code()?;
+    if !op.is_fusable_operator() {
+        return Err(VMError::InvalidOpCode);
+    }
+
+    Ok(op)
+}
@@ -1,20 +1,31 @@
 mod reader;
+mod decoded;
 
-use std::{cmp::Ordering, ops::{Deref, DerefMut}};
-use xelis_bytecode::Chunk;
+use std::{cmp::Ordering, ops::{Deref, DerefMut}, sync::Arc};
+use xelis_bytecode::{Chunk, OpCode};
 use xelis_types::StackValue;
 
 use super::{Stack, iterator::ValueIterator, VMError};
 
 pub use reader::ChunkReader;
+pub use decoded::*;
 
 // u16::MAX registers maximum
-const REGISTERS_SIZE: usize = u16::MAX as usize;
+pub(crate) const REGISTERS_SIZE: usize = u16::MAX as usize;
 
 // Manager for a chunk
-// It contains the reader and the stacks
+// It contains the decoded instructions and the stacks
 pub struct ChunkManager<'a> {
+    // Id of the chunk in the module
+    id: u16,
+    chunk: &'a Chunk,
+    decoded: Arc<DecodedChunk>,
+    // Reader on the operands of the instruction being executed
     reader: ChunkReader<'a>,
+    // Index of the next instruction to execute
+    ip: usize,
+    // Maximum registers the chunk can use
+    max_registers: usize,
     // Registers are temporary and "scoped" per chunk
     registers: Vec<StackValue>,
     // Iterators stack
@@ -23,23 +34,137 @@ pub struct ChunkManager<'a> {
 
 impl<'a> ChunkManager<'a> {
     // Create a new chunk manager
-    // It will create a reader from the chunk
-    // and initialize the stack and registers
+    // The decoded instructions must be the ones of the chunk
     #[inline]
-    pub fn new(chunk: &'a Chunk) -> Self {
+    pub fn new(id: u16, chunk: &'a Chunk, decoded: Arc<DecodedChunk>, max_registers: usize) -> Self {
         ChunkManager {
+            id,
+            chunk,
+            decoded,
             reader: ChunkReader::new(chunk),
+            ip: 0,
+            max_registers,
             registers: Vec::new(),
             iterators: Vec::new(),
         }
     }
 
+    // Restore a chunk manager at the given offset in the chunk bytes
+    pub(crate) fn restore(id: u16, chunk: &'a Chunk, decoded: Arc<DecodedChunk>, max_registers: usize, index: usize, registers: Vec<StackValue>, iterators: Vec<ValueIterator>) -> Result<Self, VMError> {
+        let ip = decoded.index_of(index)
+            .ok_or(VMError::OutOfBounds)?;
+
+        if registers.len() > max_registers {
+            return Err(VMError::RegisterMaxSize(max_registers));
+        }
+
+        Ok(ChunkManager {
+            id,
+            chunk,
+            decoded,
+            reader: ChunkReader::new(chunk),
+            ip,
+            max_registers,
+            registers,
+            iterators,
+        })
+    }
+
+    // Get the id of the chunk executed
+    #[inline]
+    pub fn chunk_id(&self) -> u16 {
+        self.id
+    }
+
+    // Get the chunk executed
+    #[inline]
+    pub fn chunk(&self) -> &'a Chunk {
+        self.chunk
+    }
+
+    // Get the decoded instructions of the chunk
+    #[inline]
+    pub fn decoded(&self) -> &DecodedChunk {
+        &self.decoded
+    }
+
+    // Get the offset in the chunk bytes of the next instruction
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.decoded.offset_of(self.ip)
+            .unwrap_or(self.chunk.get_instructions().len())
+    }
+
+    // Get the opcode of the next instruction and move to it
+    #[inline(always)]
+    pub(crate) fn next_opcode(&mut self) -> Option<u8> {
+        let instruction = self.decoded.get(self.ip)?;
+        if let Some(offset) = self.decoded.offset_of(self.ip) {
+            self.reader.seek(offset + 1);
+        }
+
+        self.ip += 1;
+        Some(instruction.opcode)
+    }
+
+    // Get the operands of the instruction being executed
+    #[inline(always)]
+    pub fn operands(&self) -> Result<&Operands, VMError> {
+        self.ip.checked_sub(1)
+            .and_then(|index| self.decoded.get(index))
+            .map(|instruction| &instruction.operands)
+            .ok_or(VMError::MissingInstruction)
+    }
+
+    // Get the offset and the operand bytes of the instruction being executed
+    pub(crate) fn current_bytes(&self) -> Option<(usize, &'a [u8])> {
+        let index = self.ip.checked_sub(1)?;
+        let offset = self.decoded.offset_of(index)?;
+        let end = self.decoded.offset_of(index + 1)?;
+
+        Some((offset, &self.chunk.get_instructions()[offset + 1..end]))
+    }
+
+    // Continue the execution at the instruction index
+    // Decoded addresses are already resolved to instruction indexes
+    #[inline]
+    pub fn jump(&mut self, index: usize) {
+        self.ip = index;
+    }
+
+    // Check if we have another instruction to execute
+    // Return OpCode is exempted
+    // This function is used for the call stack optimization
+    #[inline]
+    pub fn has_next_instruction(&self) -> bool {
+        self.decoded.get(self.ip)
+            .is_some_and(|instruction| instruction.opcode != OpCode::Return.as_byte())
+    }
+
     // Get the registers
     #[inline]
     pub fn get_registers(&self) -> &Vec<StackValue> {
         &self.registers
     }
 
+    // Get the mutable registers
+    #[inline]
+    pub(crate) fn get_registers_mut(&mut self) -> &mut Vec<StackValue> {
+        &mut self.registers
+    }
+
+    // Get the iterators stack
+    #[inline]
+    pub fn get_iterators(&self) -> &[ValueIterator] {
+        &self.iterators
+    }
+
+    // Get the mutable iterators stack
+    #[inline]
+    pub(crate) fn get_iterators_mut(&mut self) -> &mut [ValueIterator] {
+        &mut self.iterators
+    }
+
     // Add an iterator to the stack
     pub fn add_iterator(&mut self, iterator: ValueIterator) {
         self.iterators.push(iterator);
@@ -60,8 +185,8 @@ impl<'a> ChunkManager<'a> {
     // Push/set a new value into the registers
     #[inline]
     pub fn set_register(&mut self, index: usize, mut value: StackValue, stack: &mut Stack) -> Result<(), VMError> {
-        if index >= REGISTERS_SIZE {
-            return Err(VMError::RegisterMaxSize);
+        if index >= self.max_registers {
+            return Err(VMError::RegisterMaxSize(self.max_registers));
         }
 
         let cmp = self.registers.len().cmp(&index);
@@ -102,6 +227,12 @@ impl<'a> ChunkManager<'a> {
         }
     }
 
+    // Get a reference to a value from the registers
+    #[inline]
+    pub fn get_register(&self, index: usize) -> Result<&StackValue, VMError> {
+        self.registers.get(index).ok_or(VMError::RegisterNotFound)
+    }
+
     // Get a value from the registers
     #[inline]
     pub fn from_register(&mut self, index: usize) -> Result<&mut StackValue, VMError> {
@@ -130,6 +261,8 @@ impl<'a> ChunkManager<'a> {
     }
 }
 
+// The reader gives the raw operand bytes of the instruction being executed
+// Moving it doesn't change the next instruction, use `jump` for it
 impl<'a> Deref for ChunkManager<'a> {
     type Target = ChunkReader<'a>;
 
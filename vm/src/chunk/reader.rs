@@ -19,6 +19,12 @@ impl<'a> ChunkReader<'a> {
         }
     }
 
+    // Get the chunk read
+    #[inline]
+    pub fn chunk(&self) -> &'a Chunk {
+        self.chunk
+    }
+
     // Get the current index in our reader
     #[inline]
     pub fn index(&self) -> usize {
@@ -36,6 +42,12 @@ impl<'a> ChunkReader<'a> {
         Ok(())
     }
 
+    // Move to an index known to be in the chunk
+    #[inline(always)]
+    pub(super) fn seek(&mut self, index: usize) {
+        self.ip = index;
+    }
+
     // Advance the index by a given amount
     // Check that we don't go out of bounds
     #[inline]
@@ -0,0 +1,46 @@
+use crate::{chunk::REGISTERS_SIZE, stack::STACK_SIZE, VMError, CALL_STACK_SIZE};
+
+// Limits and options of a VM
+// The default limits are the historical ones of the VM
+#[derive(Debug, Clone)]
+pub struct VMConfig {
+    // How many chunks can be chained in the call stack
+    pub max_call_stack_size: usize,
+    // How many values the stack can hold
+    pub max_stack_size: usize,
+    // How many registers a chunk can use
+    // Registers are indexed by a u16, so it can't exceed u16::MAX
+    pub max_registers_size: usize,
+    // Reuse the current frame when a chunk is invoked right before a return
+    pub tail_call_optimization: bool
+}
+
+impl Default for VMConfig {
+    fn default() -> Self {
+        Self {
+            max_call_stack_size: CALL_STACK_SIZE,
+            max_stack_size: STACK_SIZE,
+            max_registers_size: REGISTERS_SIZE,
+            tail_call_optimization: false
+        }
+    }
+}
+
+impl VMConfig {
+    // Verify that the limits can be used by a VM
+    pub fn validate(&self) -> Result<(), VMError> {
+        if self.max_call_stack_size == 0 {
+            return Err(VMError::InvalidConfig("call stack size must be greater than zero"));
+        }
+
+        if self.max_stack_size == 0 {
+            return Err(VMError::InvalidConfig("stack size must be greater than zero"));
+        }
+
+        if self.max_registers_size == 0 || self.max_registers_size > REGISTERS_SIZE {
+            return Err(VMError::InvalidConfig("registers size must be between one and u16::MAX"));
+        }
+
+        Ok(())
+    }
+}
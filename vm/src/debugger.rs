@@ -0,0 +1,169 @@
+use indexmap::IndexSet;
+use xelis_bytecode::DebugInfo;
+use xelis_types::ValueCell;
+
+use crate::{ChunkManager, Stack, VMError, VM};
+
+// Reason of a debugger stop
+#[derive(Debug)]
+pub enum StopReason {
+    // The requested step is done
+    Step,
+    // A breakpoint was reached at the (chunk id, offset)
+    Breakpoint(usize, usize),
+    // The call stack is empty, this is the returned value
+    Finished(ValueCell)
+}
+
+// Step debugger over a VM
+// Breakpoints are checked between each instruction,
+// so running through the debugger doesn't slow down `VM::run`
+pub struct Debugger<'v, 'a, 'r> {
+    vm: &'v mut VM<'a, 'r>,
+    // Breakpoints as (chunk id, offset)
+    breakpoints: IndexSet<(usize, usize)>,
+    // Source lines mapping used for the line breakpoints
+    debug_info: Option<&'v DebugInfo>
+}
+
+impl<'v, 'a, 'r> Debugger<'v, 'a, 'r> {
+    // Create a new debugger
+    // The VM must already have a chunk invoked
+    pub fn new(vm: &'v mut VM<'a, 'r>) -> Self {
+        Self {
+            vm,
+            breakpoints: IndexSet::new(),
+            debug_info: None
+        }
+    }
+
+    // Set the debug info used to resolve the source lines
+    pub fn with_debug_info(mut self, debug_info: &'v DebugInfo) -> Self {
+        self.debug_info = Some(debug_info);
+        self
+    }
+
+    // Get the VM debugged
+    #[inline]
+    pub fn vm(&self) -> &VM<'a, 'r> {
+        self.vm
+    }
+
+    // Add a breakpoint on the instruction at offset in the chunk
+    // Returns false if it was already set
+    pub fn add_breakpoint(&mut self, chunk_id: usize, offset: usize) -> bool {
+        self.breakpoints.insert((chunk_id, offset))
+    }
+
+    // Remove a breakpoint
+    // Returns false if it wasn't set
+    pub fn remove_breakpoint(&mut self, chunk_id: usize, offset: usize) -> bool {
+        self.breakpoints.shift_remove(&(chunk_id, offset))
+    }
+
+    // Add a breakpoint on each instruction sequence of the source line
+    // Returns the count of breakpoints added, none without debug info
+    pub fn add_line_breakpoint(&mut self, line: usize) -> usize {
+        let Some(debug_info) = self.debug_info else {
+            return 0;
+        };
+
+        debug_info.get_offsets_of_line(line)
+            .into_iter()
+            .filter(|breakpoint| self.breakpoints.insert(*breakpoint))
+            .count()
+    }
+
+    // Remove all the breakpoints
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    // Get all the breakpoints set
+    #[inline]
+    pub fn breakpoints(&self) -> &IndexSet<(usize, usize)> {
+        &self.breakpoints
+    }
+
+    // Get the (chunk id, offset) of the next instruction to execute
+    pub fn location(&self) -> Option<(usize, usize)> {
+        self.vm.call_stack()
+            .last()
+            .map(|frame| (frame.chunk_id() as usize, frame.index()))
+    }
+
+    // Get the source line of the next instruction to execute
+    pub fn current_line(&self) -> Option<usize> {
+        let (chunk_id, offset) = self.location()?;
+        self.debug_info?.get_line(chunk_id, offset)
+    }
+
+    // Get the frames from the first invoked to the current one
+    #[inline]
+    pub fn frames(&self) -> &[ChunkManager<'a>] {
+        self.vm.call_stack()
+    }
+
+    // Get the stack of the VM
+    #[inline]
+    pub fn stack(&self) -> &Stack {
+        self.vm.get_stack()
+    }
+
+    // Get the gas used until now
+    #[inline]
+    pub fn gas_usage(&self) -> u64 {
+        self.vm.context().current_gas_usage()
+    }
+
+    // Execute a single instruction
+    // An invoked chunk is entered
+    pub fn step(&mut self) -> Result<StopReason, VMError> {
+        Ok(match self.vm.step()? {
+            Some(value) => StopReason::Finished(value),
+            None => StopReason::Step
+        })
+    }
+
+    // Same as `step`, named for the symmetry with `step_over` and `step_out`
+    #[inline]
+    pub fn step_into(&mut self) -> Result<StopReason, VMError> {
+        self.step()
+    }
+
+    // Execute a single instruction of the current frame
+    // An invoked chunk is executed until it returns, unless a breakpoint is reached
+    pub fn step_over(&mut self) -> Result<StopReason, VMError> {
+        let depth = self.vm.call_stack().len();
+        self.run_while(|vm| vm.call_stack().len() > depth)
+    }
+
+    // Execute until the current frame returns, unless a breakpoint is reached
+    pub fn step_out(&mut self) -> Result<StopReason, VMError> {
+        let depth = self.vm.call_stack().len();
+        self.run_while(|vm| vm.call_stack().len() >= depth)
+    }
+
+    // Execute until a breakpoint is reached or the execution is done
+    pub fn continue_execution(&mut self) -> Result<StopReason, VMError> {
+        self.run_while(|_| true)
+    }
+
+    // Execute one instruction then continue while the condition is met
+    // Stops on any breakpoint reached after the first instruction
+    fn run_while<F: Fn(&VM<'a, 'r>) -> bool>(&mut self, condition: F) -> Result<StopReason, VMError> {
+        loop {
+            if let Some(value) = self.vm.step()? {
+                return Ok(StopReason::Finished(value));
+            }
+
+            if let Some((chunk_id, offset)) = self.location().filter(|location| self.breakpoints.contains(location)) {
+                return Ok(StopReason::Breakpoint(chunk_id, offset));
+            }
+
+            if !condition(self.vm) {
+                return Ok(StopReason::Step);
+            }
+        }
+    }
+}
@@ -1,13 +1,14 @@
 use thiserror::Error;
 use xelis_environment::EnvironmentError;
-use xelis_types::{Primitive, ValueError};
+use xelis_bytecode::AbiError;
+use xelis_types::{serializer::ReaderError, Primitive, ValueError};
 
 #[derive(Debug, Error)]
 pub enum VMError {
     #[error("Expected checkpoint")]
     ExpectedCheckPoint,
-    #[error("register max size reached")]
-    RegisterMaxSize,
+    #[error("register max size of {0} reached")]
+    RegisterMaxSize(usize),
     #[error("register overflow")]
     RegisterOverflow,
     #[error("invalid return value")]
@@ -42,6 +43,10 @@ pub enum VMError {
     MissingInstruction,
     #[error("invalid opcode")]
     InvalidOpCode,
+    #[error("invalid operands for the instruction")]
+    InvalidOperands,
+    #[error("invalid jump address: {0}")]
+    InvalidJumpAddress(usize),
     #[error("invalid primitive type")]
     InvalidPrimitiveType,
     #[error("register was not found")]
@@ -64,16 +69,38 @@ pub enum VMError {
     StackIndexOutOfBounds,
     #[error("not enough arguments")]
     NotEnoughArguments,
-    #[error("stack overflow")]
-    StackOverflow,
-    #[error("call stack overflow")]
-    CallStackOverflow,
+    #[error("stack overflow, limit of {0} values reached")]
+    StackOverflow(usize),
+    #[error("call stack overflow, limit of {0} frames reached")]
+    CallStackOverflow(usize),
+    #[error("invalid VM config: {0}")]
+    InvalidConfig(&'static str),
     #[error("unexpected type")]
     UnexpectedType,
     #[error("{0}")]
     Static(&'static str),
     #[error("Error, memory not cleaned, we have {0} bytes left")]
     MemoryNotCleaned(usize),
+    #[error(transparent)]
+    AbiError(#[from] AbiError),
+    #[error("invalid arguments count: expected {0}, got {1}")]
+    InvalidArgumentsCount(usize, usize),
+    #[error("invalid type for argument at index {0}")]
+    InvalidArgumentType(usize),
+    #[error("a native function requested to yield and returned a value")]
+    InvalidYield,
+    #[error("execution is suspended, it must be resumed")]
+    ExecutionSuspended,
+    #[error("execution is not suspended")]
+    ExecutionNotSuspended,
+    #[error("invalid value to resume the execution")]
+    InvalidResumeValue,
+    #[error(transparent)]
+    ReaderError(#[from] ReaderError),
+    #[error("invalid snapshot")]
+    InvalidSnapshot,
+    #[error("{0}, and its state changes couldn't be reverted: {1}")]
+    RollbackFailed(Box<VMError>, EnvironmentError),
 }
 
 impl From<EnvironmentError> for VMError {
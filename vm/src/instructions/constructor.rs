@@ -7,7 +7,7 @@ use crate::{stack::Stack, Backend, ChunkManager, Context, VMError};
 use super::InstructionResult;
 
 pub fn new_array<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let length = manager.read_u8()?;
+    let length = manager.operands()?.as_u8()?;
     let mut array = VecDeque::with_capacity(length as usize);
     for _ in 0..length {
         let pop = stack.pop_stack()?;
@@ -44,7 +44,7 @@ pub fn new_range<'a>(_: &Backend<'a>, stack: &mut Stack, _: &mut ChunkManager<'a
 }
 
 pub fn new_map<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let len = manager.read_u8()?;
+    let len = manager.operands()?.as_u8()?;
     let mut map = IndexMap::with_capacity(len as usize);
     for _ in 0..len {
         let value = stack.pop_stack()?;
@@ -0,0 +1,108 @@
+use xelis_bytecode::OpCode;
+use xelis_types::ValueCell;
+
+use crate::{stack::Stack, Backend, ChunkManager, Context, Operands, VMError};
+use super::{apply_operator, InstructionResult};
+
+// Superinstructions
+// Their base cost in the table is the cost of the loads they replace,
+// the operator cost is read from the table to stay in sync with it
+
+// Charge the cost of the operator, it was checked once decoded
+#[inline]
+fn charge_operator(backend: &Backend, context: &mut Context, op: OpCode, assign: bool) -> Result<(), VMError> {
+    let charged = if assign {
+        op.as_assign_operator()
+            .ok_or(VMError::InvalidOpCode)?
+    } else {
+        op
+    };
+
+    context.increase_gas_usage(backend.table.get_instruction_cost(charged.as_byte()))?;
+
+    Ok(())
+}
+
+// Read a constant and charge its memory like the Constant opcode
+#[inline]
+fn load_constant<'b>(backend: &'b Backend<'_>, index: u16, context: &mut Context) -> Result<&'b ValueCell, VMError> {
+    let constant = backend.get_constant_with_id(index as usize)?;
+    let memory_usage = constant.calculate_memory_usage(context.memory_left())?;
+    context.increase_memory_usage_unchecked(memory_usage)?;
+
+    Ok(constant)
+}
+
+pub fn memory_operator<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
+    let Operands::Registers { left, right, op } = *manager.operands()? else {
+        return Err(VMError::InvalidOperands);
+    };
+    charge_operator(backend, context, op, false)?;
+
+    let result = apply_operator(
+        op,
+        manager.get_register(left as usize)?.as_ref()?,
+        manager.get_register(right as usize)?.as_ref()?
+    )?;
+    stack.push_stack(result.into())?;
+
+    Ok(InstructionResult::Nothing)
+}
+
+pub fn memory_constant_operator<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
+    let Operands::RegisterConstant { register, constant, op } = *manager.operands()? else {
+        return Err(VMError::InvalidOperands);
+    };
+    charge_operator(backend, context, op, false)?;
+
+    let constant = load_constant(backend, constant, context)?;
+    let result = apply_operator(op, manager.get_register(register as usize)?.as_ref()?, constant)?;
+    stack.push_stack(result.into())?;
+
+    Ok(InstructionResult::Nothing)
+}
+
+pub fn memory_constant_jump_if_false<'a>(backend: &Backend<'a>, _: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
+    let Operands::RegisterConstantJump { register, constant, op, addr } = *manager.operands()? else {
+        return Err(VMError::InvalidOperands);
+    };
+    charge_operator(backend, context, op, false)?;
+
+    let constant = load_constant(backend, constant, context)?;
+    let result = apply_operator(op, manager.get_register(register as usize)?.as_ref()?, constant)?;
+    if !result.as_bool()? {
+        manager.jump(addr);
+    }
+
+    Ok(InstructionResult::Nothing)
+}
+
+pub fn memory_assign_constant<'a>(backend: &Backend<'a>, _: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
+    let Operands::RegisterConstant { register, constant, op } = *manager.operands()? else {
+        return Err(VMError::InvalidOperands);
+    };
+    charge_operator(backend, context, op, true)?;
+
+    let constant = load_constant(backend, constant, context)?;
+    let value = manager.from_register(register as usize)?;
+    let result = apply_operator(op, value.as_ref()?, constant)?;
+    *value.as_mut()? = result.into();
+
+    Ok(InstructionResult::Nothing)
+}
+
+pub fn memory_increment<'a>(backend: &Backend<'a>, _: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
+    let register = manager.operands()?.as_u16()?;
+    context.increase_gas_usage(backend.table.get_instruction_cost(OpCode::AssignAdd.as_byte()))?;
+
+    let value = manager.from_register(register as usize)?
+        .as_mut()?;
+
+    // Charge the memory of the constant one it replaces
+    let memory_usage = value.calculate_memory_usage(context.memory_left())?;
+    context.increase_memory_usage_unchecked(memory_usage)?;
+
+    value.increment()?;
+
+    Ok(InstructionResult::Nothing)
+}
@@ -1,10 +1,12 @@
 use std::collections::VecDeque;
 
+use xelis_environment::{ChunkInvoker, NoInvoker};
+
 use crate::{stack::Stack, Backend, ChunkManager, Context, VMError};
 use super::InstructionResult;
 
 pub fn constant<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u16()? as usize;
+    let index = manager.operands()?.as_u16()? as usize;
     let constant = backend.get_constant_with_id(index)?;
 
     let memory_usage = constant.calculate_memory_usage(context.memory_left())?;
@@ -15,7 +17,7 @@ pub fn constant<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut Chun
 }
 
 pub fn subload<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u8()?;
+    let index = manager.operands()?.as_u8()?;
     let path = stack.pop_stack()?;
     let sub = path.get_at_index(index as usize)?;
     stack.push_stack_unchecked(sub);
@@ -36,7 +38,7 @@ pub fn copy<'a>(_: &Backend<'a>, stack: &mut Stack, _: &mut ChunkManager<'a>, co
 }
 
 pub fn copy_n<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u8()?;
+    let index = manager.operands()?.as_u8()?;
     let value = stack.get_stack_at(index as usize)?;
 
     let memory_usage = value.as_ref()?
@@ -61,20 +63,19 @@ pub fn pop<'a>(_: &Backend<'a>, stack: &mut Stack, _: &mut ChunkManager<'a>, _:
 }
 
 pub fn pop_n<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let n = manager.read_u8()?;
+    let n = manager.operands()?.as_u8()?;
     stack.pop_stack_n(n)?;
     Ok(InstructionResult::Nothing)
 }
 
 pub fn swap<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u8()?;
+    let index = manager.operands()?.as_u8()?;
     stack.swap_stack(index as usize)?;
     Ok(InstructionResult::Nothing)
 }
 
 pub fn swap2<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index_a = manager.read_u8()?;
-    let index_b = manager.read_u8()?;
+    let (index_a, index_b) = manager.operands()?.as_pair()?;
 
     stack.swap_stack_both(index_a as usize, index_b as usize)?;
     Ok(InstructionResult::Nothing)
@@ -95,9 +96,8 @@ pub fn array_call<'a>(_: &Backend<'a>, stack: &mut Stack, _: &mut ChunkManager<'
 }
 
 pub fn invoke_chunk<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let id = manager.read_u16()?;
-    let on_value = manager.read_bool()?;
-    let mut args = manager.read_u8()? as usize;
+    let (id, on_value, args) = manager.operands()?.as_call()?;
+    let mut args = args as usize;
     if on_value {
         args += 1;
     }
@@ -116,10 +116,22 @@ pub fn invoke_chunk<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkM
 }
 
 pub fn syscall<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let id = manager.read_u16()?;
-    let on_value = manager.read_bool()?;
-    let args = manager.read_u8()?;
+    let (id, on_value, args) = manager.operands()?.as_call()?;
+
+    let f = backend.environment.get_functions()
+        .get(id as usize)
+        .ok_or(VMError::UnknownSysCall)?;
+
+    // Invoking chunks requires the call stack, the VM calls it
+    if f.is_reentrant() {
+        return Ok(InstructionResult::ReentrantSysCall(id));
+    }
 
+    call_native(backend, stack, context, id, on_value, args, &mut NoInvoker)
+}
+
+// Pop the arguments of a native function and call it
+pub(crate) fn call_native<'a, 'r>(backend: &Backend<'a>, stack: &mut Stack, context: &mut Context<'a, 'r>, id: u16, on_value: bool, args: u8, invoker: &mut dyn ChunkInvoker<'a, 'r>) -> Result<InstructionResult, VMError> {
     let mut arguments = VecDeque::with_capacity(args as usize);
     for _ in 0..args {
         arguments.push_front(stack.pop_stack()?);
@@ -151,7 +163,22 @@ pub fn syscall<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut Chunk
         None => None,
     };
 
-    if let Some(v) = f.call_function(instance, arguments.into(), context)? {
+    // A failed call can't suspend the execution
+    let value = f.call_function_with_invoker(instance, arguments.into(), invoker, context)
+        .inspect_err(|_| {
+            context.take_yield_request();
+        })?;
+
+    // The value will be provided by the host on resume
+    if context.has_yield_request() {
+        if value.is_some() {
+            return Err(VMError::InvalidYield);
+        }
+
+        return Ok(InstructionResult::Yield(id));
+    }
+
+    if let Some(v) = value {
         let memory_usage = v.calculate_memory_usage(context.memory_left())?;
         context.increase_memory_usage_unchecked(memory_usage)?;
 
@@ -29,7 +29,7 @@ pub fn iterator_begin<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut Chun
 }
 
 pub fn iterator_next<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let addr = manager.read_u32()?;
+    let addr = manager.operands()?.as_address()?;
     if let Some(value) = manager.next_iterator()? {
         let memory_usage = value.as_ref()?
             .calculate_memory_usage(context.memory_left())?;
@@ -37,7 +37,7 @@ pub fn iterator_next<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut Chunk
 
         stack.push_stack(value)?;
     } else {
-        manager.set_index(addr as usize)?;
+        manager.jump(addr);
     }
     Ok(InstructionResult::Nothing)
 }
@@ -5,7 +5,7 @@ use super::InstructionResult;
 
 
 pub fn memory_load<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u16()?;
+    let index = manager.operands()?.as_u16()?;
     let value = manager.from_register(index as usize)?;
     stack.push_stack(value.reference())?;
 
@@ -13,7 +13,7 @@ pub fn memory_load<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkMa
 }
 
 pub fn memory_set<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u16()?;
+    let index = manager.operands()?.as_u16()?;
     let value = stack.pop_stack()?;
     manager.set_register(index as usize, value, stack)?;
 
@@ -41,7 +41,7 @@ pub fn memory_len<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkMan
 }
 
 pub fn memory_to_owned<'a>(_: &Backend<'a>, _: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let index = manager.read_u16()?;
+    let index = manager.operands()?.as_u16()?;
     manager.to_owned_register(index as usize)?;
 
     Ok(InstructionResult::Nothing)
@@ -3,12 +3,16 @@ mod r#impl;
 mod iterator;
 mod constructor;
 mod memory;
+mod fused;
 
 use operator::*;
 use r#impl::*;
 use iterator::*;
 use constructor::*;
 use memory::*;
+use fused::*;
+
+pub(crate) use r#impl::call_native;
 
 use xelis_bytecode::OpCode;
 
@@ -21,6 +25,10 @@ pub enum InstructionResult {
     Nothing,
     Break,
     InvokeChunk(u16),
+    // A native function requested to suspend the execution
+    Yield(u16),
+    // A native function able to invoke chunks must be called by the VM
+    ReentrantSysCall(u16),
 }
 
 // A handler is a function pointer to an instruction
@@ -31,6 +39,7 @@ pub type Handler<'a> = (fn(&Backend<'a>, &mut Stack, &mut ChunkManager<'a>, &mut
 // It contains all the instructions that the VM can execute
 // It is a fixed size array of 256 elements
 // Each element is a function pointer to the instruction
+#[derive(Clone)]
 pub struct InstructionTable<'a> {
     instructions: [Handler<'a>; 256],
 }
@@ -120,6 +129,14 @@ impl<'a> InstructionTable<'a> {
         instructions[OpCode::Inc.as_usize()] = (increment, 1);
         instructions[OpCode::Dec.as_usize()] = (decrement, 1);
 
+        // Superinstructions cost the loads they replace
+        // The operator cost is charged on top of it
+        instructions[OpCode::MemoryOperator.as_usize()] = (memory_operator, 10);
+        instructions[OpCode::MemoryConstantOperator.as_usize()] = (memory_constant_operator, 6);
+        instructions[OpCode::MemoryConstantJumpIfFalse.as_usize()] = (memory_constant_jump_if_false, 9);
+        instructions[OpCode::MemoryAssignConstant.as_usize()] = (memory_assign_constant, 6);
+        instructions[OpCode::MemoryIncrement.as_usize()] = (memory_increment, 6);
+
         Self { instructions }
     }
 
@@ -133,6 +150,12 @@ impl<'a> InstructionTable<'a> {
         self.instructions[opcode.as_usize()].1 = cost;
     }
 
+    // Get the base cost of an instruction
+    #[inline]
+    pub fn get_instruction_cost(&self, opcode: u8) -> u64 {
+        self.instructions[opcode as usize].1
+    }
+
     // Execute an instruction
     pub fn execute(&self, opcode: u8, backend: &Backend<'a>, stack: &mut Stack, chunk_manager: &mut ChunkManager<'a>, context: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
         let (instruction, cost) = self.instructions[opcode as usize];
@@ -153,16 +176,16 @@ fn return_fn<'a>(_: &Backend<'a>, _: &mut Stack, _: &mut ChunkManager<'a>, _: &m
 }
 
 fn jump<'a>(_: &Backend<'a>, _: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let addr = manager.read_u32()?;
-    manager.set_index(addr as usize)?;
+    let addr = manager.operands()?.as_address()?;
+    manager.jump(addr);
     Ok(InstructionResult::Nothing)
 }
 
 fn jump_if_false<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let addr = manager.read_u32()?;
+    let addr = manager.operands()?.as_address()?;
     let value = stack.pop_stack()?;
     if !value.as_bool()? {
-        manager.set_index(addr as usize)?;
+        manager.jump(addr);
     }
     Ok(InstructionResult::Nothing)
 }
\ No newline at end of file
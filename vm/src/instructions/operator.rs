@@ -5,6 +5,7 @@ use crate::{
     Context,
     VMError
 };
+use xelis_bytecode::OpCode;
 use xelis_types::{Primitive, ValueCell, Type};
 
 use super::InstructionResult;
@@ -197,7 +198,7 @@ opcode_fn!(add, opcode_op, op_string, +);
 opcode_fn!(sub, opcode_op, op, -);
 opcode_fn!(mul, opcode_op, op, *);
 opcode_fn!(div, opcode_op, op_div, /);
-opcode_fn!(rem, opcode_op, op, %);
+opcode_fn!(rem, opcode_op, op_div, %);
 
 opcode_fn!(bitwise_and, opcode_op, op_bool, &);
 opcode_fn!(bitwise_or, opcode_op, op_bool, |);
@@ -214,8 +215,8 @@ opcode_fn!(lte, opcode_op, op_bool_res, <=);
 opcode_fn!(add_assign, opcode_op_assign, op_string, +);
 opcode_fn!(sub_assign, opcode_op_assign, op, -);
 opcode_fn!(mul_assign, opcode_op_assign ,op, *);
-opcode_fn!(div_assign, opcode_op_assign, op, /);
-opcode_fn!(rem_assign, opcode_op_assign, op, %);
+opcode_fn!(div_assign, opcode_op_assign, op_div, /);
+opcode_fn!(rem_assign, opcode_op_assign, op_div, %);
 
 opcode_fn!(bitwise_and_assign, opcode_op_assign, op_bool, &);
 opcode_fn!(bitwise_or_assign, opcode_op_assign, op_bool, |);
@@ -223,6 +224,32 @@ opcode_fn!(bitwise_xor_assign, opcode_op_assign, op_bool, ^);
 opcode_fn!(bitwise_shl_assign, opcode_op_assign, op, <<);
 opcode_fn!(bitwise_shr_assign, opcode_op_assign, op, >>);
 
+// Apply a binary operator on two values
+// Used by the superinstructions to share the same semantics
+// as the operators opcodes
+pub(super) fn apply_operator(op: OpCode, left: &ValueCell, right: &ValueCell) -> Result<Primitive, VMError> {
+    Ok(match op {
+        OpCode::Add => op_string!(left, right, +),
+        OpCode::Sub => op!(left, right, -),
+        OpCode::Mul => op!(left, right, *),
+        OpCode::Div => op_div!(left, right, /),
+        OpCode::Mod => op_div!(left, right, %),
+
+        OpCode::BitwiseAnd => op_bool!(left, right, &),
+        OpCode::BitwiseOr => op_bool!(left, right, |),
+        OpCode::BitwiseXor => op_bool!(left, right, ^),
+        OpCode::BitwiseShl => op!(left, right, <<),
+        OpCode::BitwiseShr => op!(left, right, >>),
+
+        OpCode::Eq => op_bool_all!(left, right, ==),
+        OpCode::Gt => op_bool_res!(left, right, >),
+        OpCode::Lt => op_bool_res!(left, right, <),
+        OpCode::Gte => op_bool_res!(left, right, >=),
+        OpCode::Lte => op_bool_res!(left, right, <=),
+        _ => return Err(VMError::InvalidOpCode)
+    })
+}
+
 pub fn neg<'a>(_: &Backend<'a>, stack: &mut Stack, _: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
     let value = stack.pop_stack()?;
     stack.push_stack_unchecked(Primitive::Boolean(!value.as_bool()?).into());
@@ -292,7 +319,7 @@ pub fn pow_assign<'a>(_: &Backend<'a>, stack: &mut Stack, _: &mut ChunkManager<'
 }
 
 pub fn cast<'a>(_: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
-    let _type = manager.read_type()?;
+    let _type = manager.operands()?.as_type()?;
     let mut current = stack.pop_stack()?
         .into_owned()?;
 
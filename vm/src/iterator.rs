@@ -16,6 +16,29 @@ impl ValueIterator {
         Ok(ValueIterator { inner, index })
     }
 
+    // Restore an iterator at the given index
+    pub(crate) fn with_index(inner: StackValue, index: Primitive) -> Self {
+        ValueIterator { inner, index }
+    }
+
+    // Get the value iterated
+    #[inline]
+    pub fn value(&self) -> &StackValue {
+        &self.inner
+    }
+
+    // Get the mutable value iterated
+    #[inline]
+    pub(crate) fn value_mut(&mut self) -> &mut StackValue {
+        &mut self.inner
+    }
+
+    // Get the next index to be read
+    #[inline]
+    pub fn index(&self) -> &Primitive {
+        &self.index
+    }
+
     pub fn next(&mut self) -> Result<Option<StackValue>, ValueError> {
         let index = self.index.clone();
         self.index.increment()?;
@@ -4,12 +4,23 @@ mod iterator;
 mod stack;
 mod validator;
 mod instructions;
+mod debugger;
+mod tracer;
+mod profiler;
+mod suspend;
+mod snapshot;
+mod config;
+mod nested;
+mod resolver;
 
 #[cfg(test)]
 mod tests;
 
-use stack::Stack;
+use std::{cell::RefCell, collections::{hash_map::Entry, HashMap}, rc::Rc, sync::{Arc, OnceLock}};
+
 use log::trace;
+use serde_json::Value;
+use nested::NestedInvoker;
 
 // Re-export the necessary types
 pub use xelis_environment::*;
@@ -20,13 +31,61 @@ pub use validator::*;
 pub use instructions::*;
 pub use error::VMError;
 pub use chunk::*;
-
-// 64 elements maximum in the call stack
+pub use stack::Stack;
+pub use debugger::*;
+pub use tracer::*;
+pub use profiler::*;
+pub use suspend::*;
+pub use snapshot::{SNAPSHOT_MAGIC, SNAPSHOT_FORMAT_VERSION};
+pub use iterator::ValueIterator;
+pub use config::VMConfig;
+pub use resolver::ModuleResolver;
+
+// 64 elements maximum in the call stack by default
 // This represents how many calls can be chained
-const CALL_STACK_SIZE: usize = 64;
+pub(crate) const CALL_STACK_SIZE: usize = 64;
+
+// Module with every chunk decoded, ready to be executed
+// It is decoded once when loaded and can be shared between the VMs
+pub struct DecodedModule<'a> {
+    module: &'a Module,
+    chunks: Box<[Arc<DecodedChunk>]>,
+    // Hash of the module, computed the first time it's requested
+    hash: OnceLock<[u8; 32]>
+}
+
+impl<'a> DecodedModule<'a> {
+    // Decode every chunk of a module
+    // The module is rejected if one of its chunks fails to decode
+    pub fn new(module: &'a Module) -> Result<Self, VMError> {
+        let chunks = module.chunks()
+            .iter()
+            .map(|chunk| DecodedChunk::decode(chunk).map(Arc::new))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            module,
+            chunks,
+            hash: OnceLock::new()
+        })
+    }
+
+    // Get the module decoded
+    #[inline]
+    pub fn module(&self) -> &'a Module {
+        self.module
+    }
+
+    // Get the hash of the module
+    // It is computed only once
+    pub fn hash(&self) -> [u8; 32] {
+        *self.hash.get_or_init(|| self.module.hash())
+    }
+}
 
 // Backend of the VM
 // This is the immutable part of the VM
+#[derive(Clone)]
 pub struct Backend<'a> {
     // The module to execute
     module: &'a Module,
@@ -34,15 +93,83 @@ pub struct Backend<'a> {
     environment: &'a Environment,
     // The instruction table of the VM
     table: InstructionTable<'a>,
+    // Limits and options of the VM
+    config: VMConfig,
+    // The module decoded
+    decoded: Arc<DecodedModule<'a>>,
+    // Every module executed, shared with the backends of the modules called
+    // so a module called several times is decoded only once
+    modules: Rc<RefCell<HashMap<*const Module, Arc<DecodedModule<'a>>>>>,
+    // Resolver of the modules that can be called by this one
+    resolver: Option<&'a dyn ModuleResolver<'a>>,
 }
 
 impl<'a> Backend<'a> {
+    // Create the backend of a decoded module
+    fn new(decoded: Arc<DecodedModule<'a>>, environment: &'a Environment, table: InstructionTable<'a>, config: VMConfig, resolver: Option<&'a dyn ModuleResolver<'a>>) -> Self {
+        let module = decoded.module();
+        let modules = HashMap::from([(module as *const Module, decoded.clone())]);
+
+        Self {
+            module,
+            environment,
+            table,
+            config,
+            decoded,
+            modules: Rc::new(RefCell::new(modules)),
+            resolver,
+        }
+    }
+
+    // Create the backend of a module called by this one
+    // The callee uses the same environment, instructions and limits
+    // Its chunks are decoded only the first time it is called
+    fn callee(&self, module: &'a Module) -> Result<Self, VMError> {
+        let decoded = match self.modules.borrow_mut().entry(module as *const Module) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => entry.insert(Arc::new(DecodedModule::new(module)?)).clone()
+        };
+
+        Ok(Self {
+            module,
+            environment: self.environment,
+            table: self.table.clone(),
+            config: self.config.clone(),
+            decoded,
+            modules: self.modules.clone(),
+            resolver: self.resolver,
+        })
+    }
+
     // Get a constant registered in the module using its id
     #[inline(always)]
     pub fn get_constant_with_id(&self, id: usize) -> Result<&ValueCell, VMError> {
         self.module.get_constant_at(id)
             .ok_or(VMError::ConstantNotFound)
     }
+
+    // Get the config of the VM
+    #[inline(always)]
+    pub fn config(&self) -> &VMConfig {
+        &self.config
+    }
+
+    // Get the hash of the module
+    // It is computed only once per module decoded
+    #[inline]
+    pub fn module_hash(&self) -> [u8; 32] {
+        self.decoded.hash()
+    }
+
+    // Get a chunk with its decoded instructions using its id
+    pub(crate) fn get_decoded_chunk(&self, id: usize) -> Result<(&'a Chunk, Arc<DecodedChunk>), VMError> {
+        let chunk = self.module.get_chunk_at(id)
+            .ok_or(VMError::ChunkNotFound)?;
+        let decoded = self.decoded.chunks.get(id)
+            .ok_or(VMError::ChunkNotFound)?;
+
+        Ok((chunk, decoded.clone()))
+    }
 }
 
 // Virtual Machine to execute the bytecode from chunks of a Module.
@@ -56,46 +183,150 @@ pub struct VM<'a, 'r> {
     stack: Stack,
     // Context given to each instruction
     context: Context<'a, 'r>,
-    // Flag to enable/disable the tail call optimization
-    // in our VM
-    tail_call_optimization: bool
+    // Syscall id of the native function that suspended the execution
+    suspended: Option<u16>,
+    // How many VMs are executing a native function that invoked this one
+    depth: usize,
+    // Chunks active in these parent VMs with their module
+    parent_chunks: Vec<(&'a Module, u16)>,
+    // State checkpoints opened for the frames of the call stack
+    state_checkpoints: usize
 }
 
 impl<'a, 'r> VM<'a, 'r> {
     // Create a new VM
+    // Every chunk of the module is decoded here, see `new_decoded` to decode it only once
+    pub fn new(module: &'a Module, environment: &'a Environment) -> Result<Self, VMError> {
+        Ok(Self::new_decoded(Arc::new(DecodedModule::new(module)?), environment))
+    }
+
+    // Create a new VM for a module already decoded
     // Insert the environment as a reference in the context
-    pub fn new(module: &'a Module, environment: &'a Environment) -> Self {
+    // and an empty log to collect the events emitted
+    pub fn new_decoded(decoded: Arc<DecodedModule<'a>>, environment: &'a Environment) -> Self {
         let mut context = Context::default();
         context.insert_ref(environment);
+        context.insert(EventLog::new());
 
-        Self::with(module, environment, InstructionTable::new(), context)
+        Self::with_decoded(decoded, environment, InstructionTable::new(), context, VMConfig::default())
+            .expect("default config must be valid")
     }
 
-    // Create a new VM with a given table and context
-    pub fn with(module: &'a Module, environment: &'a Environment, table: InstructionTable<'a>, context: Context<'a, 'r>) -> Self {
+    // Create a new VM with a given table, context and config
+    // Every chunk of the module is decoded here
+    pub fn with(module: &'a Module, environment: &'a Environment, table: InstructionTable<'a>, context: Context<'a, 'r>, config: VMConfig) -> Result<Self, VMError> {
+        let decoded = DecodedModule::new(module)?;
+        Self::with_decoded(Arc::new(decoded), environment, table, context, config)
+    }
+
+    // Create a new VM for a module already decoded with a given table, context and config
+    pub fn with_decoded(decoded: Arc<DecodedModule<'a>>, environment: &'a Environment, table: InstructionTable<'a>, context: Context<'a, 'r>, config: VMConfig) -> Result<Self, VMError> {
+        config.validate()?;
+
+        Ok(Self {
+            call_stack: Vec::with_capacity(4),
+            stack: Stack::with_max_size(config.max_stack_size),
+            backend: Backend::new(decoded, environment, table, config, None),
+            context,
+            suspended: None,
+            depth: 0,
+            parent_chunks: Vec::new(),
+            state_checkpoints: 0
+        })
+    }
+
+    // Create a child VM to run a chunk invoked by a native function
+    fn nested(backend: Backend<'a>, context: Context<'a, 'r>, depth: usize, parent_chunks: Vec<(&'a Module, u16)>) -> Self {
         Self {
-            backend: Backend {
-                module,
-                environment,
-                table,
-            },
             call_stack: Vec::with_capacity(4),
-            stack: Stack::new(),
+            stack: Stack::with_max_size(backend.config.max_stack_size),
+            backend,
             context,
-            tail_call_optimization: false
+            suspended: None,
+            depth,
+            parent_chunks,
+            state_checkpoints: 0
+        }
+    }
+
+    // Run a chunk invoked by a native function until it returns
+    // Arguments are given in the parameters order
+    // If the ABI is set, the chunk must be one of its entries
+    // and both the arguments and the returned value are verified against it
+    fn run_nested(&mut self, id: u16, args: Vec<ValueCell>, abi: Option<&Abi>) -> Result<Option<ValueCell>, VMError> {
+        let entry = match abi {
+            Some(abi) => {
+                let entry = abi.get_entry_by_chunk_id(id)
+                    .filter(|_| self.backend.module.is_entry_chunk(id as usize))
+                    .ok_or(VMError::ChunkNotEntry)?;
+
+                self.verify_abi_args(entry, &args)?;
+                Some(entry)
+            },
+            None => None
+        };
+
+        for arg in args.iter() {
+            let memory_usage = arg.calculate_memory_usage(self.context.memory_left())?;
+            self.context.increase_memory_usage_unchecked(memory_usage)?;
+        }
+
+        let values = self.verify_chunk_args(id as usize, args.into_iter().rev())?;
+        self.invoke_chunk_id(id)?;
+        self.push_frame_args(values)?;
+
+        while !self.call_stack.is_empty() {
+            self.execute_frame(false, &mut NoTracer)?;
+            if self.suspended.is_some() {
+                // The state changes can't be kept without the host resuming it
+                return Err(self.revert_state(VMError::ExecutionSuspended));
+            }
         }
+
+        // A chunk without a returned value leaves the stack empty
+        let result = if self.stack.count() == 0 {
+            Ok(None)
+        } else {
+            self.finish_internal().map(Some)
+        };
+
+        let result = result.and_then(|value| match entry {
+            Some(entry) => self.verify_abi_return(entry, value),
+            None => Ok(value)
+        });
+
+        self.close_state(result)
+    }
+
+    // Get the config of the VM
+    #[inline]
+    pub fn config(&self) -> &VMConfig {
+        &self.backend.config
+    }
+
+    // Set the resolver used to find the modules called by this one
+    // Without it, calls to other modules fail
+    #[inline]
+    pub fn set_module_resolver(&mut self, resolver: &'a dyn ModuleResolver<'a>) {
+        self.backend.resolver = Some(resolver);
     }
 
     // Check if the tail call optimization flag is enabled or not
     #[inline]
     pub fn has_tail_call_optimization(&self) -> bool {
-        self.tail_call_optimization
+        self.backend.config.tail_call_optimization
     }
 
     // Enable/disable the tail call optimization flag
     #[inline]
     pub fn set_tail_call_optimization(&mut self, value: bool) {
-        self.tail_call_optimization = value;
+        self.backend.config.tail_call_optimization = value;
+    }
+
+    // Check if the execution is suspended and waiting for the host
+    #[inline]
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.is_some()
     }
 
     // Get the stack
@@ -104,6 +335,13 @@ impl<'a, 'r> VM<'a, 'r> {
         &self.stack
     }
 
+    // Get the frames of the chunks invoked
+    // The last one is the chunk currently executed
+    #[inline]
+    pub fn call_stack(&self) -> &[ChunkManager<'a>] {
+        &self.call_stack
+    }
+
     // Get the context
     #[inline]
     pub fn context(&self) -> &Context<'a, 'r> {
@@ -136,51 +374,223 @@ impl<'a, 'r> VM<'a, 'r> {
 
     // Invoke a chunk using its id
     pub(crate) fn invoke_chunk_id(&mut self, id: u16) -> Result<(), VMError> {
-        if self.call_stack.len() >= CALL_STACK_SIZE {
-            return Err(VMError::CallStackOverflow);
+        let max_call_stack_size = self.backend.config.max_call_stack_size;
+        if self.call_stack.len() >= max_call_stack_size {
+            return Err(VMError::CallStackOverflow(max_call_stack_size));
         }
 
-        let chunk = self.backend.module.get_chunk_at(id as usize)
-            .ok_or(VMError::ChunkNotFound)?;
-
-        let manager = ChunkManager::new(chunk);
+        let (chunk, decoded) = self.backend.get_decoded_chunk(id as usize)?;
+        let manager = ChunkManager::new(id, chunk, decoded, self.backend.config.max_registers_size);
         self.call_stack.push(manager);
+        self.open_state_checkpoint();
         Ok(())
     }
 
+    // Push the arguments of the frame just invoked
+    // Its state checkpoint is reverted if they can't be pushed
+    fn push_frame_args(&mut self, values: Vec<StackValue>) -> Result<(), VMError> {
+        self.stack.extend_stack(values.into_iter())
+            .map_err(|e| self.revert_state(e))
+    }
+
+    // Apply a checkpoint operation on each journaled value of the context
+    // These are the journaled state, the event log and the storage handle
+    fn for_each_journal(context: &mut Context<'a, 'r>, mut f: impl FnMut(&mut dyn Journaled)) {
+        if let Some(state) = context.get_mut::<JournaledState>() {
+            f(state);
+        }
+
+        if let Some(log) = context.get_mut::<EventLog>() {
+            f(log);
+        }
+
+        if let Some(storage) = context.get_mut::<StorageHandle>() {
+            f(storage);
+        }
+    }
+
+    // Open a checkpoint in each journaled value for the frame invoked
+    fn open_state_checkpoint(&mut self) {
+        let mut opened = false;
+        Self::for_each_journal(&mut self.context, |journal| {
+            journal.checkpoint();
+            opened = true;
+        });
+
+        if opened {
+            self.state_checkpoints += 1;
+        }
+    }
+
+    // Commit the state checkpoint of the frame that returned
+    fn commit_state_checkpoint(&mut self) -> Result<(), VMError> {
+        if self.state_checkpoints == 0 {
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        Self::for_each_journal(&mut self.context, |journal| {
+            if let Err(e) = journal.commit() {
+                result = Err(e);
+            }
+        });
+        result?;
+
+        self.state_checkpoints -= 1;
+        Ok(())
+    }
+
+    // Revert the changes of all the frames left
+    // Every checkpoint is closed even if one fails to be reverted,
+    // the first failure is returned
+    fn rollback_state(&mut self) -> Result<(), EnvironmentError> {
+        let checkpoints = self.state_checkpoints;
+        self.state_checkpoints = 0;
+
+        let mut result = Ok(());
+        Self::for_each_journal(&mut self.context, |journal| {
+            for _ in 0..checkpoints {
+                if let Err(e) = journal.rollback() {
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+        });
+
+        result
+    }
+
+    // Revert the changes of all the frames left
+    // Called when the execution stops on an error,
+    // a failure to revert them is reported with it
+    fn revert_state(&mut self, error: VMError) -> VMError {
+        match self.rollback_state() {
+            Ok(()) => error,
+            Err(e) => VMError::RollbackFailed(Box::new(error), e)
+        }
+    }
+
     // Invoke an entry chunk using its id
+    // An entry with a signature in the module must be invoked with its arguments
+    #[inline]
     pub fn invoke_entry_chunk(&mut self, id: u16) -> Result<(), VMError> {
-        if !self.backend.module.is_entry_chunk(id as usize) {
-            return Err(VMError::ChunkNotEntry);
+        self.invoke_entry_chunk_with_args(id, std::iter::empty::<StackValue>())
+    }
+
+    // Check if a value matches the expected type
+    // Opaque types are resolved using the environment
+    #[inline]
+    fn is_value_of_type(&self, value: &ValueCell, ty: &Type) -> bool {
+        self.backend.environment.is_value_of_type(value, ty)
+    }
+
+    // Verify the arguments against the chunk signature
+    // Arguments are pushed in the given order, so the last one is the first parameter
+    // Chunks without a signature in the module accept any arguments
+    fn verify_chunk_args<V: Into<StackValue>, I: Iterator<Item = V> + ExactSizeIterator>(&self, id: usize, args: I) -> Result<Vec<StackValue>, VMError> {
+        let Some(parameters) = self.backend.module.get_chunk_signature(id) else {
+            return Ok(args.map(Into::into).collect());
+        };
+
+        if args.len() != parameters.len() {
+            return Err(VMError::InvalidArgumentsCount(parameters.len(), args.len()));
+        }
+
+        let values = args.map(Into::into).collect::<Vec<StackValue>>();
+        for (index, (value, parameter)) in values.iter().rev().zip(parameters).enumerate() {
+            if !self.is_value_of_type(value.as_ref()?, parameter) {
+                return Err(VMError::InvalidArgumentType(index));
+            }
+        }
+
+        Ok(values)
+    }
+
+    // Verify the arguments given in the parameters order against the ABI entry
+    fn verify_abi_args(&self, entry: &AbiEntry, args: &[ValueCell]) -> Result<(), VMError> {
+        if args.len() != entry.parameters.len() {
+            return Err(VMError::InvalidArgumentsCount(entry.parameters.len(), args.len()));
+        }
+
+        for (index, (value, parameter)) in args.iter().zip(&entry.parameters).enumerate() {
+            if !self.is_value_of_type(value, &parameter.value_type) {
+                return Err(VMError::InvalidArgumentType(index));
+            }
         }
-        self.invoke_chunk_id(id)
+
+        Ok(())
+    }
+
+    // Verify the value returned against the ABI entry
+    fn verify_abi_return(&self, entry: &AbiEntry, value: Option<ValueCell>) -> Result<Option<ValueCell>, VMError> {
+        let valid = match (&entry.return_type, &value) {
+            (Some(ty), Some(value)) => self.is_value_of_type(value, ty),
+            (None, None) => true,
+            _ => false
+        };
+
+        if !valid {
+            return Err(VMError::InvalidReturnValue);
+        }
+
+        Ok(value)
     }
 
     // Invoke an entry chunk using its id
+    // Arguments are verified against the entry signature if the module has one
     pub fn invoke_entry_chunk_with_args<V: Into<StackValue>, I: Iterator<Item = V> + ExactSizeIterator>(&mut self, id: u16, args: I) -> Result<(), VMError> {
-        self.invoke_entry_chunk(id)?;
-        self.stack.extend_stack(args.map(Into::into))?;
-        Ok(())
+        if !self.backend.module.is_entry_chunk(id as usize) {
+            return Err(VMError::ChunkNotEntry);
+        }
+
+        let values = self.verify_chunk_args(id as usize, args)?;
+        self.invoke_chunk_id(id)?;
+        self.push_frame_args(values)
+    }
+
+    // Invoke an entry chunk by its name with JSON encoded arguments
+    // The ABI is used to find the chunk id and to convert each argument to its declared type
+    pub fn invoke_entry(&mut self, abi: &Abi, name: &str, args: &[Value]) -> Result<(), VMError> {
+        let registry = self.backend.environment.get_opaque_registry();
+        let (id, values) = abi.entry_arguments(name, args, Some(registry))?;
+
+        // Arguments are pushed in reverse order like the InvokeChunk opcode does,
+        // so the first parameter is the first one stored in the registers
+        self.invoke_entry_chunk_with_args(id, values.into_iter().rev())
+    }
+
+    // Invoke an entry chunk by its name and run it until the end
+    // The returned value is converted to JSON using the entry return type
+    pub fn run_entry(&mut self, abi: &Abi, name: &str, args: &[Value]) -> Result<Value, VMError> {
+        self.invoke_entry(abi, name, args)?;
+        let value = self.run()?;
+
+        match abi.get_entry(name).and_then(|entry| entry.return_type.as_ref()) {
+            Some(ty) => Ok(abi.value_to_json(ty, &value)?),
+            None => Ok(Value::Null)
+        }
     }
 
     // Invoke a hook
+    // A hook with a signature in the module must be invoked with its arguments
     // Return true if the Module has an implementation for the hook
     // Return false if the hook isn't supported
+    #[inline]
     pub fn invoke_hook_id(&mut self, hook_id: u8) -> Result<bool, VMError> {
-        match self.backend.module.get_chunk_id_of_hook(hook_id) {
-            Some(id) => self.invoke_chunk_id(id as _).map(|_| true),
-            None => Ok(false)
-        }
+        self.invoke_hook_id_with_args(hook_id, std::iter::empty::<StackValue>())
     }
 
     // Invoke a hook with args
+    // Arguments are verified against the hook signature if the module has one
     // Return true if the Module has an implementation for the hook
     // Return false if the hook isn't supported
     pub fn invoke_hook_id_with_args<V: Into<StackValue>, I: Iterator<Item = V> + ExactSizeIterator>(&mut self, hook_id: u8, args: I) -> Result<bool, VMError> {
         match self.backend.module.get_chunk_id_of_hook(hook_id) {
             Some(id) => {
+                let values = self.verify_chunk_args(id, args)?;
                 self.invoke_chunk_id(id as _)?;
-                self.stack.extend_stack(args.map(Into::into))?;
+                self.push_frame_args(values)?;
                 Ok(true)
             },
             None => Ok(false)
@@ -192,53 +602,149 @@ impl<'a, 'r> VM<'a, 'r> {
         self.stack.push_stack(value.into())
     }
 
-    // Run the VM
-    // It will execute the bytecode
-    // First chunk executed should always return a value
-    pub fn run(&mut self) -> Result<ValueCell, VMError> {
-        while let Some(mut manager) = self.call_stack.pop() {
-            let mut clean_pointers = true;
-            while let Some(opcode) = manager.next_u8() {
-                match self.backend.table.execute(opcode, &self.backend, &mut self.stack, &mut manager, &mut self.context) {
-                    Ok(InstructionResult::Nothing) => {},
-                    Ok(InstructionResult::InvokeChunk(id)) => {
-                        if self.backend.module.is_entry_chunk(id as usize) {
-                            return Err(VMError::EntryChunkCalled);
-                        }
-
-                        // If tail call optimization is enabled,
-                        // we have another instruction and that its not a OpCode::Return
-                        // push current frame back to our call_stack
-                        // Otherwise, clean pointers for safety reasons
-                        if !self.tail_call_optimization || manager.has_next_instruction() {
-                            // We don't check the call stack size
-                            // because we've pop it from call stack
-                            // and it will be done below for next invoke
-                            self.call_stack.push(manager);
-                            clean_pointers = false;
-                        }
-
-                        self.invoke_chunk_id(id)?;
-                        break;
-                    },
-                    Ok(InstructionResult::Break) => {
-                        break;
-                    },
-                    Err(e) => {
-                        trace!("Error: {:?}", e);
-                        trace!("Stack: {:?}", self.stack.get_inner());
-                        trace!("Call stack left: {}", self.call_stack.len());
-                        trace!("Current registers: {:?}", manager.get_registers());
-                        return Err(e);
+    // Execute the instructions of the current frame
+    // It stops once the frame is left (invoke, return or end of chunk),
+    // or after the first instruction if `single` is set
+    // The tracer is only used if enabled, so no cost is added otherwise
+    // The state changes of the frames left are reverted on error
+    fn execute_frame<T: Tracer>(&mut self, single: bool, tracer: &mut T) -> Result<(), VMError> {
+        if self.suspended.is_some() {
+            return Err(VMError::ExecutionSuspended);
+        }
+
+        self.execute_frame_internal(single, tracer)
+            .map_err(|e| self.revert_state(e))
+    }
+
+    fn execute_frame_internal<T: Tracer>(&mut self, single: bool, tracer: &mut T) -> Result<(), VMError> {
+        let call_depth = self.call_stack.len();
+        let calls_left = call_depth.saturating_sub(1);
+        let Some(mut manager) = self.call_stack.last_mut() else {
+            return Ok(());
+        };
+
+        // Chunk to invoke and whether the current frame must be kept
+        let mut invoke = None;
+        while let Some(opcode) = manager.next_opcode() {
+            let step = if T::ENABLED {
+                let (offset, operands) = manager.current_bytes()
+                    .ok_or(VMError::MissingInstruction)?;
+
+                let step = TraceStep {
+                    chunk_id: manager.chunk_id(),
+                    offset,
+                    opcode,
+                    operands,
+                    call_depth,
+                    stack_depth: self.stack.count(),
+                    gas: self.context.current_gas_usage(),
+                    memory: self.context.current_memory_usage()
+                };
+                tracer.before_instruction(&step);
+                Some(step)
+            } else {
+                None
+            };
+
+            let mut result = self.backend.table.execute(opcode, &self.backend, &mut self.stack, manager, &mut self.context);
+            if let Ok(InstructionResult::ReentrantSysCall(id)) = result {
+                let (_, on_value, args) = manager.operands()?.as_call()?;
+                let mut invoker = NestedInvoker::new(&self.backend, self.depth, &self.parent_chunks, &self.call_stack);
+                result = call_native(&self.backend, &mut self.stack, &mut self.context, id, on_value, args, &mut invoker);
+                manager = self.call_stack.last_mut()
+                    .expect("current frame must be in the call stack");
+            }
+            if let Some(step) = step {
+                tracer.after_instruction(&step, &TraceResult {
+                    stack_depth: self.stack.count(),
+                    gas: self.context.current_gas_usage(),
+                    memory: self.context.current_memory_usage(),
+                    error: result.as_ref().err()
+                });
+            }
+
+            match result {
+                Ok(InstructionResult::Nothing) => if single {
+                    return Ok(());
+                },
+                Ok(InstructionResult::InvokeChunk(id)) => {
+                    if self.backend.module.is_entry_chunk(id as usize) {
+                        return Err(VMError::EntryChunkCalled);
                     }
+
+                    // If tail call optimization is enabled,
+                    // we have another instruction and that its not a OpCode::Return
+                    // keep current frame in our call_stack
+                    // Otherwise, clean pointers for safety reasons
+                    invoke = Some((id, !self.backend.config.tail_call_optimization || manager.has_next_instruction()));
+                    break;
+                },
+                Ok(InstructionResult::Break) => {
+                    break;
+                },
+                // Called right after the instruction, it can't be returned again
+                Ok(InstructionResult::ReentrantSysCall(_)) => {
+                    return Err(VMError::UnknownSysCall);
+                },
+                // The frame is kept as is to continue after the syscall
+                Ok(InstructionResult::Yield(id)) => {
+                    self.suspended = Some(id);
+                    return Ok(());
+                },
+                Err(e) => {
+                    trace!("Error: {:?}", e);
+                    trace!("Stack: {:?}", self.stack.get_inner());
+                    trace!("Call stack left: {}", calls_left);
+                    trace!("Current registers: {:?}", manager.get_registers());
+                    return Err(e);
                 }
             }
+        }
 
-            if clean_pointers {
-                self.stack.checkpoint_clean()?;
-            }
+        // The current frame is resumed once the invoked chunk returns
+        if let Some((id, true)) = invoke {
+            return self.invoke_chunk_id(id);
         }
 
+        // The frame must outlive the cleaning as the stack
+        // may still contain pointers to its registers
+        let frame = self.call_stack.pop();
+        self.stack.checkpoint_clean()?;
+        drop(frame);
+
+        // The checkpoint of the last frame is kept open
+        // until its returned value is verified by `finish`
+        if !self.call_stack.is_empty() || invoke.is_some() {
+            self.commit_state_checkpoint()?;
+        }
+
+        match invoke {
+            Some((id, _)) => self.invoke_chunk_id(id),
+            None => Ok(())
+        }
+    }
+
+    // Get the value returned by the first chunk executed
+    // The stack must be empty after it
+    // Changes of the execution are kept only if the final value is valid
+    fn finish(&mut self) -> Result<ValueCell, VMError> {
+        let result = self.finish_internal();
+        self.close_state(result)
+    }
+
+    // Commit the state checkpoints left open if the execution succeeded
+    // Otherwise every change is reverted
+    fn close_state<T>(&mut self, result: Result<T, VMError>) -> Result<T, VMError> {
+        let value = result.map_err(|e| self.revert_state(e))?;
+        while self.state_checkpoints > 0 {
+            self.commit_state_checkpoint()
+                .map_err(|e| self.revert_state(e))?;
+        }
+
+        Ok(value)
+    }
+
+    fn finish_internal(&mut self) -> Result<ValueCell, VMError> {
         let end_value = self.stack.pop_stack()?
             .into_owned()?;
         if self.stack.count() != 0 {
@@ -247,4 +753,124 @@ impl<'a, 'r> VM<'a, 'r> {
 
         Ok(end_value)
     }
+
+    // Execute the next instruction only
+    // Returns the final value once the call stack is empty
+    // If a native function yields, `resume` must be called before the next step
+    #[inline]
+    pub fn step(&mut self) -> Result<Option<ValueCell>, VMError> {
+        self.step_with_tracer(&mut NoTracer)
+    }
+
+    // Same as `step` but the instruction is reported to the tracer
+    pub fn step_with_tracer<T: Tracer>(&mut self, tracer: &mut T) -> Result<Option<ValueCell>, VMError> {
+        self.execute_frame(true, tracer)?;
+        if self.call_stack.is_empty() {
+            return self.finish().map(Some);
+        }
+
+        Ok(None)
+    }
+
+    // Run the VM
+    // It will execute the bytecode
+    // First chunk executed should always return a value
+    // Native functions yielding to the host are only supported through `execute`,
+    // the execution is aborted and its changes are reverted if one yields
+    #[inline]
+    pub fn run(&mut self) -> Result<ValueCell, VMError> {
+        self.run_with_tracer(&mut NoTracer)
+    }
+
+    // Same as `run` but every instruction executed is reported to the tracer
+    pub fn run_with_tracer<T: Tracer>(&mut self, tracer: &mut T) -> Result<ValueCell, VMError> {
+        match self.execute_with_tracer(tracer)? {
+            ExecutionState::Finished(value) => Ok(value),
+            ExecutionState::Suspended(_) => Err(self.abort(VMError::ExecutionSuspended))
+        }
+    }
+
+    // Stop the execution left and revert its changes
+    // The VM can invoke a new chunk after it
+    fn abort(&mut self, error: VMError) -> VMError {
+        // Values may still point to the registers of the frames
+        self.stack.get_inner_mut().clear();
+        self.call_stack.clear();
+        self.suspended = None;
+        self.context.take_yield_request();
+
+        self.revert_state(error)
+    }
+
+    // Same as `run` but the events emitted are returned with the final value
+    // They are taken from the event log, so the log is empty for the next execution
+    // No events are returned if the context has no event log
+    pub fn run_with_events(&mut self) -> Result<(ValueCell, Vec<Event>), VMError> {
+        let value = self.run()?;
+        let events = self.context.get_mut::<EventLog>()
+            .map(EventLog::take_events)
+            .unwrap_or_default();
+
+        Ok((value, events))
+    }
+
+    // Run the VM until the end or until a native function yields to the host
+    #[inline]
+    pub fn execute(&mut self) -> Result<ExecutionState, VMError> {
+        self.execute_with_tracer(&mut NoTracer)
+    }
+
+    // Same as `execute` but every instruction executed is reported to the tracer
+    pub fn execute_with_tracer<T: Tracer>(&mut self, tracer: &mut T) -> Result<ExecutionState, VMError> {
+        while !self.call_stack.is_empty() {
+            self.execute_frame(false, tracer)?;
+            if let Some(pending) = self.pending_call() {
+                return Ok(ExecutionState::Suspended(pending));
+            }
+        }
+
+        self.finish().map(ExecutionState::Finished)
+    }
+
+    // Build the pending call of a suspended execution
+    fn pending_call(&mut self) -> Option<PendingCall> {
+        let syscall_id = self.suspended?;
+        let expects_value = self.backend.environment.get_functions()
+            .get(syscall_id as usize)
+            .is_some_and(|f| f.return_type().is_some());
+
+        Some(PendingCall {
+            syscall_id,
+            payload: self.context.take_yield_request().unwrap_or_default(),
+            expects_value
+        })
+    }
+
+    // Provide the result of the suspended native function
+    // The value is checked against the function return type
+    // Execution continues from the instruction following the syscall
+    pub fn resume(&mut self, value: Option<ValueCell>) -> Result<ExecutionState, VMError> {
+        self.resume_with_tracer(value, &mut NoTracer)
+    }
+
+    // Same as `resume` but every instruction executed is reported to the tracer
+    pub fn resume_with_tracer<T: Tracer>(&mut self, value: Option<ValueCell>, tracer: &mut T) -> Result<ExecutionState, VMError> {
+        let syscall_id = self.suspended.ok_or(VMError::ExecutionNotSuspended)?;
+        let f = self.backend.environment.get_functions()
+            .get(syscall_id as usize)
+            .ok_or(VMError::UnknownSysCall)?;
+
+        match (value, f.return_type()) {
+            (Some(value), Some(ty)) if self.is_value_of_type(&value, ty) => {
+                let memory_usage = value.calculate_memory_usage(self.context.memory_left())?;
+                self.context.increase_memory_usage_unchecked(memory_usage)?;
+                self.stack.push_stack(value.into())?;
+            },
+            (None, None) => {},
+            _ => return Err(VMError::InvalidResumeValue)
+        };
+
+        self.suspended = None;
+        self.execute_with_tracer(tracer)
+    }
 }
\ No newline at end of file
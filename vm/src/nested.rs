@@ -0,0 +1,93 @@
+use std::{mem, ptr};
+
+use xelis_bytecode::{Abi, Module};
+use xelis_environment::{ChunkInvoker, Context, EnvironmentError};
+use xelis_types::ValueCell;
+
+use crate::{Backend, ChunkManager, VMError, VM};
+
+// Invoker given to the native functions called by a VM
+// Each chunk invoked runs in a child VM sharing the same context,
+// so the gas and memory used are accounted to the parent execution
+pub(crate) struct NestedInvoker<'b, 'a> {
+    backend: &'b Backend<'a>,
+    // Depth of the VM calling the native function, zero for the root VM
+    depth: usize,
+    // Chunks active in the parents of the VM calling the native function
+    parent_chunks: &'b [(&'a Module, u16)],
+    // Frames of the VM calling the native function
+    frames: &'b [ChunkManager<'a>]
+}
+
+impl<'b, 'a> NestedInvoker<'b, 'a> {
+    pub fn new(backend: &'b Backend<'a>, depth: usize, parent_chunks: &'b [(&'a Module, u16)], frames: &'b [ChunkManager<'a>]) -> Self {
+        Self {
+            backend,
+            depth,
+            parent_chunks,
+            frames
+        }
+    }
+
+    // Chunks currently active with their module
+    fn active_chunks(&self) -> impl Iterator<Item = (&'a Module, u16)> + '_ {
+        self.parent_chunks.iter()
+            .copied()
+            .chain(self.frames.iter().map(|frame| (self.backend.module, frame.chunk_id())))
+    }
+
+    // Run the chunk of the backend module in a child VM
+    // The context is moved to the child VM and given back once it's done
+    // If the ABI is set, the chunk is called as one of its entries
+    fn run<'r>(&self, backend: Backend<'a>, id: u16, args: Vec<ValueCell>, abi: Option<&Abi>, context: &mut Context<'a, 'r>) -> Result<Option<ValueCell>, EnvironmentError> {
+        let guard = self.backend.environment.get_reentrancy_guard();
+        if self.depth >= guard.max_depth {
+            return Err(EnvironmentError::ReentrancyDepthExceeded(guard.max_depth));
+        }
+
+        if !guard.allow_active_chunks && self.active_chunks().any(|(module, chunk)| ptr::eq(module, backend.module) && chunk == id) {
+            return Err(EnvironmentError::ReentrantCall(id));
+        }
+
+        let mut vm = VM::nested(
+            backend,
+            mem::take(context),
+            self.depth + 1,
+            self.active_chunks().collect()
+        );
+        let result = vm.run_nested(id, args, abi);
+        *context = vm.context;
+
+        result.map_err(into_environment_error)
+    }
+}
+
+// Errors of the VM are reported to the native function as environment errors
+fn into_environment_error(error: VMError) -> EnvironmentError {
+    match error {
+        VMError::EnvironmentError(e) => e,
+        e => EnvironmentError::Any(e.into())
+    }
+}
+
+impl<'a, 'r> ChunkInvoker<'a, 'r> for NestedInvoker<'_, 'a> {
+    fn invoke_chunk(&mut self, id: u16, args: Vec<ValueCell>, context: &mut Context<'a, 'r>) -> Result<Option<ValueCell>, EnvironmentError> {
+        self.run(self.backend.clone(), id, args, None, context)
+    }
+
+    // Only the entries declared in the ABI of the callee can be called
+    fn invoke_module(&mut self, module: &ValueCell, entry: u16, args: Vec<ValueCell>, context: &mut Context<'a, 'r>) -> Result<Option<ValueCell>, EnvironmentError> {
+        let resolver = self.backend.resolver
+            .ok_or(EnvironmentError::InvokerUnavailable)?;
+        let (module, abi) = resolver.resolve_module(module)
+            .ok_or(EnvironmentError::UnknownModule)?;
+
+        let backend = self.backend.callee(module)
+            .map_err(into_environment_error)?;
+        self.run(backend, entry, args, Some(abi), context)
+    }
+
+    fn module_hash(&self) -> Result<[u8; 32], EnvironmentError> {
+        Ok(self.backend.module_hash())
+    }
+}
@@ -0,0 +1,240 @@
+use std::{cmp::Reverse, fmt};
+
+use indexmap::IndexMap;
+use xelis_bytecode::OpCode;
+
+use crate::{TraceResult, TraceStep, Tracer, VM};
+
+// Count of executions and gas attributed to them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasUsage {
+    pub count: u64,
+    pub gas: u64
+}
+
+impl GasUsage {
+    #[inline]
+    fn add(&mut self, gas: u64) {
+        self.count += 1;
+        self.gas += gas;
+    }
+}
+
+// Tracer attributing the gas used to each chunk, opcode and syscall
+// Gas of an instruction is split between:
+// - the memory allocated (memory delta * memory price per byte)
+// - the base cost of the instruction from the table
+// - the syscall cost for the remaining part of a SysCall
+pub struct GasProfiler {
+    // Base cost of each opcode
+    instructions_costs: Vec<u64>,
+    memory_price_per_byte: u64,
+    chunks_names: IndexMap<u16, String>,
+    syscalls_names: IndexMap<u16, String>,
+    // Chunks ids from the first invoked to the current one
+    path: Vec<u16>,
+    instructions_gas: u64,
+    syscalls_gas: u64,
+    memory_gas: u64,
+    // Chunk id => gas used by its own instructions
+    by_chunk: IndexMap<u16, GasUsage>,
+    by_opcode: IndexMap<u8, GasUsage>,
+    by_syscall: IndexMap<u16, GasUsage>,
+    // (call path, syscall id) => gas used
+    stacks: IndexMap<(Vec<u16>, Option<u16>), u64>
+}
+
+impl GasProfiler {
+    // Create a profiler using the costs configured in the VM
+    pub fn new(vm: &VM) -> Self {
+        let table = vm.table();
+        Self {
+            instructions_costs: (0..=u8::MAX).map(|opcode| table.get_instruction_cost(opcode)).collect(),
+            memory_price_per_byte: vm.context().memory_price_per_byte(),
+            chunks_names: IndexMap::new(),
+            syscalls_names: IndexMap::new(),
+            path: Vec::new(),
+            instructions_gas: 0,
+            syscalls_gas: 0,
+            memory_gas: 0,
+            by_chunk: IndexMap::new(),
+            by_opcode: IndexMap::new(),
+            by_syscall: IndexMap::new(),
+            stacks: IndexMap::new()
+        }
+    }
+
+    // Set the name displayed for a chunk
+    pub fn set_chunk_name(&mut self, chunk_id: u16, name: impl Into<String>) {
+        self.chunks_names.insert(chunk_id, name.into());
+    }
+
+    // Set the name displayed for a syscall
+    pub fn set_syscall_name(&mut self, syscall_id: u16, name: impl Into<String>) {
+        self.syscalls_names.insert(syscall_id, name.into());
+    }
+
+    // Get the total gas attributed
+    #[inline]
+    pub fn total_gas(&self) -> u64 {
+        self.instructions_gas + self.syscalls_gas + self.memory_gas
+    }
+
+    // Get the gas used by the base cost of instructions
+    #[inline]
+    pub fn instructions_gas(&self) -> u64 {
+        self.instructions_gas
+    }
+
+    // Get the gas used by the syscalls costs
+    #[inline]
+    pub fn syscalls_gas(&self) -> u64 {
+        self.syscalls_gas
+    }
+
+    // Get the gas used by the memory allocated
+    #[inline]
+    pub fn memory_gas(&self) -> u64 {
+        self.memory_gas
+    }
+
+    // Get the instructions executed and gas used by each chunk
+    #[inline]
+    pub fn by_chunk(&self) -> &IndexMap<u16, GasUsage> {
+        &self.by_chunk
+    }
+
+    // Get the executions and gas used by each opcode
+    #[inline]
+    pub fn by_opcode(&self) -> &IndexMap<u8, GasUsage> {
+        &self.by_opcode
+    }
+
+    // Get the calls and cost of each syscall
+    #[inline]
+    pub fn by_syscall(&self) -> &IndexMap<u16, GasUsage> {
+        &self.by_syscall
+    }
+
+    // Get the folded stacks output, one "frame;frame gas" line per call path
+    // This is the input format of flamegraph tools
+    pub fn folded(&self) -> String {
+        let mut output = String::new();
+        for ((path, syscall), gas) in self.stacks.iter() {
+            let mut frames = path.iter()
+                .map(|id| self.chunk_name(*id))
+                .collect::<Vec<_>>();
+
+            if let Some(id) = syscall {
+                frames.push(self.syscall_name(*id));
+            }
+
+            output.push_str(&frames.join(";"));
+            output.push(' ');
+            output.push_str(&gas.to_string());
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn chunk_name(&self, id: u16) -> String {
+        self.chunks_names.get(&id)
+            .cloned()
+            .unwrap_or_else(|| format!("chunk_{}", id))
+    }
+
+    fn syscall_name(&self, id: u16) -> String {
+        self.syscalls_names.get(&id)
+            .cloned()
+            .unwrap_or_else(|| format!("syscall_{}", id))
+    }
+
+    fn add_stack(&mut self, syscall: Option<u16>, gas: u64) {
+        if gas == 0 {
+            return;
+        }
+
+        let key = (self.path.clone(), syscall);
+        *self.stacks.entry(key).or_insert(0) += gas;
+    }
+}
+
+impl Tracer for GasProfiler {
+    fn before_instruction(&mut self, step: &TraceStep) {
+        self.path.truncate(step.call_depth.saturating_sub(1));
+        self.path.push(step.chunk_id);
+    }
+
+    fn after_instruction(&mut self, step: &TraceStep, result: &TraceResult) {
+        let mut left = result.gas.saturating_sub(step.gas);
+
+        let memory = (result.memory.saturating_sub(step.memory) as u64)
+            .saturating_mul(self.memory_price_per_byte)
+            .min(left);
+        left -= memory;
+
+        let base = self.instructions_costs[step.opcode as usize].min(left);
+        left -= base;
+
+        let syscall = match step.op_code() {
+            Some(OpCode::SysCall) if step.operands.len() >= 2 => Some(u16::from_le_bytes([step.operands[0], step.operands[1]])),
+            _ => None
+        };
+
+        // Any extra gas of a regular instruction is part of its cost
+        let (instruction, syscall_gas) = match syscall {
+            Some(_) => (base, left),
+            None => (base + left, 0)
+        };
+
+        self.memory_gas += memory;
+        self.instructions_gas += instruction;
+        self.syscalls_gas += syscall_gas;
+
+        self.by_chunk.entry(step.chunk_id).or_default().add(memory + instruction);
+        self.by_opcode.entry(step.opcode).or_default().add(instruction);
+        self.add_stack(None, memory + instruction);
+
+        if let Some(id) = syscall {
+            self.by_syscall.entry(id).or_default().add(syscall_gas);
+            self.add_stack(Some(id), syscall_gas);
+        }
+    }
+}
+
+impl fmt::Display for GasProfiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total gas: {}", self.total_gas())?;
+        writeln!(f, "  instructions: {}", self.instructions_gas)?;
+        writeln!(f, "  syscalls: {}", self.syscalls_gas)?;
+        writeln!(f, "  memory: {}", self.memory_gas)?;
+
+        let mut chunks = self.by_chunk.iter().collect::<Vec<_>>();
+        chunks.sort_by_key(|(_, usage)| Reverse(usage.gas));
+        writeln!(f, "By chunk:")?;
+        for (id, usage) in chunks {
+            writeln!(f, "  {}: {} gas, {} instructions", self.chunk_name(*id), usage.gas, usage.count)?;
+        }
+
+        let mut opcodes = self.by_opcode.iter().collect::<Vec<_>>();
+        opcodes.sort_by_key(|(_, usage)| Reverse(usage.gas));
+        writeln!(f, "By opcode:")?;
+        for (opcode, usage) in opcodes {
+            match OpCode::from_byte(*opcode) {
+                Some(op) => write!(f, "  {:?}", op)?,
+                None => write!(f, "  0x{:02x}", opcode)?
+            };
+            writeln!(f, ": {} gas, {} executions", usage.gas, usage.count)?;
+        }
+
+        let mut syscalls = self.by_syscall.iter().collect::<Vec<_>>();
+        syscalls.sort_by_key(|(_, usage)| Reverse(usage.gas));
+        writeln!(f, "By syscall:")?;
+        for (id, usage) in syscalls {
+            writeln!(f, "  {}: {} gas, {} calls", self.syscall_name(*id), usage.gas, usage.count)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+use xelis_bytecode::{Abi, Module};
+use xelis_types::ValueCell;
+
+// Provided by the host to find the modules that can be called
+// from another module using a key, for example a contract address
+pub trait ModuleResolver<'a> {
+    // Get the module registered under the key with its ABI
+    // The ABI is used to verify the arguments and the value returned by each call
+    // None if no module is known for it
+    fn resolve_module(&self, key: &ValueCell) -> Option<(&'a Module, &'a Abi)>;
+}
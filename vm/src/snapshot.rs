@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use xelis_types::{
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    Primitive,
+    StackValue,
+    ValueCell
+};
+
+use crate::{
+    ChunkManager,
+    ValueIterator,
+    VMError,
+    VM
+};
+
+// Magic header written at the start of every snapshot
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"XVMS";
+
+// Current version of the snapshot format
+pub const SNAPSHOT_FORMAT_VERSION: u8 = 1;
+
+// Slot of the VM owning a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Root {
+    Stack(usize),
+    // (frame, register index)
+    Register(usize, usize),
+    // (frame, iterator index)
+    Iterator(usize, usize)
+}
+
+// Position of a value pointed in the VM
+// The path is the index of each sub value from the root
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Location {
+    root: Root,
+    path: Vec<usize>
+}
+
+impl Location {
+    fn write(&self, writer: &mut Writer) {
+        match self.root {
+            Root::Stack(index) => {
+                writer.write_u8(0);
+                writer.write_varint(index as u64);
+            },
+            Root::Register(frame, index) => {
+                writer.write_u8(1);
+                writer.write_varint(frame as u64);
+                writer.write_varint(index as u64);
+            },
+            Root::Iterator(frame, index) => {
+                writer.write_u8(2);
+                writer.write_varint(frame as u64);
+                writer.write_varint(index as u64);
+            }
+        };
+
+        writer.write_varint(self.path.len() as u64);
+        for index in self.path.iter() {
+            writer.write_varint(*index as u64);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let root = match reader.read_u8()? {
+            0 => Root::Stack(reader.read_index()?),
+            1 => Root::Register(reader.read_index()?, reader.read_index()?),
+            2 => Root::Iterator(reader.read_index()?, reader.read_index()?),
+            tag => return Err(ReaderError::InvalidTag(tag))
+        };
+
+        let len = reader.read_len()?;
+        let mut path = Vec::with_capacity(len);
+        for _ in 0..len {
+            path.push(reader.read_index()?);
+        }
+
+        Ok(Self { root, path })
+    }
+}
+
+// Pointer read from a snapshot, resolved once all the values are restored
+struct PendingPointer {
+    slot: Root,
+    origin: Option<Location>,
+    ptr: Location,
+    depth: usize
+}
+
+// Locations of every value owned by the VM
+struct Locations(HashMap<*const ValueCell, Location>);
+
+impl Locations {
+    fn add(&mut self, root: Root, value: &StackValue) {
+        let StackValue::Owned(value) = value else {
+            return;
+        };
+
+        let mut queue = vec![(value, Vec::new())];
+        while let Some((value, path)) = queue.pop() {
+            match value {
+                ValueCell::Array(values) => queue.extend(values.iter()
+                    .enumerate()
+                    .map(|(index, value)| (value, [path.as_slice(), &[index]].concat()))
+                ),
+                ValueCell::Map(map) => queue.extend(map.values()
+                    .enumerate()
+                    .map(|(index, value)| (value, [path.as_slice(), &[index]].concat()))
+                ),
+                _ => {}
+            };
+
+            self.0.insert(value as *const ValueCell, Location { root, path });
+        }
+    }
+
+    fn get(&self, ptr: *mut ValueCell) -> Result<&Location, VMError> {
+        self.0.get(&(ptr as *const ValueCell))
+            .ok_or(VMError::InvalidSnapshot)
+    }
+
+    fn write_value(&self, value: &StackValue, writer: &mut Writer) -> Result<(), VMError> {
+        match value {
+            StackValue::Owned(value) => {
+                writer.write_u8(0);
+                value.write(writer);
+            },
+            StackValue::Pointer { origin, ptr, depth } => {
+                writer.write_u8(1);
+                match origin {
+                    Some(origin) => {
+                        writer.write_bool(true);
+                        self.get(*origin)?.write(writer);
+                    },
+                    None => writer.write_bool(false)
+                };
+                self.get(*ptr)?.write(writer);
+                writer.write_varint(*depth as u64);
+            }
+        };
+
+        Ok(())
+    }
+}
+
+// Read a value, a pointer is replaced by a placeholder until resolved
+fn read_value(reader: &mut Reader, slot: Root, pointers: &mut Vec<PendingPointer>) -> Result<StackValue, ReaderError> {
+    Ok(match reader.read_u8()? {
+        0 => StackValue::Owned(ValueCell::read(reader)?),
+        1 => {
+            let origin = if reader.read_bool()? {
+                Some(Location::read(reader)?)
+            } else {
+                None
+            };
+
+            pointers.push(PendingPointer {
+                slot,
+                origin,
+                ptr: Location::read(reader)?,
+                depth: reader.read_index()?
+            });
+
+            StackValue::Owned(ValueCell::default())
+        },
+        tag => return Err(ReaderError::InvalidTag(tag))
+    })
+}
+
+impl<'a, 'r> VM<'a, 'r> {
+    // Serialize the state of the execution
+    // Layout:
+    // - magic header and format version
+    // - gas and memory used, suspended syscall id if any
+    // - stack: varint count, each value
+    // - call stack: varint count, each frame with its chunk id, index, registers and iterators
+    // Pointers are written as the location of the value pointed
+    pub fn snapshot(&self) -> Result<Vec<u8>, VMError> {
+        let mut locations = Locations(HashMap::new());
+        for (index, value) in self.stack.get_inner().iter().enumerate() {
+            locations.add(Root::Stack(index), value);
+        }
+
+        for (frame, manager) in self.call_stack.iter().enumerate() {
+            for (index, value) in manager.get_registers().iter().enumerate() {
+                locations.add(Root::Register(frame, index), value);
+            }
+
+            for (index, iterator) in manager.get_iterators().iter().enumerate() {
+                locations.add(Root::Iterator(frame, index), iterator.value());
+            }
+        }
+
+        let mut writer = Writer::new();
+        writer.write_bytes(&SNAPSHOT_MAGIC);
+        writer.write_u8(SNAPSHOT_FORMAT_VERSION);
+
+        writer.write_u64(self.context.current_gas_usage());
+        writer.write_varint(self.context.current_memory_usage() as u64);
+        match self.suspended {
+            Some(id) => {
+                writer.write_bool(true);
+                writer.write_u16(id);
+            },
+            None => writer.write_bool(false)
+        };
+
+        writer.write_varint(self.stack.count() as u64);
+        for value in self.stack.get_inner() {
+            locations.write_value(value, &mut writer)?;
+        }
+
+        writer.write_varint(self.call_stack.len() as u64);
+        for manager in self.call_stack.iter() {
+            writer.write_u16(manager.chunk_id());
+            writer.write_varint(manager.index() as u64);
+
+            let registers = manager.get_registers();
+            writer.write_varint(registers.len() as u64);
+            for value in registers {
+                locations.write_value(value, &mut writer)?;
+            }
+
+            let iterators = manager.get_iterators();
+            writer.write_varint(iterators.len() as u64);
+            for iterator in iterators {
+                locations.write_value(iterator.value(), &mut writer)?;
+                iterator.index().write(&mut writer);
+            }
+        }
+
+        Ok(writer.into_bytes())
+    }
+
+    // Restore the state of an execution from a snapshot
+    // The VM must use the same module and environment as the one snapshotted
+    // The current execution state is replaced
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), VMError> {
+        let mut reader = Reader::new(bytes)
+            .with_opaque_registry(self.backend.environment.get_opaque_registry());
+
+        if reader.read_array::<4>()? != SNAPSHOT_MAGIC {
+            return Err(ReaderError::InvalidMagic.into());
+        }
+
+        let version = reader.read_u8()?;
+        if version != SNAPSHOT_FORMAT_VERSION {
+            return Err(ReaderError::UnsupportedVersion(version).into());
+        }
+
+        let gas = reader.read_u64()?;
+        let memory = reader.read_index()?;
+        let suspended = if reader.read_bool()? {
+            Some(reader.read_u16()?)
+        } else {
+            None
+        };
+
+        let mut pointers = Vec::new();
+
+        let config = &self.backend.config;
+
+        let count = reader.read_len()?;
+        if count > config.max_stack_size {
+            return Err(VMError::InvalidSnapshot);
+        }
+
+        // Reserve the whole stack so the values pointed are never moved
+        let mut stack = Vec::with_capacity(config.max_stack_size);
+        for index in 0..count {
+            stack.push(read_value(&mut reader, Root::Stack(index), &mut pointers)?);
+        }
+
+        let frames = reader.read_len()?;
+        if frames > config.max_call_stack_size {
+            return Err(VMError::InvalidSnapshot);
+        }
+
+        let mut call_stack = Vec::with_capacity(frames);
+        for frame in 0..frames {
+            let id = reader.read_u16()?;
+            let index = reader.read_index()?;
+            let (chunk, decoded) = self.backend.get_decoded_chunk(id as usize)?;
+
+            let count = reader.read_len()?;
+            let mut registers = Vec::with_capacity(count);
+            for index in 0..count {
+                registers.push(read_value(&mut reader, Root::Register(frame, index), &mut pointers)?);
+            }
+
+            let count = reader.read_len()?;
+            let mut iterators = Vec::with_capacity(count);
+            for index in 0..count {
+                let value = read_value(&mut reader, Root::Iterator(frame, index), &mut pointers)?;
+                iterators.push(ValueIterator::with_index(value, Primitive::read(&mut reader)?));
+            }
+
+            call_stack.push(ChunkManager::restore(id, chunk, decoded, config.max_registers_size, index, registers, iterators)?);
+        }
+
+        reader.expect_end()?;
+
+        if suspended.is_some_and(|id| self.backend.environment.get_functions().get(id as usize).is_none()) {
+            return Err(VMError::InvalidSnapshot);
+        }
+
+        // Changes of the execution replaced are reverted
+        // A checkpoint is opened for each frame restored, so the changes done
+        // from now can be reverted, the ones done before the snapshot can't
+        self.rollback_state()?;
+        for _ in 0..call_stack.len() {
+            self.open_state_checkpoint();
+        }
+
+        // Every value is at its final place, pointers can now be resolved
+        *self.stack.get_inner_mut() = stack;
+        self.call_stack = call_stack;
+
+        // A pointer can only target an owned value
+        let slots = pointers.iter()
+            .map(|pointer| pointer.slot)
+            .collect::<Vec<_>>();
+
+        for pointer in pointers {
+            let targets_pointer = pointer.origin.iter()
+                .chain([&pointer.ptr])
+                .any(|location| slots.contains(&location.root));
+
+            if targets_pointer {
+                return Err(VMError::InvalidSnapshot);
+            }
+
+            let origin = match pointer.origin {
+                Some(origin) => Some(self.resolve_location(&origin)?),
+                None => None
+            };
+            let ptr = self.resolve_location(&pointer.ptr)?;
+
+            *self.slot_mut(pointer.slot)? = StackValue::Pointer {
+                origin,
+                ptr,
+                depth: pointer.depth
+            };
+        }
+
+        self.context.set_current_gas_usage(gas);
+        self.context.set_current_memory_usage(memory);
+        self.context.take_yield_request();
+        self.suspended = suspended;
+
+        Ok(())
+    }
+
+    // Get the value stored in a slot
+    fn slot_mut(&mut self, root: Root) -> Result<&mut StackValue, VMError> {
+        let value = match root {
+            Root::Stack(index) => self.stack.get_inner_mut().get_mut(index),
+            Root::Register(frame, index) => self.call_stack.get_mut(frame)
+                .and_then(|manager| manager.get_registers_mut().get_mut(index)),
+            Root::Iterator(frame, index) => self.call_stack.get_mut(frame)
+                .and_then(|manager| manager.get_iterators_mut().get_mut(index))
+                .map(ValueIterator::value_mut)
+        };
+
+        value.ok_or(VMError::InvalidSnapshot)
+    }
+
+    // Find the owned value at the location
+    fn resolve_location(&mut self, location: &Location) -> Result<*mut ValueCell, VMError> {
+        let StackValue::Owned(root) = self.slot_mut(location.root)? else {
+            return Err(VMError::InvalidSnapshot);
+        };
+
+        let mut value = root;
+
+        for index in location.path.iter() {
+            value = match value {
+                ValueCell::Array(values) => values.get_mut(*index),
+                ValueCell::Map(map) => map.get_index_mut(*index).map(|(_, value)| value),
+                _ => None
+            }.ok_or(VMError::InvalidSnapshot)?;
+        }
+
+        Ok(value as *mut ValueCell)
+    }
+}
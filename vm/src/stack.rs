@@ -4,19 +4,39 @@ use super::VMError;
 
 // 256 elements maximum in the stack:
 // Function Call can have up to 255 arguments and 1 on value
-const STACK_SIZE: usize = 256;
+pub(crate) const STACK_SIZE: usize = 256;
 
 pub struct Stack {
-    stack: Vec<StackValue>
+    stack: Vec<StackValue>,
+    // Maximum values in the stack
+    max_size: usize
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Stack {
     pub fn new() -> Self {
+        Self::with_max_size(STACK_SIZE)
+    }
+
+    // Create a stack holding up to `max_size` values
+    pub fn with_max_size(max_size: usize) -> Self {
         Self {
-            stack: Vec::with_capacity(16)
+            stack: Vec::with_capacity(16),
+            max_size
         }
     }
 
+    // Get the maximum values in the stack
+    #[inline]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
     pub fn checkpoint_clean(&mut self) -> Result<(), VMError> {
         for value in self.stack.iter_mut() {
             value.make_owned()?;
@@ -28,8 +48,8 @@ impl Stack {
     // Push a value to the stack
     #[inline]
     pub fn push_stack(&mut self, value: StackValue) -> Result<(), VMError> {
-        if self.stack.len() >= STACK_SIZE {
-            return Err(VMError::StackOverflow);
+        if self.stack.len() >= self.max_size {
+            return Err(VMError::StackOverflow(self.max_size));
         }
 
         self.push_stack_unchecked(value);
@@ -84,8 +104,8 @@ impl Stack {
     // Push multiple values to the stack
     #[inline]
     pub fn extend_stack<I: IntoIterator<Item = StackValue> + ExactSizeIterator>(&mut self, values: I) -> Result<(), VMError> {
-        if self.stack.len() + values.len() >= STACK_SIZE {
-            return Err(VMError::StackOverflow);
+        if self.stack.len() + values.len() >= self.max_size {
+            return Err(VMError::StackOverflow(self.max_size));
         }
 
         self.stack.extend(values);
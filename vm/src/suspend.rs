@@ -0,0 +1,34 @@
+use xelis_types::ValueCell;
+
+// Native function call that suspended the execution
+// The host does the requested work and resumes the VM with the result
+#[derive(Debug)]
+pub struct PendingCall {
+    // Syscall id of the native function
+    pub syscall_id: u16,
+    // Payload given by the native function to the host
+    pub payload: ValueCell,
+    // Whether a value is expected to resume the execution
+    pub expects_value: bool
+}
+
+// State of the VM once it stopped executing
+#[derive(Debug)]
+pub enum ExecutionState {
+    // The call stack is empty, this is the returned value
+    Finished(ValueCell),
+    // A native function yielded to the host
+    // `VM::resume` must be called to continue the execution
+    Suspended(PendingCall)
+}
+
+impl ExecutionState {
+    // Get the returned value if the execution is finished
+    #[inline]
+    pub fn finished(self) -> Option<ValueCell> {
+        match self {
+            Self::Finished(value) => Some(value),
+            Self::Suspended(_) => None
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use xelis_compiler::Compiler;
+use serde_json::json;
+use xelis_compiler::{generate_abi, Compiler};
 use xelis_environment::{Environment, EnvironmentError};
 use xelis_builder::EnvironmentBuilder;
 use xelis_lexer::Lexer;
 use xelis_parser::Parser;
-use xelis_types::{traits::{JSONHelper, Serializable}, Primitive};
+use xelis_types::{serializer::Serializer, traits::{JSONHelper, Serializable}, Primitive};
 use super::*;
 
 #[track_caller]
@@ -22,6 +23,20 @@ fn prepare_module_with(code: &str, env: EnvironmentBuilder) -> (Module, Environm
     (module, env)
 }
 
+// Compile the code and generate its ABI
+#[track_caller]
+fn prepare_module_with_abi(code: &str) -> (Module, Abi, Environment) {
+    let env = EnvironmentBuilder::default();
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let (program, _) = Parser::with(tokens.into_iter(), &env).parse().unwrap();
+    let abi = generate_abi(&program).unwrap();
+
+    let env = env.build();
+    let module = Compiler::new(&program, &env).compile().unwrap();
+
+    (module, abi, env)
+}
+
 #[track_caller]
 fn try_run_code(code: &str, id: u16) -> Result<Primitive, VMError> {
     let (module, environment) = prepare_module(code);
@@ -65,7 +80,7 @@ fn test_max_gas() {
     "#;
 
     let (module, environment) = prepare_module(code);
-    let mut vm = VM::new(&module, &environment);
+    let mut vm = VM::new(&module, &environment).unwrap();
     vm.context_mut().set_gas_limit(1000);
     vm.invoke_entry_chunk(0).unwrap();
 
@@ -1389,4 +1404,1207 @@ fn test_types_compatibility() {
         return 0
     }", env);
     run_internal(module, &env, 0).unwrap();
-}
\ No newline at end of file
+}
+#[test]
+fn test_invoke_entry_by_name() {
+    let code = r#"
+        struct Point { x: u64, y: u64 }
+        enum Action { Add { amount: u64 }, Reset }
+        entry noop() { return 0 }
+        entry compute(p: Point, action: Action, scale: u64, bonus: map<string, u64>) {
+            return (p.x + p.y) * scale + bonus.get("extra").unwrap()
+        }
+    "#;
+
+    let env = EnvironmentBuilder::default();
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let (program, _) = Parser::with(tokens.into_iter(), &env).parse().unwrap();
+    let abi = generate_abi(&program).unwrap();
+
+    let env = env.build();
+    let module = Compiler::new(&program, &env).compile().unwrap();
+    ModuleValidator::new(&module, &env).verify().unwrap();
+
+    let args = [
+        json!({ "x": 1, "y": 2 }),
+        json!({ "variant": "Add", "fields": { "amount": 5 } }),
+        json!(10),
+        json!({ "extra": 7 })
+    ];
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    assert_eq!(vm.run_entry(&abi, "compute", &args).unwrap(), json!(37));
+
+    // Wrong arguments are rejected before running
+    let mut vm = VM::new(&module, &env).unwrap();
+    assert!(matches!(
+        vm.invoke_entry(&abi, "compute", &args[..2]),
+        Err(VMError::AbiError(AbiError::InvalidArgumentsCount(4, 2)))
+    ));
+    assert!(matches!(
+        vm.invoke_entry(&abi, "compute", &[json!({ "x": 1 }), args[1].clone(), json!(10), json!({})]),
+        Err(VMError::AbiError(AbiError::MissingField(_)))
+    ));
+    assert!(matches!(
+        vm.invoke_entry(&abi, "compute", &[args[0].clone(), args[1].clone(), json!("ten"), json!({})]),
+        Err(VMError::AbiError(AbiError::ExpectedType(Type::U64)))
+    ));
+    assert!(matches!(
+        vm.invoke_entry(&abi, "unknown", &[]),
+        Err(VMError::AbiError(AbiError::UnknownEntry(_)))
+    ));
+}
+
+#[test]
+fn test_entry_arguments_type_check() {
+    let code = r#"
+        struct Point { x: u64, y: u64 }
+        hook on_init(data: u8) -> bool { return data == 1 }
+        entry sum(p: Point, extra: optional<u64>) {
+            return p.x + p.y + extra.unwrap_or(0)
+        }
+    "#;
+
+    let mut env = EnvironmentBuilder::default();
+    env.register_hook("on_init", vec![("data", Type::U8)], Some(Type::Bool));
+    let (module, env) = prepare_module_with(code, env);
+    ModuleValidator::new(&module, &env).verify().unwrap();
+    assert_eq!(module.get_chunk_signature(1).map(Vec::len), Some(2));
+
+    let point = || ValueCell::Array(vec![Primitive::U64(1).into(), Primitive::U64(2).into()]);
+
+    // Arguments are pushed in order, the last one is the first parameter
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk_with_args(1, [Primitive::Null.into(), point()].into_iter()).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U64(3).into());
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk_with_args(1, [Primitive::U64(4).into(), point()].into_iter()).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U64(7).into());
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    assert!(matches!(
+        vm.invoke_entry_chunk_with_args(1, [point()].into_iter()),
+        Err(VMError::InvalidArgumentsCount(2, 1))
+    ));
+    assert!(matches!(
+        vm.invoke_entry_chunk_with_args(1, [ValueCell::from(Primitive::Null), Primitive::U64(1).into()].into_iter()),
+        Err(VMError::InvalidArgumentType(0))
+    ));
+    assert!(matches!(
+        vm.invoke_entry_chunk_with_args(1, [Primitive::U8(4).into(), point()].into_iter()),
+        Err(VMError::InvalidArgumentType(1))
+    ));
+    assert!(matches!(
+        vm.invoke_hook_id_with_args(0, [ValueCell::from(Primitive::U64(1))].into_iter()),
+        Err(VMError::InvalidArgumentType(0))
+    ));
+
+    // Arguments can't be pushed without being verified
+    assert!(matches!(vm.invoke_entry_chunk(1), Err(VMError::InvalidArgumentsCount(2, 0))));
+    assert!(matches!(vm.invoke_hook_id(0), Err(VMError::InvalidArgumentsCount(1, 0))));
+
+    // Nothing was invoked by the rejected calls
+    assert!(vm.invoke_hook_id_with_args(0, [ValueCell::from(Primitive::U8(1))].into_iter()).unwrap());
+    assert_eq!(vm.run().unwrap(), Primitive::Boolean(true).into());
+}
+
+#[test]
+fn test_debugger() {
+    let code = r#"
+        fn add(a: u64, b: u64) -> u64 {
+            return a + b
+        }
+
+        entry main() {
+            let a: u64 = add(1, 2)
+            return add(a, 10)
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+    let mut debugger = Debugger::new(&mut vm);
+    assert_eq!(debugger.location(), Some((1, 0)));
+
+    // Step into the first call
+    while debugger.frames().len() == 1 {
+        assert!(matches!(debugger.step_into().unwrap(), StopReason::Step));
+    }
+    assert_eq!(debugger.location(), Some((0, 0)));
+    assert_eq!(debugger.frames()[0].chunk_id(), 1);
+
+    // Step out returns in main with the result on the stack
+    assert!(matches!(debugger.step_out().unwrap(), StopReason::Step));
+    assert_eq!(debugger.frames().len(), 1);
+    assert_eq!(debugger.stack().last_stack().unwrap().as_ref().unwrap(), &Primitive::U64(3).into());
+    assert!(debugger.gas_usage() > 0);
+
+    // Stepping over never enters the second call
+    loop {
+        match debugger.step_over().unwrap() {
+            StopReason::Step => assert_eq!(debugger.frames().len(), 1),
+            StopReason::Finished(value) => {
+                assert_eq!(value, Primitive::U64(13).into());
+                break;
+            },
+            StopReason::Breakpoint(..) => panic!("no breakpoint set")
+        }
+    }
+}
+
+#[test]
+fn test_debugger_breakpoints() {
+    let code = r#"
+        fn add(a: u64, b: u64) -> u64 {
+            return a + b
+        }
+
+        entry main() {
+            let a: u64 = add(1, 2)
+            return add(a, 10)
+        }
+    "#;
+
+    // Lines are mapped by the compiler using the position of the tokens
+    let env = EnvironmentBuilder::default();
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let (program, _) = Parser::with(tokens.into_iter(), &env).parse().unwrap();
+    let env = env.build();
+    let (module, debug_info) = Compiler::new(&program, &env).compile_with_debug_info().unwrap();
+    assert_eq!(debug_info.get_offsets_of_line(7), vec![(1, 0)]);
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+    let mut debugger = Debugger::new(&mut vm).with_debug_info(&debug_info);
+    assert_eq!(debugger.add_line_breakpoint(3), 1);
+    assert_eq!(debugger.current_line(), Some(7));
+
+    // The breakpoint is hit on each call of add
+    for a in [1u64, 3] {
+        assert!(matches!(debugger.continue_execution().unwrap(), StopReason::Breakpoint(0, 6)));
+        assert_eq!(debugger.current_line(), Some(3));
+        assert_eq!(debugger.frames().last().unwrap().get_registers()[0].as_ref().unwrap(), &Primitive::U64(a).into());
+    }
+
+    assert!(debugger.remove_breakpoint(0, 6));
+    assert!(!debugger.remove_breakpoint(0, 6));
+
+    // A breakpoint reached while stepping over stops the step
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+    let mut debugger = Debugger::new(&mut vm);
+    assert!(debugger.add_breakpoint(0, 0));
+    while debugger.frames().len() == 1 {
+        if let StopReason::Breakpoint(chunk_id, offset) = debugger.step_over().unwrap() {
+            assert_eq!((chunk_id, offset), (0, 0));
+            break;
+        }
+    }
+    assert_eq!(debugger.frames().len(), 2);
+
+    debugger.clear_breakpoints();
+    assert!(matches!(debugger.continue_execution().unwrap(), StopReason::Finished(v) if v == Primitive::U64(13).into()));
+}
+
+#[test]
+fn test_tracer() {
+    let code = r#"
+        fn div(a: u64, b: u64) -> u64 {
+            return a / b
+        }
+
+        entry main() {
+            return div(10, 2)
+        }
+
+        entry fail() {
+            return div(10, 0)
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+    let run = |id: u16| {
+        let mut vm = VM::new(&module, &env).unwrap();
+        vm.invoke_entry_chunk(id).unwrap();
+        let mut log = TraceLog::new();
+        let result = vm.run_with_tracer(&mut log);
+        (result, log.into_entries())
+    };
+
+    let (result, entries) = run(1);
+    assert_eq!(result.unwrap(), Primitive::U64(5).into());
+
+    let first = &entries[0];
+    assert_eq!((first.chunk_id, first.offset, first.call_depth, first.stack_depth_before), (1, 0, 1, 0));
+    assert_eq!(first.opcode, OpCode::Constant.as_byte());
+    assert_eq!(first.operands.len(), 2);
+    assert_eq!(first.stack_depth_after, 1);
+
+    // Every instruction consumes gas and the entries are chained
+    for pair in entries.windows(2) {
+        assert!(pair[0].gas_after > pair[0].gas_before);
+        assert_eq!(pair[0].gas_after, pair[1].gas_before);
+    }
+    assert!(entries.iter().any(|entry| entry.chunk_id == 0 && entry.call_depth == 2));
+    assert!(entries.iter().all(|entry| entry.error.is_none()));
+
+    // Executions are deterministic
+    assert_eq!(run(1).1, entries);
+
+    // The failing instruction is traced
+    let (result, entries) = run(2);
+    assert!(matches!(result, Err(VMError::DivisionByZero)));
+    let last = entries.last().unwrap();
+    assert_eq!(last.chunk_id, 0);
+    assert_eq!(last.error, Some(VMError::DivisionByZero.to_string()));
+
+    // Tracing a single step
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+    let mut log = TraceLog::new();
+    assert!(vm.step_with_tracer(&mut log).unwrap().is_none());
+    assert_eq!(log.entries().len(), 1);
+}
+
+#[test]
+fn test_gas_profiler() {
+    let code = r#"
+        fn sum(values: u64[]) -> u64 {
+            let total: u64 = 0;
+            foreach value in values {
+                total += value;
+            }
+            return total
+        }
+
+        entry main() {
+            let values: u64[] = [1, 2, 3];
+            return sum(values) + values.len() as u64
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+
+    let mut profiler = GasProfiler::new(&vm);
+    profiler.set_chunk_name(1, "main");
+    profiler.set_chunk_name(0, "sum");
+
+    assert_eq!(vm.run_with_tracer(&mut profiler).unwrap(), Primitive::U64(9).into());
+
+    // All the gas used is attributed
+    assert_eq!(profiler.total_gas(), vm.context().current_gas_usage());
+    assert_eq!(profiler.total_gas(), profiler.instructions_gas() + profiler.syscalls_gas() + profiler.memory_gas());
+    assert!(profiler.memory_gas() > 0);
+
+    let total_by_chunk: u64 = profiler.by_chunk().values().map(|usage| usage.gas).sum();
+    let total_by_syscall: u64 = profiler.by_syscall().values().map(|usage| usage.gas).sum();
+    assert_eq!(total_by_chunk + total_by_syscall, profiler.total_gas());
+    assert_eq!(profiler.by_syscall().values().map(|usage| usage.count).sum::<u64>(), 1);
+    assert!(profiler.by_opcode().contains_key(&OpCode::InvokeChunk.as_byte()));
+
+    // Folded stacks cover the whole gas with the nested calls
+    let folded = profiler.folded();
+    let total_folded: u64 = folded.lines()
+        .map(|line| line.rsplit_once(' ').unwrap().1.parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(total_folded, profiler.total_gas());
+    assert!(folded.lines().any(|line| line.starts_with("main;sum ")));
+    assert!(folded.lines().any(|line| line.starts_with("main;syscall_")));
+
+    let report = profiler.to_string();
+    assert!(report.contains("main:"));
+    assert!(report.contains("InvokeChunk"));
+}
+
+#[test]
+fn test_yield_and_resume() {
+    let build_env = || {
+        let mut env = EnvironmentBuilder::default();
+        env.register_native_function("load", None, vec![("key", Type::String)], |_, mut params, context| {
+            let key = params.remove(0).into_owned()?;
+            context.request_yield(key);
+            Ok(None)
+        }, 5, Some(Type::U64));
+
+        env.register_native_function("notify", None, vec![], |_, _, context| {
+            context.request_yield(Primitive::Null.into());
+            Ok(None)
+        }, 0, None);
+
+        env
+    };
+
+    let code = r#"
+        entry main() {
+            let a: u64 = load("a");
+            notify();
+            return a + load("b")
+        }
+    "#;
+
+    let (module, env) = prepare_module_with(code, build_env());
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(0).unwrap();
+
+    let ExecutionState::Suspended(pending) = vm.execute().unwrap() else {
+        panic!("expected a suspension");
+    };
+    assert_eq!(pending.payload, Primitive::String("a".to_owned()).into());
+    assert!(pending.expects_value);
+    assert!(vm.is_suspended());
+
+    // Can't continue without the value
+    assert!(matches!(vm.run(), Err(VMError::ExecutionSuspended)));
+    assert!(matches!(vm.resume(None), Err(VMError::InvalidResumeValue)));
+    assert!(matches!(vm.resume(Some(Primitive::U8(1).into())), Err(VMError::InvalidResumeValue)));
+
+    let ExecutionState::Suspended(pending) = vm.resume(Some(Primitive::U64(10).into())).unwrap() else {
+        panic!("expected a suspension");
+    };
+    assert!(!pending.expects_value);
+
+    let ExecutionState::Suspended(pending) = vm.resume(None).unwrap() else {
+        panic!("expected a suspension");
+    };
+    assert_eq!(pending.payload, Primitive::String("b".to_owned()).into());
+
+    let value = vm.resume(Some(Primitive::U64(32).into())).unwrap().finished();
+    assert_eq!(value, Some(Primitive::U64(42).into()));
+    assert!(!vm.is_suspended());
+    assert!(matches!(vm.resume(None), Err(VMError::ExecutionNotSuspended)));
+
+    // Run doesn't support the suspension
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(0).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::ExecutionSuspended)));
+
+    // The execution is aborted and its changes are reverted
+    assert!(!vm.is_suspended());
+    assert!(vm.call_stack().is_empty());
+    assert_eq!(vm.get_stack().count(), 0);
+
+    let code = r#"
+        entry main() {
+            emit(0u16, "before");
+            notify();
+            return 0
+        }
+    "#;
+
+    let (module, env) = prepare_module_with(code, build_env());
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(0).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::ExecutionSuspended)));
+    assert!(vm.context_mut().get_mut::<EventLog>().unwrap().take_events().is_empty());
+
+    vm.invoke_entry_chunk(0).unwrap();
+    assert!(matches!(vm.execute(), Ok(ExecutionState::Suspended(_))));
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let code = r#"
+        struct Point {
+            x: u64,
+            y: u64
+        }
+
+        fn shift(points: Point[], delta: u64) -> u64 {
+            let total: u64 = 0;
+            foreach point in points {
+                total += point.x + point.y + delta;
+            }
+            return total
+        }
+
+        entry main() {
+            let points: Point[] = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+            for i: u64 = 0; i < 3; i += 1 {
+                points[1].y += i;
+            }
+            let scores: map<string, u64> = {};
+            scores.insert("a", 5);
+            return shift(points, 2) + scores.get("a").unwrap()
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+    let expected = vm.run().unwrap();
+    let gas = vm.context().current_gas_usage();
+
+    // Snapshot at every instruction, the restored execution must be identical
+    let mut steps = 0;
+    loop {
+        let mut vm = VM::new(&module, &env).unwrap();
+        vm.invoke_entry_chunk(1).unwrap();
+        let mut finished = false;
+        for _ in 0..steps {
+            if vm.step().unwrap().is_some() {
+                finished = true;
+                break;
+            }
+        }
+
+        if finished {
+            break;
+        }
+
+        let snapshot = vm.snapshot().unwrap();
+        let mut restored = VM::new(&module, &env).unwrap();
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot().unwrap(), snapshot);
+
+        assert_eq!(restored.run().unwrap(), expected);
+        assert_eq!(restored.context().current_gas_usage(), gas);
+        steps += 1;
+    }
+    assert!(steps > 10);
+
+    // Invalid snapshots are rejected
+    let mut vm = VM::new(&module, &env).unwrap();
+    assert!(vm.restore(b"XVMS").is_err());
+    assert!(vm.restore(&[0; 8]).is_err());
+}
+
+#[test]
+fn test_vm_config_limits() {
+    let code = r#"
+        fn depth(n: u64) -> u64 {
+            if n == 0 {
+                return 0
+            }
+            return depth(n - 1) + 1
+        }
+
+        fn tail(n: u64) -> u64 {
+            if n == 0 {
+                return 0
+            }
+            return tail(n - 1)
+        }
+
+        entry main() {
+            return depth(10)
+        }
+
+        entry main_tail() {
+            return tail(10)
+        }
+
+        entry array() {
+            let a: u64 = 1;
+            let values: u64[] = [a, a, a, a, a, a, a, a];
+            return values.len() as u64
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+    let run_with = |id: u16, config: VMConfig| {
+        let mut context = Context::default();
+        context.insert_ref(&env);
+        let mut vm = VM::with(&module, &env, InstructionTable::new(), context, config)?;
+        vm.invoke_entry_chunk(id)?;
+        vm.run().map(|mut v| v.into_value().unwrap())
+    };
+
+    let small_call_stack = VMConfig {
+        max_call_stack_size: 4,
+        ..Default::default()
+    };
+    assert_eq!(run_with(2, VMConfig::default()).unwrap(), Primitive::U64(10));
+    assert!(matches!(run_with(2, small_call_stack.clone()), Err(VMError::CallStackOverflow(4))));
+
+    // Tail calls don't keep their frame
+    assert!(matches!(run_with(3, small_call_stack.clone()), Err(VMError::CallStackOverflow(4))));
+    let tail_call = VMConfig {
+        tail_call_optimization: true,
+        ..small_call_stack
+    };
+    assert_eq!(run_with(3, tail_call).unwrap(), Primitive::U64(0));
+
+    let small_stack = VMConfig {
+        max_stack_size: 4,
+        ..Default::default()
+    };
+    assert_eq!(run_with(4, VMConfig::default()).unwrap(), Primitive::U64(8));
+    assert!(matches!(run_with(4, small_stack.clone()), Err(VMError::StackOverflow(4))));
+
+    // The validator uses the same limits
+    let mut validator = ModuleValidator::new(&module, &env);
+    validator.set_config(small_stack);
+    assert!(matches!(validator.verify(), Err(ValidatorError::StackOverflow(4, _))));
+
+    let invalid = VMConfig {
+        max_registers_size: 0,
+        ..Default::default()
+    };
+    assert!(matches!(run_with(2, invalid), Err(VMError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_native_invoke_chunk() {
+    let mut env = EnvironmentBuilder::default();
+    env.register_reentrant_function("invoke", None, vec![("id", Type::U16), ("value", Type::U64)], |_, mut params, invoker, context| {
+        let value = params.remove(1).into_owned()?;
+        let id = params.remove(0).as_ref()?.as_u16()?;
+        invoker.invoke_chunk(id, vec![value], context)
+    }, 5, Some(Type::U64));
+
+    let code = r#"
+        fn double(x: u64) -> u64 {
+            return x * 2
+        }
+
+        fn reenter(x: u64) -> u64 {
+            return invoke(1, x)
+        }
+
+        entry main() {
+            return invoke(0, 21)
+        }
+
+        entry main_reenter() {
+            return invoke(1, 1)
+        }
+    "#;
+
+    let (module, env) = prepare_module_with(code, env);
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_chunk_id(0).unwrap();
+    vm.push_stack(Primitive::U64(21)).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U64(42).into());
+    let double_gas = vm.context().current_gas_usage();
+
+    // The gas of the nested execution is accounted in the same context
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(2).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U64(42).into());
+    assert!(vm.context().current_gas_usage() > double_gas + 5);
+
+    // A chunk already active can't be invoked again by default
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(3).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::ReentrantCall(1)))));
+
+    let mut env = env.clone();
+    env.set_reentrancy_guard(ReentrancyGuard {
+        max_depth: 3,
+        allow_active_chunks: true
+    });
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(3).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::ReentrancyDepthExceeded(3)))));
+}
+
+struct TestResolver<'a>(Vec<(&'static str, &'a Module, &'a Abi)>);
+
+impl<'a> ModuleResolver<'a> for TestResolver<'a> {
+    fn resolve_module(&self, key: &ValueCell) -> Option<(&'a Module, &'a Abi)> {
+        let key = key.as_string().ok()?;
+        self.0.iter()
+            .find(|(name, _, _)| *name == key)
+            .map(|(_, module, abi)| (*module, *abi))
+    }
+}
+
+#[test]
+fn test_call_module() {
+    let callee_code = r#"
+        entry add(a: u64, b: u64) {
+            return a + b
+        }
+
+        entry fail(x: u64) {
+            require(x == 0, "reverted");
+            return x
+        }
+
+        entry burn(x: u64) {
+            while true {}
+            return x
+        }
+
+        fn helper(x: u64) -> u64 {
+            return x
+        }
+
+        event Ping {}
+
+        entry ping(x: u64) {
+            Ping.emit();
+            return x
+        }
+    "#;
+
+    let caller_code = r#"
+        entry add() {
+            return call_module("callee", 0u16, [40, 2])
+        }
+
+        entry call(id: u16) {
+            return call_module("callee", id, [1])
+        }
+
+        entry unknown() {
+            return call_module("other", 0u16, [40, 2])
+        }
+
+        entry wrong_type() {
+            return call_module("callee", 0u16, ["40", "2"])
+        }
+
+        entry twice() {
+            let a: u64 = call_module("callee", 0u16, [40, 2]);
+            let b: u64 = call_module("callee", 0u16, [a, 2]);
+            return b
+        }
+
+        entry lying() {
+            return call_module("liar", 0u16, [40, 2])
+        }
+
+        entry ping() {
+            emit(0u16, "caller");
+            let _: u64 = call_module("callee", 4u16, [0]);
+            return 0
+        }
+    "#;
+
+    let (callee, callee_abi, _) = prepare_module_with_abi(callee_code);
+    let (caller, env) = prepare_module(caller_code);
+
+    // ABI declaring another return type than the one of the module
+    let mut liar_abi = callee_abi.clone();
+    liar_abi.entries[0].return_type = Some(Type::String);
+
+    let resolver = TestResolver(vec![("callee", &callee, &callee_abi), ("liar", &callee, &liar_abi)]);
+
+    let run = |id: u16, args: Vec<Primitive>, gas_limit: Option<u64>| {
+        let mut vm = VM::new(&caller, &env).unwrap();
+        vm.set_module_resolver(&resolver);
+        if let Some(limit) = gas_limit {
+            vm.context_mut().set_gas_limit(limit);
+        }
+        vm.invoke_entry_chunk_with_args(id, args.into_iter().rev())?;
+        vm.run()
+    };
+
+    assert_eq!(run(0, vec![], None).unwrap(), Primitive::U64(42).into());
+
+    // Reverts of the callee are propagated to the caller
+    assert!(matches!(run(1, vec![Primitive::U16(1)], None), Err(VMError::EnvironmentError(EnvironmentError::Expect(msg))) if msg == "reverted"));
+
+    // Arguments must match the entry signature
+    let Err(VMError::EnvironmentError(EnvironmentError::Any(e))) = run(1, vec![Primitive::U16(0)], None) else {
+        panic!("expected the callee error");
+    };
+    assert!(matches!(e.downcast_ref::<VMError>(), Some(VMError::InvalidArgumentsCount(2, 1))));
+
+    // Only entries can be called
+    let Err(VMError::EnvironmentError(EnvironmentError::Any(e))) = run(1, vec![Primitive::U16(3)], None) else {
+        panic!("expected the callee error");
+    };
+    assert!(matches!(e.downcast_ref::<VMError>(), Some(VMError::ChunkNotEntry)));
+
+    let Err(VMError::EnvironmentError(EnvironmentError::Any(e))) = run(3, vec![], None) else {
+        panic!("expected the callee error");
+    };
+    assert!(matches!(e.downcast_ref::<VMError>(), Some(VMError::InvalidArgumentType(0))));
+
+    // The value returned must match the ABI of the callee
+    let Err(VMError::EnvironmentError(EnvironmentError::Any(e))) = run(5, vec![], None) else {
+        panic!("expected the callee error");
+    };
+    assert!(matches!(e.downcast_ref::<VMError>(), Some(VMError::InvalidReturnValue)));
+
+    // A module called several times is decoded once
+    let mut vm = VM::new(&caller, &env).unwrap();
+    vm.set_module_resolver(&resolver);
+    vm.invoke_entry_chunk(4).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U64(44).into());
+    assert_eq!(vm.backend.modules.borrow().len(), 2);
+
+    // Events are tagged with the module emitting them
+    let mut vm = VM::new(&caller, &env).unwrap();
+    vm.set_module_resolver(&resolver);
+    vm.invoke_entry_chunk(6).unwrap();
+    let (_, events) = vm.run_with_events().unwrap();
+    assert_eq!(events, vec![
+        Event { module: caller.hash(), id: 0, raw: true, data: Primitive::String("caller".to_owned()).into() },
+        Event { module: callee.hash(), id: 0, raw: false, data: ValueCell::Array(Vec::new()) }
+    ]);
+
+    // The callee shares the gas limit of the caller
+    assert!(matches!(run(1, vec![Primitive::U16(2)], Some(10_000)), Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { .. }))));
+
+    assert!(matches!(run(2, vec![], None), Err(VMError::EnvironmentError(EnvironmentError::UnknownModule))));
+
+    // Without a resolver, no module can be called
+    let mut vm = VM::new(&caller, &env).unwrap();
+    vm.invoke_entry_chunk(0).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::InvokerUnavailable))));
+}
+
+#[test]
+fn test_journaled_state() {
+    let mut env = EnvironmentBuilder::default();
+    env.register_native_function("set", None, vec![("key", Type::String), ("value", Type::U64)], |_, mut params, context| {
+        let value = params.remove(1).into_owned()?;
+        let key = params.remove(0).into_owned()?;
+        let state = context.get_mut::<JournaledState>()
+            .ok_or(EnvironmentError::InvalidParameter)?;
+
+        state.set(key, value);
+        Ok(None)
+    }, 1, None);
+    env.register_reentrant_function("try_invoke", None, vec![("id", Type::U16)], |_, params, invoker, context| {
+        let id = params[0].as_ref()?.as_u16()?;
+        let success = invoker.invoke_chunk(id, Vec::new(), context).is_ok();
+        Ok(Some(Primitive::Boolean(success).into()))
+    }, 5, Some(Type::Bool));
+
+    let code = r#"
+        fn inner_fail() -> u64 {
+            set("b", 3);
+            assert(false);
+            return 0
+        }
+
+        fn inner() -> u64 {
+            set("c", 4);
+            return 0
+        }
+
+        entry main() {
+            set("a", 1);
+            return 0
+        }
+
+        entry fail() {
+            set("a", 2);
+            set("b", 2);
+            assert(false);
+            return 0
+        }
+
+        entry nested() {
+            set("a", 5);
+            assert(!try_invoke(0u16));
+            assert(try_invoke(1u16));
+            return 0
+        }
+
+        entry burn() {
+            set("a", 6);
+            while true {}
+            return 0
+        }
+    "#;
+
+    let (module, env) = prepare_module_with(code, env);
+    let key = |k: &str| ValueCell::from(Primitive::String(k.to_owned()));
+    let value = |v: u64| ValueCell::from(Primitive::U64(v));
+
+    let run = |id: u16, state: JournaledState, gas_limit: Option<u64>| {
+        let mut vm = VM::new(&module, &env).unwrap();
+        vm.context_mut().insert(state);
+        if let Some(limit) = gas_limit {
+            vm.context_mut().set_gas_limit(limit);
+        }
+        vm.invoke_entry_chunk(id).unwrap();
+        let result = vm.run();
+        let state = vm.context_mut().take::<JournaledState>().unwrap();
+        assert_eq!(state.depth(), 0);
+        (result, state)
+    };
+
+    let (result, state) = run(2, JournaledState::new(), None);
+    assert!(result.is_ok());
+    assert_eq!(state.get(&key("a")), Some(&value(1)));
+
+    // All the changes are reverted when the execution fails
+    let (result, state) = run(3, state, None);
+    assert!(matches!(result, Err(VMError::EnvironmentError(EnvironmentError::AssertionFailed))));
+    assert_eq!(state.get(&key("a")), Some(&value(1)));
+    assert!(!state.contains_key(&key("b")));
+
+    // Only the changes of the failed invocation are reverted
+    let (result, state) = run(4, state, None);
+    assert!(result.is_ok());
+    assert_eq!(state.get(&key("a")), Some(&value(5)));
+    assert!(!state.contains_key(&key("b")));
+    assert_eq!(state.get(&key("c")), Some(&value(4)));
+
+    let (result, state) = run(5, state, Some(10_000));
+    assert!(matches!(result, Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { .. }))));
+    assert_eq!(state.get(&key("a")), Some(&value(5)));
+
+    // Changes are reverted if the final value is invalid
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.context_mut().insert(state);
+    vm.push_stack(ValueCell::from(Primitive::Null)).unwrap();
+    vm.invoke_entry_chunk(2).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::StackNotCleaned(1))));
+    let state = vm.context_mut().take::<JournaledState>().unwrap();
+    assert_eq!(state.depth(), 0);
+    assert_eq!(state.get(&key("a")), Some(&value(5)));
+
+    // The frames restored from a snapshot are journaled
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(3).unwrap();
+    let snapshot = vm.snapshot().unwrap();
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.context_mut().insert(state);
+    vm.restore(&snapshot).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::AssertionFailed))));
+    let state = vm.context_mut().take::<JournaledState>().unwrap();
+    assert_eq!(state.depth(), 0);
+    assert_eq!(state.get(&key("a")), Some(&value(5)));
+    assert!(!state.contains_key(&key("b")));
+}
+
+#[test]
+fn test_storage() {
+    let code = r#"
+        entry store(value: u64) {
+            storage_store("counter", value);
+            return 0
+        }
+
+        entry load() {
+            let value: u64 = storage_load("counter").unwrap();
+            return value
+        }
+
+        entry delete() {
+            assert(storage_delete("counter"));
+            assert(!storage_has("counter"));
+            assert(storage_load("counter").is_none());
+            return 0
+        }
+
+        fn overwrite() -> u64 {
+            storage_store("counter", 7);
+            storage_store("other", 1);
+            assert(storage_delete("counter"));
+            return 0
+        }
+
+        entry store_fail() {
+            let _: u64 = overwrite();
+            assert(false);
+            return 0
+        }
+
+        entry store_burn() {
+            let _: u64 = overwrite();
+            while true {}
+            return 0
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+    let mut storage = MemoryStorage::new();
+
+    let run = |storage: &mut MemoryStorage, id: u16, args: Vec<Primitive>, gas_limit: Option<u64>| {
+        let mut vm = VM::new(&module, &env).unwrap();
+        vm.context_mut().insert(StorageHandle::new(storage));
+        if let Some(limit) = gas_limit {
+            vm.context_mut().set_gas_limit(limit);
+        }
+        vm.invoke_entry_chunk_with_args(id, args.into_iter().rev())?;
+        let result = vm.run();
+        // Every checkpoint is closed once the execution is done
+        assert_eq!(vm.context_mut().get_mut::<StorageHandle>().unwrap().depth(), 0);
+        let value = result?;
+        Ok::<_, VMError>((value, vm.context().current_gas_usage()))
+    };
+
+    // The values are persisted across the executions
+    let (_, store_gas) = run(&mut storage, 0, vec![Primitive::U64(42)], None).unwrap();
+    let (value, _) = run(&mut storage, 1, vec![], None).unwrap();
+    assert_eq!(value, Primitive::U64(42).into());
+
+    // Gas is charged for each byte written
+    let key = ValueCell::from(Primitive::String("counter".to_owned()));
+    let value = ValueCell::from(Primitive::U64(42));
+    let written = (key.to_bytes().len() + value.to_bytes().len()) as u64;
+    assert!(store_gas >= 100 + written * xelis_builder::xstd::STORAGE_WRITE_COST_PER_BYTE);
+
+    // Writes are reverted when the execution fails, including the ones of returned frames
+    let expected = storage.values().clone();
+    assert!(matches!(run(&mut storage, 4, vec![], None), Err(VMError::EnvironmentError(EnvironmentError::AssertionFailed))));
+    assert_eq!(storage.values(), &expected);
+
+    let result = run(&mut storage, 5, vec![], Some(10_000));
+    assert!(matches!(result, Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { .. }))));
+    assert_eq!(storage.values(), &expected);
+
+    run(&mut storage, 2, vec![], None).unwrap();
+    assert!(storage.values().is_empty());
+
+    // The host must provide a storage
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry_chunk(1).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::StorageUnavailable))));
+}
+
+#[test]
+fn test_storage_rollback_failure() {
+    // Storage failing once its writes are exhausted
+    struct LimitedStorage {
+        inner: MemoryStorage,
+        writes_left: usize
+    }
+
+    impl LimitedStorage {
+        fn write(&mut self) -> Result<(), EnvironmentError> {
+            self.writes_left = self.writes_left.checked_sub(1)
+                .ok_or(EnvironmentError::Static("storage unavailable"))?;
+            Ok(())
+        }
+    }
+
+    impl Storage for LimitedStorage {
+        fn load(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<Option<ValueCell>, EnvironmentError> {
+            self.inner.load(module, key)
+        }
+
+        fn has(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError> {
+            self.inner.has(module, key)
+        }
+
+        fn store(&mut self, module: &[u8; 32], key: ValueCell, value: ValueCell) -> Result<(), EnvironmentError> {
+            self.write()?;
+            self.inner.store(module, key, value)
+        }
+
+        fn delete(&mut self, module: &[u8; 32], key: &ValueCell) -> Result<bool, EnvironmentError> {
+            self.write()?;
+            self.inner.delete(module, key)
+        }
+    }
+
+    let code = r#"
+        entry main() {
+            storage_store("counter", 7);
+            assert(false);
+            return 0
+        }
+    "#;
+
+    let (module, env) = prepare_module(code);
+    let mut storage = LimitedStorage { inner: MemoryStorage::new(), writes_left: 1 };
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.context_mut().insert(StorageHandle::new(&mut storage));
+    vm.invoke_entry_chunk(0).unwrap();
+
+    // The failure to revert the write is reported with the error
+    let result = vm.run();
+    assert!(matches!(
+        result,
+        Err(VMError::RollbackFailed(e, EnvironmentError::Static("storage unavailable"))) if matches!(*e, VMError::EnvironmentError(EnvironmentError::AssertionFailed))
+    ));
+    assert_eq!(vm.context_mut().get_mut::<StorageHandle>().unwrap().depth(), 0);
+}
+
+#[test]
+fn test_storage_variables() {
+    let code = r#"
+        storage supply: u64
+        storage balances: map<string, u64>
+
+        entry mint(to: string, amount: u64) {
+            let balance: u64 = balances.get(to).unwrap_or(0);
+            balances.set(to, balance + amount);
+            supply.set(supply.get().unwrap_or(0) + amount);
+            return 0
+        }
+
+        entry balance_of(account: string) {
+            return balances.get(account).unwrap_or(0)
+        }
+
+        entry burn(account: string) {
+            let balance: u64 = balances.get(account).unwrap();
+            assert(balances.delete(account));
+            assert(!balances.has(account));
+            supply.set(supply.get().unwrap() - balance);
+            return 0
+        }
+
+        entry overwrite_supply() {
+            storage_store("supply", "invalid");
+            return 0
+        }
+    "#;
+
+    let env = EnvironmentBuilder::default();
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let (program, _) = Parser::with(tokens.into_iter(), &env).parse().unwrap();
+    let abi = generate_abi(&program).unwrap();
+
+    let env = env.build();
+    let module = Compiler::new(&program, &env).compile().unwrap();
+    ModuleValidator::new(&module, &env).verify().unwrap();
+
+    let mut storage = MemoryStorage::new();
+    let run = |storage: &mut MemoryStorage, id: u16, args: Vec<Primitive>| {
+        let mut vm = VM::new(&module, &env).unwrap();
+        vm.context_mut().insert(StorageHandle::new(storage));
+        vm.invoke_entry_chunk_with_args(id, args.into_iter().rev()).unwrap();
+        vm.run().unwrap()
+    };
+
+    let alice = || Primitive::String("alice".to_owned());
+    let bob = || Primitive::String("bob".to_owned());
+    run(&mut storage, 0, vec![alice(), Primitive::U64(10)]);
+    run(&mut storage, 0, vec![alice(), Primitive::U64(5)]);
+    run(&mut storage, 0, vec![bob(), Primitive::U64(7)]);
+    assert_eq!(run(&mut storage, 1, vec![alice()]), Primitive::U64(15).into());
+    assert_eq!(run(&mut storage, 1, vec![bob()]), Primitive::U64(7).into());
+
+    // The keys can be derived from the ABI to read the values
+    let supply = abi.storage_key("supply", None, None).unwrap();
+    let balance = abi.storage_key("balances", Some(&json!("alice")), None).unwrap();
+    let hash = module.hash();
+    assert_eq!(storage.get(&hash, &supply), Some(&Primitive::U64(22).into()));
+    assert_eq!(storage.get(&hash, &balance), Some(&Primitive::U64(15).into()));
+
+    run(&mut storage, 2, vec![alice()]);
+    assert!(storage.get(&hash, &balance).is_none());
+    assert_eq!(storage.get(&hash, &supply), Some(&Primitive::U64(7).into()));
+
+    // A value of another type written under the same key is rejected
+    run(&mut storage, 3, vec![]);
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.context_mut().insert(StorageHandle::new(&mut storage));
+    vm.invoke_entry_chunk_with_args(0, [bob(), Primitive::U64(1)].into_iter().rev()).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::InvalidStorageValue))));
+}
+
+#[test]
+fn test_storage_per_module() {
+    let callee_code = r#"
+        storage counter: u64
+
+        entry bump(x: u64) {
+            counter.set(counter.get().unwrap_or(0) + x);
+            return counter.get().unwrap()
+        }
+    "#;
+
+    let caller_code = r#"
+        storage counter: u64
+
+        entry main() {
+            counter.set(100);
+            let a: u64 = call_module("callee", 0u16, [5]);
+            let b: u64 = call_module("callee", 0u16, [5]);
+            assert(a == 5 && b == 10);
+            return counter.get().unwrap()
+        }
+    "#;
+
+    let (callee, callee_abi, _) = prepare_module_with_abi(callee_code);
+    let (caller, env) = prepare_module(caller_code);
+    let resolver = TestResolver(vec![("callee", &callee, &callee_abi)]);
+
+    let mut storage = MemoryStorage::new();
+    let mut vm = VM::new(&caller, &env).unwrap();
+    vm.set_module_resolver(&resolver);
+    vm.context_mut().insert(StorageHandle::new(&mut storage));
+    vm.invoke_entry_chunk(0).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U64(100).into());
+    drop(vm);
+
+    // Both modules declare the same variable without sharing it
+    let key = ValueCell::from(Primitive::String("counter".to_owned()));
+    assert_eq!(storage.get(&caller.hash(), &key), Some(&Primitive::U64(100).into()));
+    assert_eq!(storage.get(&callee.hash(), &key), Some(&Primitive::U64(10).into()));
+}
+
+#[test]
+fn test_events() {
+    let mut env = EnvironmentBuilder::default();
+    env.register_reentrant_function("try_invoke", None, vec![("id", Type::U16)], |_, params, invoker, context| {
+        let id = params[0].as_ref()?.as_u16()?;
+        let success = invoker.invoke_chunk(id, Vec::new(), context).is_ok();
+        Ok(Some(Primitive::Boolean(success).into()))
+    }, 5, Some(Type::Bool));
+
+    let code = r#"
+        event Transfer { to: string, amount: u64 }
+        event Paused {}
+
+        fn inner_fail() -> u64 {
+            Transfer.emit("bob", 1);
+            assert(false);
+            return 0
+        }
+
+        entry transfer(to: string, amount: u64) {
+            Transfer.emit(to, amount);
+            Paused.emit();
+            return 0
+        }
+
+        entry fail() {
+            Transfer.emit("alice", 2);
+            assert(false);
+            return 0
+        }
+
+        entry nested() {
+            emit(42u16, "raw");
+            assert(!try_invoke(0u16));
+            return 0
+        }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let (program, _) = Parser::with(tokens.into_iter(), &env).parse().unwrap();
+    let abi = generate_abi(&program).unwrap();
+
+    let env = env.build();
+    let module = Compiler::new(&program, &env).compile().unwrap();
+    ModuleValidator::new(&module, &env).verify().unwrap();
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry(&abi, "transfer", &[json!("alice"), json!(10)]).unwrap();
+    let (_, events) = vm.run_with_events().unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|event| !event.raw && event.module == module.hash()));
+    assert_eq!(abi.event_to_json(events[0].id, &events[0].data).unwrap(), json!({ "event": "Transfer", "fields": { "to": "alice", "amount": 10 } }));
+    assert_eq!(abi.event_to_json(events[1].id, &events[1].data).unwrap(), json!({ "event": "Paused", "fields": {} }));
+
+    // The log is emptied once the events are returned
+    vm.invoke_entry(&abi, "transfer", &[json!("bob"), json!(1)]).unwrap();
+    assert_eq!(vm.run_with_events().unwrap().1.len(), 2);
+
+    // Events are dropped when the execution fails
+    vm.invoke_entry(&abi, "fail", &[]).unwrap();
+    assert!(vm.run_with_events().is_err());
+    assert!(vm.context().get::<EventLog>().unwrap().events().is_empty());
+
+    // Only the events of the failed frame are dropped
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.invoke_entry(&abi, "nested", &[]).unwrap();
+    let (_, events) = vm.run_with_events().unwrap();
+    assert_eq!(events, vec![Event { module: module.hash(), id: 42, raw: true, data: Primitive::String("raw".to_owned()).into() }]);
+
+    // The host must provide an event log
+    let mut vm = VM::with(&module, &env, InstructionTable::new(), Context::default(), VMConfig::default()).unwrap();
+    vm.invoke_entry(&abi, "transfer", &[json!("alice"), json!(10)]).unwrap();
+    assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::EventLogUnavailable))));
+}
@@ -1,5 +1,9 @@
+use std::borrow::Cow;
+
 use super::*;
 
+use indexmap::{IndexMap, IndexSet};
+use xelis_ast::Signature;
 use xelis_bytecode::{Chunk, Module, OpCode};
 use xelis_types::{Type, Primitive};
 
@@ -195,7 +199,7 @@ fn test_struct() {
 
     {
         let env = Environment::new();
-        let mut vm = VM::new(&module, &env);
+        let mut vm = VM::new(&module, &env).unwrap();
         vm.invoke_chunk_id(0).unwrap();
         assert_eq!(
             vm.run().unwrap(),
@@ -242,7 +246,7 @@ fn test_struct() {
     chunk.emit_opcode(OpCode::Return);
 
     let env = Environment::new();
-    let mut vm = VM::new(&module, &env);
+    let mut vm = VM::new(&module, &env).unwrap();
     vm.invoke_chunk_id(0).unwrap();
     assert_eq!(vm.run().unwrap(), Primitive::U16(30).into());
 }
@@ -279,7 +283,7 @@ fn test_function_call() {
     module.add_chunk(bool_fn);
 
     let env = Environment::new();
-    let mut vm = VM::new(&module, &env);
+    let mut vm = VM::new(&module, &env).unwrap();
     vm.invoke_chunk_id(0).unwrap();
     assert_eq!(vm.run().unwrap(), Primitive::Boolean(true).into());
 }
@@ -315,7 +319,7 @@ fn test_function_call_on_value() {
     module.add_chunk(struct_fn);
 
     let env = Environment::new();
-    let mut vm = VM::new(&module, &env);
+    let mut vm = VM::new(&module, &env).unwrap();
     vm.invoke_chunk_id(0).unwrap();
     assert_eq!(vm.run().unwrap(), Primitive::U64(10).into());
 }
@@ -714,6 +718,12 @@ fn test_infinite_map_depth() {
     //     map.insert("hello world", map);
     // }
 
+    let env = EnvironmentBuilder::default();
+    let map_type = Type::Map(Box::new(Type::T(0)), Box::new(Type::T(1)));
+    let insert_id = env.get_functions_mapper()
+        .get(&Signature::new(Cow::Borrowed("insert"), Some((Cow::Owned(map_type), true)), Cow::Owned(vec![Type::T(0), Type::T(1)])))
+        .unwrap();
+
     let constant_id = module.add_constant(Primitive::String("hello world".to_string())) as u16;
 
     // Create the map
@@ -753,7 +763,7 @@ fn test_infinite_map_depth() {
 
     // Insert map
     chunk.emit_opcode(OpCode::SysCall);
-    chunk.write_u16(89);
+    chunk.write_u16(insert_id);
     chunk.write_bool(true);
     chunk.write_u8(2);
 
@@ -779,6 +789,776 @@ fn test_infinite_map_depth() {
     // Execute
     module.add_chunk(chunk);
 
-    assert!(try_run(module).is_err());
+    assert!(run_internal(module, &env.build(), 0).is_err());
     // assert!(matches!(try_run(module), Err(VMError::EnvironmentError(EnvironmentError::ValueError(ValueError::MaxDepthReached)))));
-}
\ No newline at end of file
+}
+#[test]
+fn test_opaque_constant_roundtrip() {
+    use xelis_types::{
+        impl_opaque,
+        serializer::Serializer,
+        traits::{JSONHelper, Serializable},
+        OpaqueWrapper
+    };
+    use xelis_bytecode::MODULE_MAX_SIZE;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Id(u16);
+
+    impl JSONHelper for Id {}
+    impl Serializable for Id {
+        fn serialize(&self, buffer: &mut Vec<u8>) -> usize {
+            buffer.extend_from_slice(&self.0.to_le_bytes());
+            2
+        }
+
+        fn is_serializable(&self) -> bool {
+            true
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+            Ok(Self(u16::from_le_bytes(bytes.try_into()?)))
+        }
+    }
+
+    impl_opaque!("Id", Id);
+
+    let mut env = EnvironmentBuilder::default();
+    // The name used in the source code doesn't have to be the type name
+    env.register_opaque::<Id>("Identifier");
+    let env = env.build();
+
+    let mut module = Module::new();
+    let mut chunk = Chunk::new();
+    let index = module.add_constant(Primitive::Opaque(OpaqueWrapper::new(Id(42))));
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(index as u16);
+    module.add_chunk(chunk);
+
+    let bytes = module.to_bytes();
+    let module = Module::from_bytes_with_limit(&bytes, MODULE_MAX_SIZE, env.get_opaque_registry()).unwrap();
+
+    assert_eq!(
+        run_internal(module, &env, 0).unwrap(),
+        Primitive::Opaque(OpaqueWrapper::new(Id(42)))
+    );
+}
+
+// Run the stack effects verification on the module
+fn verify_stack(module: &Module) -> Result<Vec<ChunkSummary>, String> {
+    let env = EnvironmentBuilder::default().build();
+    ModuleValidator::new(module, &env)
+        .verify_stack_effects()
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_validator_stack_underflow() {
+    let mut module = Module::new();
+
+    let mut main = Chunk::new();
+    main.emit_opcode(OpCode::InvokeChunk);
+    main.write_u16(1);
+    main.write_bool(false);
+    main.write_u8(0);
+    module.add_entry_chunk(main);
+
+    let mut callee = Chunk::new();
+    callee.emit_opcode(OpCode::Pop);
+    module.add_chunk(callee);
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::StackUnderflow(1, 0).to_string())
+    );
+}
+
+#[test]
+fn test_validator_stack_overflow() {
+    let mut module = Module::new();
+    let index = module.add_constant(Primitive::U8(0));
+
+    let mut chunk = Chunk::new();
+    for _ in 0..257 {
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(index as u16);
+    }
+    module.add_entry_chunk(chunk);
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::StackOverflow(0, 256 * 3).to_string())
+    );
+}
+
+#[test]
+fn test_validator_stack_overflow_across_calls() {
+    let mut module = Module::new();
+    let index = module.add_constant(Primitive::U8(0));
+
+    // Each chunk stays below the limit on its own
+    let mut main = Chunk::new();
+    for _ in 0..200 {
+        main.emit_opcode(OpCode::Constant);
+        main.write_u16(index as u16);
+    }
+    main.emit_opcode(OpCode::InvokeChunk);
+    main.write_u16(1);
+    main.write_bool(false);
+    main.write_u8(0);
+    main.emit_opcode(OpCode::PopN);
+    main.write_u8(200);
+    module.add_entry_chunk(main);
+
+    let mut callee = Chunk::new();
+    for _ in 0..100 {
+        callee.emit_opcode(OpCode::Constant);
+        callee.write_u16(index as u16);
+    }
+    callee.emit_opcode(OpCode::PopN);
+    callee.write_u8(100);
+    module.add_chunk(callee);
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::StackOverflow(0, 200 * 3).to_string())
+    );
+}
+
+#[test]
+fn test_validator_copy_n() {
+    let mut module = Module::new();
+    let index = module.add_constant(Primitive::U8(0));
+
+    let build = |copied: u8| {
+        let mut module = module.clone();
+        let mut chunk = Chunk::new();
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(index as u16);
+        chunk.emit_opcode(OpCode::CopyN);
+        chunk.write_u8(copied);
+        chunk.emit_opcode(OpCode::PopN);
+        chunk.write_u8(2);
+        module.add_entry_chunk(chunk);
+        module
+    };
+
+    assert!(verify_stack(&build(0)).is_ok());
+    assert_eq!(
+        verify_stack(&build(1)),
+        Err(ValidatorError::StackUnderflow(0, 3).to_string())
+    );
+}
+
+#[test]
+fn test_validator_invalid_jump_target() {
+    let mut module = Module::new();
+    let index = module.add_constant(Primitive::U8(0));
+
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(index as u16);
+    // Jump in the middle of the constant arguments
+    chunk.emit_opcode(OpCode::Jump);
+    chunk.write_u32(1);
+    module.add_entry_chunk(chunk);
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::InvalidJumpTarget(0, 3).to_string())
+    );
+}
+
+#[test]
+fn test_validator_invalid_register() {
+    let mut module = Module::new();
+
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::MemoryLoad);
+    chunk.write_u16(0);
+    module.add_entry_chunk(chunk);
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::InvalidRegister(0, 0).to_string())
+    );
+}
+
+#[test]
+fn test_validator_stack_height_mismatch() {
+    let mut module = Module::new();
+    let index = module.add_constant(Primitive::Boolean(true));
+
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(index as u16);
+    chunk.emit_opcode(OpCode::JumpIfFalse);
+    chunk.write_u32(11);
+    // Only pushed when the condition is true
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(index as u16);
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(index as u16);
+    module.add_entry_chunk(chunk);
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::StackHeightMismatch(0, 11).to_string())
+    );
+}
+
+#[test]
+fn test_validator_inconsistent_arity() {
+    let mut module = Module::new();
+
+    let mut main = Chunk::new();
+    for args in [0, 1] {
+        main.emit_opcode(OpCode::InvokeChunk);
+        main.write_u16(1);
+        main.write_bool(false);
+        main.write_u8(args);
+    }
+    module.add_entry_chunk(main);
+    module.add_chunk(Chunk::new());
+
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::InconsistentChunkArity(1).to_string())
+    );
+}
+
+#[test]
+fn test_validator_chunk_signature() {
+    let mut module = Module::new();
+
+    // Entry storing its two parameters
+    let mut main = Chunk::new();
+    for id in 0..2 {
+        main.emit_opcode(OpCode::MemorySet);
+        main.write_u16(id);
+    }
+    main.emit_opcode(OpCode::MemoryLoad);
+    main.write_u16(0);
+    main.emit_opcode(OpCode::Return);
+    module.add_entry_chunk(main);
+    module.add_chunk(Chunk::new());
+
+    module.set_chunk_signature(0, vec![Type::U64, Type::U64]);
+    let summaries = verify_stack(&module).unwrap();
+    assert_eq!(summaries[0].parameters(), 2);
+
+    // Declaring less parameters than used underflows
+    module.set_chunk_signature(0, vec![Type::U64]);
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::StackUnderflow(0, 3).to_string())
+    );
+
+    // Only the chunks invoked by the host can have a signature
+    module.set_chunk_signature(0, vec![Type::U64, Type::U64]);
+    module.set_chunk_signature(1, Vec::new());
+    assert_eq!(
+        verify_stack(&module),
+        Err(ValidatorError::InvalidChunkSignature(1).to_string())
+    );
+}
+
+#[test]
+fn test_validator_chunk_summary() {
+    let mut module = Module::new();
+    let index = module.add_constant(Primitive::U64(1));
+
+    // Entry expecting one parameter and calling a function with it
+    let mut main = Chunk::new();
+    main.emit_opcode(OpCode::MemorySet);
+    main.write_u16(0);
+    main.emit_opcode(OpCode::MemoryLoad);
+    main.write_u16(0);
+    main.emit_opcode(OpCode::Constant);
+    main.write_u16(index as u16);
+    main.emit_opcode(OpCode::InvokeChunk);
+    main.write_u16(1);
+    main.write_bool(false);
+    main.write_u8(2);
+    main.emit_opcode(OpCode::Return);
+    module.add_entry_chunk(main);
+
+    let mut add = Chunk::new();
+    add.emit_opcode(OpCode::Add);
+    add.emit_opcode(OpCode::Return);
+    module.add_chunk(add);
+
+    let summaries = verify_stack(&module).unwrap();
+    assert_eq!(summaries[0].parameters(), 1);
+    assert_eq!(summaries[0].returns(), Some(1));
+    assert_eq!(summaries[0].max_height(), 2);
+
+    assert_eq!(summaries[1].parameters(), 2);
+    assert_eq!(summaries[1].returns(), Some(1));
+    assert_eq!(summaries[1].max_height(), 2);
+
+    // The arguments are replaced by the parameters of the chunk invoked
+    assert_eq!(summaries[0].max_call_height(), Some(2));
+    assert_eq!(summaries[1].max_call_height(), Some(2));
+}
+
+// Run the full module verification
+fn verify_module(module: &Module, env: &Environment) -> Result<(), String> {
+    ModuleValidator::new(module, env)
+        .verify()
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_validator_entry_chunk_invoked() {
+    let mut module = Module::new();
+
+    let mut main = Chunk::new();
+    main.emit_opcode(OpCode::InvokeChunk);
+    main.write_u16(0);
+    main.write_bool(false);
+    main.write_u8(0);
+    module.add_entry_chunk(main);
+
+    let env = EnvironmentBuilder::default().build();
+    assert_eq!(
+        verify_module(&module, &env),
+        Err(ValidatorError::EntryChunkInvoked(0, 0).to_string())
+    );
+}
+
+#[test]
+fn test_validator_hook_on_entry_chunk() {
+    let mut env = EnvironmentBuilder::default();
+    env.register_hook("on_init", Vec::new(), None);
+    let env = env.build();
+
+    let chunks = || vec![chunk_invoking(&[]), chunk_invoking(&[])];
+    let module = Module::with(IndexSet::new(), chunks(), IndexSet::from([0]), IndexMap::from([(0, 0)]), IndexMap::new());
+    assert_eq!(
+        verify_module(&module, &env),
+        Err(ValidatorError::InvalidHookId(0, 0).to_string())
+    );
+
+    let module = Module::with(IndexSet::new(), chunks(), IndexSet::from([0]), IndexMap::from([(0, 1)]), IndexMap::new());
+    assert!(verify_module(&module, &env).is_ok());
+}
+
+#[test]
+fn test_validator_syscall_operands() {
+    let env = EnvironmentBuilder::default();
+    let map_type = Type::Map(Box::new(Type::T(0)), Box::new(Type::T(1)));
+    let insert_id = env.get_functions_mapper()
+        .get(&Signature::new(Cow::Borrowed("insert"), Some((Cow::Owned(map_type), true)), Cow::Owned(vec![Type::T(0), Type::T(1)])))
+        .unwrap();
+    let env = env.build();
+
+    let syscall = |id: u16, on_value: bool, args: u8| {
+        let mut module = Module::new();
+        let mut chunk = Chunk::new();
+        chunk.emit_opcode(OpCode::SysCall);
+        chunk.write_u16(id);
+        chunk.write_bool(on_value);
+        chunk.write_u8(args);
+        module.add_entry_chunk(chunk);
+        module
+    };
+
+    assert_eq!(
+        verify_module(&syscall(u16::MAX, false, 0), &env),
+        Err(ValidatorError::UnknownSysCall(0, 0).to_string())
+    );
+    assert_eq!(
+        verify_module(&syscall(insert_id, true, 1), &env),
+        Err(ValidatorError::InvalidSysCallArguments(0, 0, 2, 1).to_string())
+    );
+    assert_eq!(
+        verify_module(&syscall(insert_id, false, 2), &env),
+        Err(ValidatorError::InvalidSysCallInstance(0, 0, true, false).to_string())
+    );
+    assert!(verify_module(&syscall(insert_id, true, 2), &env).is_ok());
+}
+
+// Build a chunk invoking all the requested chunks without arguments
+fn chunk_invoking(ids: &[u16]) -> Chunk {
+    let mut chunk = Chunk::new();
+    for id in ids {
+        chunk.emit_opcode(OpCode::InvokeChunk);
+        chunk.write_u16(*id);
+        chunk.write_bool(false);
+        chunk.write_u8(0);
+    }
+    chunk
+}
+
+#[test]
+fn test_call_graph() {
+    let mut module = Module::new();
+    module.add_entry_chunk(chunk_invoking(&[1, 3]));
+    // 1 and 2 are invoking each other
+    module.add_chunk(chunk_invoking(&[2]));
+    module.add_chunk(chunk_invoking(&[1]));
+    module.add_chunk(chunk_invoking(&[]));
+    // Unreachable self recursive chunk
+    module.add_chunk(chunk_invoking(&[4]));
+
+    let graph = CallGraph::new(&module).unwrap();
+    assert!(graph.has_recursion());
+    assert_eq!(graph.recursive_cycles(), &[vec![1, 2], vec![4]]);
+    assert_eq!(graph.unreachable_chunks(), &[4]);
+    assert_eq!(graph.chunk_depth(3), Some(1));
+    assert_eq!(graph.chunk_depth(1), None);
+    assert_eq!(graph.max_depth(), None);
+}
+
+#[test]
+fn test_call_graph_depth() {
+    let mut module = Module::new();
+    module.add_entry_chunk(chunk_invoking(&[1, 2]));
+    module.add_chunk(chunk_invoking(&[2]));
+    module.add_chunk(chunk_invoking(&[]));
+    module.add_entry_chunk(chunk_invoking(&[2]));
+
+    let graph = CallGraph::new(&module).unwrap();
+    assert!(!graph.has_recursion());
+    assert!(graph.unreachable_chunks().is_empty());
+    assert_eq!(graph.chunk_depth(3), Some(2));
+    assert_eq!(graph.max_depth(), Some(3));
+}
+
+#[test]
+fn test_call_graph_invalid_roots() {
+    let chunks = || vec![chunk_invoking(&[])];
+    let module = Module::with(IndexSet::new(), chunks(), IndexSet::from([1]), IndexMap::new(), IndexMap::new());
+    assert_eq!(
+        CallGraph::new(&module).unwrap_err().to_string(),
+        ValidatorError::InvalidEntryId(1).to_string()
+    );
+
+    let module = Module::with(IndexSet::new(), chunks(), IndexSet::new(), IndexMap::from([(3, 2)]), IndexMap::new());
+    assert_eq!(
+        CallGraph::new(&module).unwrap_err().to_string(),
+        ValidatorError::InvalidHookId(3, 2).to_string()
+    );
+}
+
+#[test]
+fn test_validator_reject_recursion() {
+    let env = EnvironmentBuilder::default().build();
+    let mut module = Module::new();
+    module.add_entry_chunk(chunk_invoking(&[1]));
+    module.add_chunk(chunk_invoking(&[1]));
+
+    let mut validator = ModuleValidator::new(&module, &env);
+    assert!(validator.verify().is_ok());
+
+    validator.set_allow_recursion(false);
+    assert_eq!(
+        validator.verify().map_err(|e| e.to_string()),
+        Err(ValidatorError::RecursiveChunks(vec![1]).to_string())
+    );
+}
+
+fn run_with_gas(module: Module) -> (Primitive, u64) {
+    let env = EnvironmentBuilder::default().build();
+    ModuleValidator::new(&module, &env).verify().unwrap();
+
+    let mut vm = VM::new(&module, &env).unwrap();
+    vm.context_mut().set_gas_limit(10u64.pow(8u32));
+    vm.invoke_chunk_id(0).unwrap();
+    let value = vm.run().unwrap().into_value().unwrap();
+
+    (value, vm.context().current_gas_usage())
+}
+
+// Count to 10 then return (i * 2) + j
+// Emitted with the superinstructions or with the sequences they replace
+fn counting_loop(fused: bool) -> Module {
+    let mut module = Module::new();
+    let zero = module.add_constant(Primitive::U64(0)) as u16;
+    let ten = module.add_constant(Primitive::U64(10)) as u16;
+    let one = module.add_constant(Primitive::U64(1)) as u16;
+    let two = module.add_constant(Primitive::U64(2)) as u16;
+
+    let mut chunk = Chunk::new();
+    // i = 0
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(zero);
+    chunk.emit_opcode(OpCode::MemorySet);
+    chunk.write_u16(0);
+
+    // j = 10
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(ten);
+    chunk.emit_opcode(OpCode::MemorySet);
+    chunk.write_u16(1);
+
+    let start = chunk.index();
+    // while i < 10
+    if fused {
+        chunk.emit_opcode(OpCode::MemoryConstantJumpIfFalse);
+        chunk.write_u16(0);
+        chunk.write_u16(ten);
+        chunk.write_u8(OpCode::Lt.as_byte());
+    } else {
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(ten);
+        chunk.emit_opcode(OpCode::Lt);
+        chunk.emit_opcode(OpCode::JumpIfFalse);
+    }
+    chunk.write_u32(0);
+    let jump = chunk.last_index();
+
+    // i += 1
+    if fused {
+        chunk.emit_opcode(OpCode::MemoryIncrement);
+        chunk.write_u16(0);
+    } else {
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(one);
+        chunk.emit_opcode(OpCode::AssignAdd);
+    }
+
+    // j -= 1
+    if fused {
+        chunk.emit_opcode(OpCode::MemoryAssignConstant);
+        chunk.write_u16(1);
+        chunk.write_u16(one);
+        chunk.write_u8(OpCode::Sub.as_byte());
+    } else {
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(1);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(one);
+        chunk.emit_opcode(OpCode::AssignSub);
+    }
+
+    chunk.emit_opcode(OpCode::Jump);
+    chunk.write_u32(start as u32);
+    chunk.patch_jump(jump, chunk.index() as u32);
+
+    // i * 2
+    if fused {
+        chunk.emit_opcode(OpCode::MemoryConstantOperator);
+        chunk.write_u16(0);
+        chunk.write_u16(two);
+        chunk.write_u8(OpCode::Mul.as_byte());
+    } else {
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(two);
+        chunk.emit_opcode(OpCode::Mul);
+    }
+    chunk.emit_opcode(OpCode::MemorySet);
+    chunk.write_u16(2);
+
+    // (i * 2) + j
+    if fused {
+        chunk.emit_opcode(OpCode::MemoryOperator);
+        chunk.write_u16(2);
+        chunk.write_u16(1);
+        chunk.write_u8(OpCode::Add.as_byte());
+    } else {
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(2);
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(1);
+        chunk.emit_opcode(OpCode::Add);
+    }
+    chunk.emit_opcode(OpCode::Return);
+
+    module.add_entry_chunk(chunk);
+    module
+}
+
+#[test]
+fn test_fused_opcodes_match_sequences() {
+    let (value, gas) = run_with_gas(counting_loop(false));
+    let (fused_value, fused_gas) = run_with_gas(counting_loop(true));
+
+    assert_eq!(value, Primitive::U64(20));
+    assert_eq!(fused_value, value);
+    assert_eq!(fused_gas, gas);
+}
+
+#[test]
+fn test_fused_division_by_zero() {
+    let mut module = Module::new();
+    let ten = module.add_constant(Primitive::U64(10)) as u16;
+    let zero = module.add_constant(Primitive::U64(0)) as u16;
+
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(ten);
+    chunk.emit_opcode(OpCode::MemorySet);
+    chunk.write_u16(0);
+
+    chunk.emit_opcode(OpCode::MemoryConstantOperator);
+    chunk.write_u16(0);
+    chunk.write_u16(zero);
+    chunk.write_u8(OpCode::Mod.as_byte());
+    chunk.emit_opcode(OpCode::Return);
+
+    module.add_entry_chunk(chunk);
+
+    assert!(matches!(try_run(module), Err(VMError::DivisionByZero)));
+}
+
+#[test]
+fn test_division_by_zero_unfused() {
+    // Same error as the superinstructions for each form of the operators
+    for (op, assign) in [(OpCode::Div, OpCode::AssignDiv), (OpCode::Mod, OpCode::AssignMod)] {
+        let mut module = Module::new();
+        let ten = module.add_constant(Primitive::U64(10)) as u16;
+        let zero = module.add_constant(Primitive::U64(0)) as u16;
+
+        let mut chunk = Chunk::new();
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(ten);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(zero);
+        chunk.emit_opcode(op);
+        chunk.emit_opcode(OpCode::Return);
+        module.add_entry_chunk(chunk);
+
+        assert!(matches!(try_run(module), Err(VMError::DivisionByZero)), "{:?}", op);
+
+        let mut module = Module::new();
+        let ten = module.add_constant(Primitive::U64(10)) as u16;
+        let zero = module.add_constant(Primitive::U64(0)) as u16;
+
+        let mut chunk = Chunk::new();
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(ten);
+        chunk.emit_opcode(OpCode::MemorySet);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::Constant);
+        chunk.write_u16(zero);
+        chunk.emit_opcode(assign);
+        chunk.emit_opcode(OpCode::MemoryLoad);
+        chunk.write_u16(0);
+        chunk.emit_opcode(OpCode::Return);
+        module.add_entry_chunk(chunk);
+
+        assert!(matches!(try_run(module), Err(VMError::DivisionByZero)), "{:?}", assign);
+    }
+}
+
+#[test]
+fn test_validator_reject_fused_operator() {
+    let env = EnvironmentBuilder::default().build();
+    let mut module = Module::new();
+    let one = module.add_constant(Primitive::U64(1)) as u16;
+
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(one);
+    chunk.emit_opcode(OpCode::MemorySet);
+    chunk.write_u16(0);
+
+    // A comparison can't be assigned
+    chunk.emit_opcode(OpCode::MemoryAssignConstant);
+    chunk.write_u16(0);
+    chunk.write_u16(one);
+    chunk.write_u8(OpCode::Eq.as_byte());
+
+    module.add_entry_chunk(chunk);
+
+    assert_eq!(
+        ModuleValidator::new(&module, &env).verify().map_err(|e| e.to_string()),
+        Err(ValidatorError::InvalidFusedOperator(0, 6).to_string())
+    );
+}
+
+#[test]
+fn test_decoded_chunk_offsets() {
+    let mut module = Module::new();
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(module.add_constant(Primitive::U8(0)) as u16);
+    chunk.emit_opcode(OpCode::Jump);
+    chunk.write_u32(8);
+    chunk.emit_opcode(OpCode::Return);
+
+    let decoded = DecodedChunk::decode(&chunk).unwrap();
+    assert_eq!(decoded.instructions().len(), 3);
+    assert!(matches!(decoded.get(1).map(|i| &i.operands), Some(Operands::Address(2))));
+
+    assert_eq!(decoded.offset_of(1), Some(3));
+    assert_eq!(decoded.offset_of(3), Some(chunk.index()));
+    assert_eq!(decoded.index_of(8), Some(2));
+    assert_eq!(decoded.index_of(4), None);
+
+    module.add_entry_chunk(chunk);
+    assert_eq!(run(module), Primitive::U8(0));
+}
+
+#[test]
+fn test_custom_instruction_reader() {
+    // Read the operands from the bytes instead of the decoded ones
+    fn constant<'a>(backend: &Backend<'a>, stack: &mut Stack, manager: &mut ChunkManager<'a>, _: &mut Context<'a, '_>) -> Result<InstructionResult, VMError> {
+        let index = manager.read_u16()? as usize;
+        stack.push_stack(backend.get_constant_with_id(index)?.clone().into())?;
+        Ok(InstructionResult::Nothing)
+    }
+
+    let env = EnvironmentBuilder::default().build();
+    let mut module = Module::new();
+    module.add_constant(Primitive::U8(0));
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(module.add_constant(Primitive::U8(7)) as u16);
+    chunk.emit_opcode(OpCode::Return);
+    module.add_entry_chunk(chunk);
+
+    let mut table = InstructionTable::new();
+    table.set_instruction(OpCode::Constant, (constant, 1));
+    let mut vm = VM::with(&module, &env, table, Context::default(), VMConfig::default()).unwrap();
+    vm.invoke_entry_chunk(0).unwrap();
+    assert_eq!(vm.run().unwrap(), Primitive::U8(7).into());
+}
+
+#[test]
+fn test_decoded_chunk_invalid_jump() {
+    let env = EnvironmentBuilder::default().build();
+    let mut module = Module::new();
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(module.add_constant(Primitive::U8(0)) as u16);
+    // Jump in the middle of the constant operands
+    chunk.emit_opcode(OpCode::Jump);
+    chunk.write_u32(1);
+    module.add_entry_chunk(chunk);
+
+    // The module is rejected when loaded
+    assert!(matches!(VM::new(&module, &env), Err(VMError::InvalidJumpAddress(1))));
+    assert!(matches!(DecodedModule::new(&module), Err(VMError::InvalidJumpAddress(1))));
+}
+
+#[test]
+fn test_decoded_module_shared() {
+    let env = EnvironmentBuilder::default().build();
+    let mut module = Module::new();
+    let mut chunk = Chunk::new();
+    chunk.emit_opcode(OpCode::Constant);
+    chunk.write_u16(module.add_constant(Primitive::U8(1)) as u16);
+    chunk.emit_opcode(OpCode::Return);
+    module.add_entry_chunk(chunk);
+
+    // Decoded once for every VM
+    let decoded = Arc::new(DecodedModule::new(&module).unwrap());
+    for _ in 0..2 {
+        let mut vm = VM::new_decoded(decoded.clone(), &env);
+        vm.invoke_entry_chunk(0).unwrap();
+        assert_eq!(vm.run().unwrap(), Primitive::U8(1).into());
+    }
+
+    assert_eq!(decoded.hash(), module.hash());
+}
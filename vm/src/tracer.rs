@@ -0,0 +1,125 @@
+use xelis_bytecode::OpCode;
+
+use crate::VMError;
+
+// State of the VM before an instruction is executed
+#[derive(Debug, Clone, Copy)]
+pub struct TraceStep<'b> {
+    // Chunk executed
+    pub chunk_id: u16,
+    // Offset of the opcode in the chunk
+    pub offset: usize,
+    // Raw opcode byte
+    pub opcode: u8,
+    // Bytes following the opcode read as its arguments
+    pub operands: &'b [u8],
+    // Count of frames in the call stack
+    pub call_depth: usize,
+    // Count of values in the stack
+    pub stack_depth: usize,
+    // Gas used before the instruction
+    pub gas: u64,
+    // Memory used before the instruction
+    pub memory: usize
+}
+
+impl TraceStep<'_> {
+    // Get the decoded opcode if valid
+    #[inline]
+    pub fn op_code(&self) -> Option<OpCode> {
+        OpCode::from_byte(self.opcode)
+    }
+}
+
+// State of the VM after an instruction was executed
+#[derive(Debug, Clone, Copy)]
+pub struct TraceResult<'b> {
+    // Count of values in the stack
+    pub stack_depth: usize,
+    // Gas used after the instruction
+    pub gas: u64,
+    // Memory used after the instruction
+    pub memory: usize,
+    // Error returned by the instruction
+    pub error: Option<&'b VMError>
+}
+
+// Observer of the instructions executed by the VM
+// Tracing is only done through `VM::run_with_tracer` and `VM::step_with_tracer`
+pub trait Tracer {
+    // If disabled, no step is built and no hook is called
+    // This allows `VM::run` to be executed without any tracing cost
+    const ENABLED: bool = true;
+
+    // Called before executing an instruction
+    fn before_instruction(&mut self, _step: &TraceStep) {}
+
+    // Called once the instruction is executed, even if it failed
+    fn after_instruction(&mut self, _step: &TraceStep, _result: &TraceResult) {}
+}
+
+// Tracer doing nothing, used by `VM::run`
+pub struct NoTracer;
+
+impl Tracer for NoTracer {
+    const ENABLED: bool = false;
+}
+
+// Owned record of an executed instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub chunk_id: u16,
+    pub offset: usize,
+    pub opcode: u8,
+    pub operands: Vec<u8>,
+    pub call_depth: usize,
+    pub stack_depth_before: usize,
+    pub stack_depth_after: usize,
+    pub gas_before: u64,
+    pub gas_after: u64,
+    // Error message if the instruction failed
+    pub error: Option<String>
+}
+
+// Tracer recording every instruction executed
+// Two logs can be compared to verify that two executions are identical
+#[derive(Debug, Default)]
+pub struct TraceLog {
+    entries: Vec<TraceEntry>
+}
+
+impl TraceLog {
+    // Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Get all the entries recorded
+    #[inline]
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    // Consume the log and get its entries
+    #[inline]
+    pub fn into_entries(self) -> Vec<TraceEntry> {
+        self.entries
+    }
+}
+
+impl Tracer for TraceLog {
+    fn after_instruction(&mut self, step: &TraceStep, result: &TraceResult) {
+        self.entries.push(TraceEntry {
+            chunk_id: step.chunk_id,
+            offset: step.offset,
+            opcode: step.opcode,
+            operands: step.operands.to_vec(),
+            call_depth: step.call_depth,
+            stack_depth_before: step.stack_depth,
+            stack_depth_after: result.stack_depth,
+            gas_before: step.gas,
+            gas_after: result.gas,
+            error: result.error.map(ToString::to_string)
+        });
+    }
+}
@@ -0,0 +1,227 @@
+use indexmap::IndexSet;
+use xelis_bytecode::{Module, OpCode};
+
+use crate::ChunkReader;
+use super::ValidatorError;
+
+// Static call graph of a module built from the InvokeChunk operands
+// Entries and hooks are the roots of the graph as they are invoked by the host
+#[derive(Debug)]
+pub struct CallGraph {
+    // Chunks invoked by each chunk
+    callees: Vec<IndexSet<usize>>,
+    // Strongly connected components that contain a recursion
+    recursive_cycles: Vec<Vec<usize>>,
+    // Maximum count of frames used when invoking each chunk
+    // None if the chunk can reach a recursive cycle
+    depths: Vec<Option<usize>>,
+    // Chunks that can't be reached from an entry or a hook
+    unreachable_chunks: Vec<usize>,
+    // Maximum count of frames used from an entry or a hook
+    max_depth: Option<usize>
+}
+
+impl CallGraph {
+    // Build the call graph of a module
+    pub fn new<'a>(module: &Module) -> Result<Self, ValidatorError<'a>> {
+        let chunks = module.chunks();
+        let mut callees = Vec::with_capacity(chunks.len());
+        for (chunk_id, chunk) in chunks.iter().enumerate() {
+            let mut set = IndexSet::new();
+            let mut reader = ChunkReader::new(chunk);
+            while let Some(instruction) = reader.next_u8() {
+                let offset = reader.index() - 1;
+                let op = OpCode::from_byte(instruction)
+                    .ok_or(ValidatorError::InvalidOpCode)?;
+
+                let count = op.arguments_bytes();
+                let invalid_args = |_| ValidatorError::InvalidOpCodeArguments(op, count);
+                if let OpCode::InvokeChunk = op {
+                    let id = reader.read_u16().map_err(invalid_args)? as usize;
+                    reader.advance(2).map_err(invalid_args)?;
+                    if id >= chunks.len() {
+                        return Err(ValidatorError::UnknownChunk(chunk_id, offset));
+                    }
+
+                    set.insert(id);
+                } else {
+                    reader.advance(count).map_err(invalid_args)?;
+                }
+            }
+
+            callees.push(set);
+        }
+
+        let mut graph = Self {
+            callees,
+            recursive_cycles: Vec::new(),
+            depths: vec![None; chunks.len()],
+            unreachable_chunks: Vec::new(),
+            max_depth: None
+        };
+
+        graph.compute_depths();
+
+        // The module may not have been verified yet
+        for id in module.chunks_entry_ids() {
+            if *id >= chunks.len() {
+                return Err(ValidatorError::InvalidEntryId(*id));
+            }
+        }
+
+        for (hook_id, id) in module.hook_chunk_ids() {
+            if *id >= chunks.len() {
+                return Err(ValidatorError::InvalidHookId(*hook_id, *id));
+            }
+        }
+
+        let roots = module.chunks_entry_ids()
+            .iter()
+            .chain(module.hook_chunk_ids().values())
+            .copied()
+            .collect::<Vec<_>>();
+
+        graph.compute_reachability(&roots);
+
+        graph.max_depth = roots.iter()
+            .try_fold(0, |max, id| graph.depths[*id].map(|depth| depth.max(max)));
+
+        Ok(graph)
+    }
+
+    // Find the strongly connected components using an iterative Tarjan algorithm
+    // Components are found in reverse topological order, so every chunk invoked
+    // by a component has its depth computed before the component itself
+    fn compute_depths(&mut self) {
+        let len = self.callees.len();
+        let mut next_index = 0;
+        let mut indices: Vec<Option<usize>> = vec![None; len];
+        let mut lowlinks = vec![0; len];
+        let mut on_stack = vec![false; len];
+        let mut stack = Vec::new();
+
+        for root in 0..len {
+            if indices[root].is_some() {
+                continue;
+            }
+
+            // Chunk id with the position of its next callee to visit
+            let mut work = vec![(root, 0)];
+            indices[root] = Some(next_index);
+            lowlinks[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((id, position)) = work.last_mut() {
+                let id = *id;
+                if let Some(callee) = self.callees[id].get_index(*position).copied() {
+                    *position += 1;
+                    match indices[callee] {
+                        None => {
+                            indices[callee] = Some(next_index);
+                            lowlinks[callee] = next_index;
+                            next_index += 1;
+                            stack.push(callee);
+                            on_stack[callee] = true;
+                            work.push((callee, 0));
+                        },
+                        Some(index) if on_stack[callee] => {
+                            lowlinks[id] = lowlinks[id].min(index);
+                        },
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                work.pop();
+                if let Some((parent, _)) = work.last() {
+                    lowlinks[*parent] = lowlinks[*parent].min(lowlinks[id]);
+                }
+
+                if Some(lowlinks[id]) != indices[id] {
+                    continue;
+                }
+
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == id {
+                        break;
+                    }
+                }
+
+                let recursive = component.len() > 1 || self.callees[id].contains(&id);
+                if recursive {
+                    component.sort_unstable();
+                    self.recursive_cycles.push(component);
+                    continue;
+                }
+
+                // One frame for the chunk itself and the deepest of its callees
+                self.depths[id] = self.callees[id]
+                    .iter()
+                    .try_fold(0, |max, callee| self.depths[*callee].map(|depth| depth.max(max)))
+                    .map(|depth| depth + 1);
+            }
+        }
+    }
+
+    // Mark all the chunks that can't be reached from the roots
+    fn compute_reachability(&mut self, roots: &[usize]) {
+        let mut reachable = vec![false; self.callees.len()];
+        let mut pending = roots.to_vec();
+        while let Some(id) = pending.pop() {
+            if reachable[id] {
+                continue;
+            }
+
+            reachable[id] = true;
+            pending.extend(self.callees[id].iter().copied());
+        }
+
+        self.unreachable_chunks = reachable.into_iter()
+            .enumerate()
+            .filter_map(|(id, reachable)| (!reachable).then_some(id))
+            .collect();
+    }
+
+    // Chunks invoked by the requested chunk
+    #[inline]
+    pub fn callees(&self, chunk_id: usize) -> Option<&IndexSet<usize>> {
+        self.callees.get(chunk_id)
+    }
+
+    // All the recursive cycles found, each one sorted by chunk id
+    #[inline]
+    pub fn recursive_cycles(&self) -> &[Vec<usize>] {
+        &self.recursive_cycles
+    }
+
+    // Check if the module contains any recursion
+    #[inline]
+    pub fn has_recursion(&self) -> bool {
+        !self.recursive_cycles.is_empty()
+    }
+
+    // Maximum count of frames used when invoking the requested chunk
+    // None if the chunk can recurse
+    #[inline]
+    pub fn chunk_depth(&self, chunk_id: usize) -> Option<usize> {
+        self.depths.get(chunk_id).copied().flatten()
+    }
+
+    // Maximum count of frames used from an entry or a hook
+    // None if a recursion is reachable
+    #[inline]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    // Chunks that can't be reached from an entry or a hook
+    #[inline]
+    pub fn unreachable_chunks(&self) -> &[usize] {
+        &self.unreachable_chunks
+    }
+}
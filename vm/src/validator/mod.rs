@@ -1,11 +1,25 @@
+mod stack;
+mod call_graph;
+
 use std::collections::HashSet;
 
 use thiserror::Error;
 use xelis_environment::Environment;
-use xelis_types::{EnumType, EnumVariant, Primitive, StructType, ValueCell, ValueError};
+use xelis_types::{
+    serializer::{DEFAULT_MAX_VALUE_DEPTH, DEFAULT_MAX_VALUE_MEMORY},
+    EnumType,
+    EnumVariant,
+    Primitive,
+    StructType,
+    ValueCell,
+    ValueError
+};
 use xelis_bytecode::{Module, OpCode};
 
-use crate::ChunkReader;
+use crate::{ChunkReader, VMConfig};
+
+pub use stack::ChunkSummary;
+pub use call_graph::CallGraph;
 
 #[derive(Debug, Error)]
 pub enum ValidatorError<'a> {
@@ -70,18 +84,97 @@ pub enum ValidatorError<'a> {
     UnknownEnum,
     #[error("string is too big")]
     StringTooBig,
+
+    #[error("stack underflow in chunk {0} at offset {1}")]
+    StackUnderflow(usize, usize),
+    #[error("stack overflow in chunk {0} at offset {1}")]
+    StackOverflow(usize, usize),
+    #[error("invalid jump target in chunk {0} at offset {1}")]
+    InvalidJumpTarget(usize, usize),
+    #[error("invalid register in chunk {0} at offset {1}")]
+    InvalidRegister(usize, usize),
+    #[error("stack height mismatch in chunk {0} at offset {1}")]
+    StackHeightMismatch(usize, usize),
+    #[error("chunk {0} returns with different stack heights")]
+    ReturnHeightMismatch(usize),
+    #[error("chunk {0} is invoked with different arguments count")]
+    InconsistentChunkArity(usize),
+    #[error("chunk {0} has a signature but is not an entry or a hook")]
+    InvalidChunkSignature(usize),
+    #[error("unknown chunk invoked in chunk {0} at offset {1}")]
+    UnknownChunk(usize, usize),
+    #[error("unknown syscall in chunk {0} at offset {1}")]
+    UnknownSysCall(usize, usize),
+    #[error("invalid constant id in chunk {0} at offset {1}")]
+    InvalidConstantId(usize, usize),
+    #[error("invalid primitive type in chunk {0} at offset {1}")]
+    InvalidPrimitiveType(usize, usize),
+    #[error("invalid fused operator in chunk {0} at offset {1}")]
+    InvalidFusedOperator(usize, usize),
+    #[error("entry chunk invoked in chunk {0} at offset {1}")]
+    EntryChunkInvoked(usize, usize),
+    #[error("invalid syscall arguments count in chunk {0} at offset {1}: expected {2}, got {3}")]
+    InvalidSysCallArguments(usize, usize, usize, usize),
+    #[error("invalid syscall instance in chunk {0} at offset {1}: expected {2}, got {3}")]
+    InvalidSysCallInstance(usize, usize, bool, bool),
+    #[error("recursive chunks {0:?} are not allowed")]
+    RecursiveChunks(Vec<usize>),
+    #[error("call depth {0} exceeds the call stack size")]
+    CallDepthTooHigh(usize),
 }
 
 pub struct ModuleValidator<'a> {
     module: &'a Module,
     environment: &'a Environment,
     constant_max_depth: usize,
-    constant_max_memory: usize
+    constant_max_memory: usize,
+    // Allow chunks to invoke themselves, directly or through other chunks
+    allow_recursion: bool,
+    // Limits of the VM that will execute the module
+    config: VMConfig
 }
 
 impl<'a> ModuleValidator<'a> {
     pub fn new(module: &'a Module, environment: &'a Environment) -> Self {
-        Self { module, environment, constant_max_depth: 16, constant_max_memory: 1024 * 1024 }
+        Self {
+            module,
+            environment,
+            constant_max_depth: DEFAULT_MAX_VALUE_DEPTH,
+            constant_max_memory: DEFAULT_MAX_VALUE_MEMORY,
+            allow_recursion: true,
+            config: VMConfig::default()
+        }
+    }
+
+    // Verify the module against the limits of the given VM config
+    pub fn set_config(&mut self, config: VMConfig) {
+        self.config = config;
+    }
+
+    // Allow or reject recursive chunks
+    // When rejected, the static call depth must also fit in the call stack
+    pub fn set_allow_recursion(&mut self, value: bool) {
+        self.allow_recursion = value;
+    }
+
+    // Verify the call graph against the recursion policy
+    fn verify_call_graph(&self) -> Result<(), ValidatorError<'a>> {
+        if self.allow_recursion {
+            return Ok(());
+        }
+
+        let graph = CallGraph::new(self.module)?;
+        if let Some(cycle) = graph.recursive_cycles().first() {
+            return Err(ValidatorError::RecursiveChunks(cycle.clone()));
+        }
+
+        if let Some(depth) = graph.max_depth() {
+            if depth > self.config.max_call_stack_size {
+                return Err(ValidatorError::CallDepthTooHigh(depth));
+            }
+        }
+
+        Ok(())
     }
 
     // Verify a constant and return the memory usage
@@ -208,7 +301,7 @@ impl<'a> ModuleValidator<'a> {
             // - chunk id not an entry
             if *chunk_id >= len
                 || *hook_id >= self.environment.hooks()
-                ||  !self.module.chunks_entry_ids().contains(chunk_id) {
+                || self.module.chunks_entry_ids().contains(chunk_id) {
                 return Err(ValidatorError::InvalidHookId(*hook_id, *chunk_id));
             }
 
@@ -220,6 +313,59 @@ impl<'a> ModuleValidator<'a> {
         Ok(())
     }
 
+    // Verify every call site of the module
+    // - InvokeChunk must target an existing chunk that is not an entry
+    // - SysCall must target an existing native function
+    //   with the same arguments count and instance requirement
+    fn verify_call_sites(&self) -> Result<(), ValidatorError<'a>> {
+        let functions = self.environment.get_functions();
+        for (chunk_id, chunk) in self.module.chunks().iter().enumerate() {
+            let mut reader = ChunkReader::new(chunk);
+            while let Some(instruction) = reader.next_u8() {
+                let offset = reader.index() - 1;
+                let op = OpCode::from_byte(instruction)
+                    .ok_or(ValidatorError::InvalidOpCode)?;
+
+                let count = op.arguments_bytes();
+                let invalid_args = |_| ValidatorError::InvalidOpCodeArguments(op, count);
+                match op {
+                    OpCode::InvokeChunk => {
+                        let id = reader.read_u16().map_err(invalid_args)? as usize;
+                        reader.advance(2).map_err(invalid_args)?;
+
+                        if id >= self.module.chunks().len() {
+                            return Err(ValidatorError::UnknownChunk(chunk_id, offset));
+                        }
+
+                        if self.module.is_entry_chunk(id) {
+                            return Err(ValidatorError::EntryChunkInvoked(chunk_id, offset));
+                        }
+                    },
+                    OpCode::SysCall => {
+                        let id = reader.read_u16().map_err(invalid_args)?;
+                        let on_value = reader.read_bool().map_err(invalid_args)?;
+                        let args = reader.read_u8().map_err(invalid_args)? as usize;
+
+                        let function = functions.get(id as usize)
+                            .ok_or(ValidatorError::UnknownSysCall(chunk_id, offset))?;
+
+                        let expected = function.get_parameters().len();
+                        if args != expected {
+                            return Err(ValidatorError::InvalidSysCallArguments(chunk_id, offset, expected, args));
+                        }
+
+                        if on_value != function.require_instance() {
+                            return Err(ValidatorError::InvalidSysCallInstance(chunk_id, offset, function.require_instance(), on_value));
+                        }
+                    },
+                    _ => reader.advance(count).map_err(invalid_args)?
+                };
+            }
+        }
+
+        Ok(())
+    }
+
     // Verify the module integrity and return an error if it's invalid
     pub fn verify(&self) -> Result<(), ValidatorError<'a>> {
         let max = u16::MAX as usize;
@@ -235,6 +381,9 @@ impl<'a> ModuleValidator<'a> {
 
         self.verify_constants(self.module.constants().iter())?;
         self.verify_chunks()?;
+        self.verify_call_sites()?;
+        self.verify_call_graph()?;
+        self.verify_stack_effects()?;
 
         Ok(())
     }
@@ -0,0 +1,636 @@
+use xelis_bytecode::OpCode;
+use xelis_types::Type;
+
+use crate::ChunkReader;
+use super::{ModuleValidator, ValidatorError};
+
+// Stack effect summary of a chunk computed by the validator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSummary {
+    // Count of values expected on the stack when the chunk is invoked
+    parameters: usize,
+    // Stack height (including the parameters) when the chunk returns
+    // None if the chunk never returns
+    returns: Option<usize>,
+    // Maximum stack height reached by the chunk itself
+    max_height: usize,
+    // Maximum stack height reached by the chunk and the chunks it invokes
+    // None if the chunk can reach a recursion
+    max_call_height: Option<usize>
+}
+
+impl ChunkSummary {
+    // Count of values expected on the stack when the chunk is invoked
+    #[inline]
+    pub fn parameters(&self) -> usize {
+        self.parameters
+    }
+
+    // Stack height when the chunk returns
+    #[inline]
+    pub fn returns(&self) -> Option<usize> {
+        self.returns
+    }
+
+    // Maximum stack height reached by the chunk
+    #[inline]
+    pub fn max_height(&self) -> usize {
+        self.max_height
+    }
+
+    // Maximum stack height reached by the chunk and the chunks it invokes
+    #[inline]
+    pub fn max_call_height(&self) -> Option<usize> {
+        self.max_call_height
+    }
+}
+
+// Abstract state at an instruction
+#[derive(Debug, Clone, Copy)]
+struct State {
+    // Stack height relative to the chunk start
+    height: i64,
+    // Minimum count of registers available
+    registers: usize
+}
+
+// Result of the analysis of one chunk
+struct Analysis {
+    min_height: i64,
+    max_height: i64,
+    max_height_at: usize,
+    returns: Option<i64>,
+    // (offset, chunk id, height below the arguments) of each invocation
+    calls: Vec<(usize, usize, i64)>,
+    // (offset, index, height) of each CopyN
+    copies: Vec<(usize, usize, i64)>
+}
+
+// Context used while analyzing one chunk
+struct Analyzer<'v, 'a> {
+    validator: &'v ModuleValidator<'a>,
+    chunk_id: usize,
+    // Parameters count of the chunk if known
+    arity: Option<usize>,
+    analysis: Analysis
+}
+
+impl<'v, 'a> Analyzer<'v, 'a> {
+    // Pop N values from the abstract stack
+    fn pop(&mut self, state: &mut State, n: usize, offset: usize) -> Result<(), ValidatorError<'a>> {
+        state.height -= n as i64;
+        // When the arity is unknown, the chunk parameters are inferred from the lowest height
+        if self.arity.is_some() && state.height < 0 {
+            return Err(ValidatorError::StackUnderflow(self.chunk_id, offset));
+        }
+
+        self.analysis.min_height = self.analysis.min_height.min(state.height);
+        Ok(())
+    }
+
+    // Push N values on the abstract stack
+    fn push(&mut self, state: &mut State, n: usize, offset: usize) {
+        state.height += n as i64;
+        if state.height > self.analysis.max_height {
+            self.analysis.max_height = state.height;
+            self.analysis.max_height_at = offset;
+        }
+    }
+
+    // Verify that N values are available without consuming them
+    fn need(&mut self, state: &mut State, n: usize, offset: usize) -> Result<(), ValidatorError<'a>> {
+        self.pop(state, n, offset)?;
+        state.height += n as i64;
+        Ok(())
+    }
+
+    // Verify a register is available
+    fn check_register(&self, state: &State, id: u16, offset: usize) -> Result<(), ValidatorError<'a>> {
+        if id as usize >= state.registers {
+            return Err(ValidatorError::InvalidRegister(self.chunk_id, offset));
+        }
+        Ok(())
+    }
+
+    // Verify a constant exists in the module
+    fn check_constant(&self, id: u16, offset: usize) -> Result<(), ValidatorError<'a>> {
+        if id as usize >= self.validator.module.constants().len() {
+            return Err(ValidatorError::InvalidConstantId(self.chunk_id, offset));
+        }
+        Ok(())
+    }
+
+    // Verify the operator of a superinstruction
+    fn check_operator(&self, byte: u8, offset: usize, filter: fn(OpCode) -> bool) -> Result<(), ValidatorError<'a>> {
+        match OpCode::from_byte(byte) {
+            Some(op) if op.is_fusable_operator() && filter(op) => Ok(()),
+            _ => Err(ValidatorError::InvalidFusedOperator(self.chunk_id, offset))
+        }
+    }
+
+    // Register the height of a return point
+    fn add_return(&mut self, height: i64) -> Result<(), ValidatorError<'a>> {
+        match self.analysis.returns {
+            Some(h) if h != height => Err(ValidatorError::ReturnHeightMismatch(self.chunk_id)),
+            _ => {
+                self.analysis.returns = Some(height);
+                Ok(())
+            }
+        }
+    }
+
+    // Run the abstract interpretation over the chunk
+    // Chunks invoked that are not known to return stop the current path
+    fn run(mut self, returns: &[Option<usize>]) -> Result<Analysis, ValidatorError<'a>> {
+        let module = self.validator.module;
+        let chunk = module.get_chunk_at(self.chunk_id)
+            .ok_or(ValidatorError::UnknownChunk(self.chunk_id, 0))?;
+        let instructions = chunk.get_instructions();
+        let len = instructions.len();
+
+        // Mark every instruction start, the end of the chunk is also a valid target
+        let mut boundaries = vec![false; len + 1];
+        let mut offset = 0;
+        while offset < len {
+            boundaries[offset] = true;
+            let op = OpCode::from_byte(instructions[offset])
+                .ok_or(ValidatorError::InvalidOpCode)?;
+            offset += 1 + op.arguments_bytes();
+        }
+        boundaries[len] = true;
+
+        let start = State {
+            height: self.arity.unwrap_or(0) as i64,
+            registers: 0
+        };
+        self.analysis.max_height = start.height;
+
+        let mut states: Vec<Option<State>> = vec![None; len + 1];
+        states[0] = Some(start);
+        let mut pending = vec![0];
+
+        while let Some(offset) = pending.pop() {
+            let mut state = states[offset].expect("state must be set");
+            // Falling off the end of a chunk is an implicit return
+            if offset == len {
+                self.add_return(state.height)?;
+                continue;
+            }
+
+            let op = OpCode::from_byte(instructions[offset])
+                .ok_or(ValidatorError::InvalidOpCode)?;
+            let next = offset + 1 + op.arguments_bytes();
+
+            let mut reader = ChunkReader::new(chunk);
+            reader.set_index(offset + 1)
+                .map_err(|_| ValidatorError::InvalidOpCodeArguments(op, op.arguments_bytes()))?;
+
+            let invalid_args = |_| ValidatorError::InvalidOpCodeArguments(op, op.arguments_bytes());
+
+            // Successors of the instruction with their state
+            let mut successors = Vec::with_capacity(2);
+            match op {
+                OpCode::Constant => {
+                    let id = reader.read_u16().map_err(invalid_args)?;
+                    if id as usize >= module.constants().len() {
+                        return Err(ValidatorError::InvalidConstantId(self.chunk_id, offset));
+                    }
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::MemoryLoad => {
+                    let id = reader.read_u16().map_err(invalid_args)? as usize;
+                    if id >= state.registers {
+                        return Err(ValidatorError::InvalidRegister(self.chunk_id, offset));
+                    }
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::MemorySet => {
+                    let id = reader.read_u16().map_err(invalid_args)? as usize;
+                    self.pop(&mut state, 1, offset)?;
+                    if id >= self.validator.config.max_registers_size || id > state.registers {
+                        return Err(ValidatorError::InvalidRegister(self.chunk_id, offset));
+                    }
+
+                    if id == state.registers {
+                        state.registers += 1;
+                    }
+                },
+                OpCode::MemoryPop => {
+                    if state.registers == 0 {
+                        return Err(ValidatorError::InvalidRegister(self.chunk_id, offset));
+                    }
+                    state.registers -= 1;
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::MemoryLen => self.push(&mut state, 1, offset),
+                OpCode::MemoryToOwned => {
+                    let id = reader.read_u16().map_err(invalid_args)? as usize;
+                    if id >= state.registers {
+                        return Err(ValidatorError::InvalidRegister(self.chunk_id, offset));
+                    }
+                },
+                OpCode::SubLoad
+                | OpCode::ToOwned
+                | OpCode::IterableLength
+                | OpCode::Neg
+                | OpCode::Inc
+                | OpCode::Dec => self.need(&mut state, 1, offset)?,
+                OpCode::Cast => {
+                    let ty = reader.read_u8().map_err(invalid_args)?;
+                    if Type::primitive_type_from_byte(ty).is_none() {
+                        return Err(ValidatorError::InvalidPrimitiveType(self.chunk_id, offset));
+                    }
+                    self.need(&mut state, 1, offset)?;
+                },
+                OpCode::Pop | OpCode::IteratorBegin => self.pop(&mut state, 1, offset)?,
+                OpCode::PopN => {
+                    let n = reader.read_u8().map_err(invalid_args)?;
+                    self.pop(&mut state, n as usize, offset)?;
+                },
+                OpCode::Copy => {
+                    self.need(&mut state, 1, offset)?;
+                    self.push(&mut state, 1, offset);
+                },
+                // The index is absolute in the VM stack
+                // It is verified once the parameters count is known
+                OpCode::CopyN => {
+                    let index = reader.read_u8().map_err(invalid_args)?;
+                    self.analysis.copies.push((offset, index as usize, state.height));
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::Swap => {
+                    let n = reader.read_u8().map_err(invalid_args)?;
+                    self.need(&mut state, n as usize + 1, offset)?;
+                },
+                OpCode::Swap2 => {
+                    let a = reader.read_u8().map_err(invalid_args)?;
+                    let b = reader.read_u8().map_err(invalid_args)?;
+                    self.need(&mut state, a.max(b) as usize + 1, offset)?;
+                },
+                OpCode::Jump => {
+                    let addr = reader.read_u32().map_err(invalid_args)?;
+                    successors.push((addr as usize, state));
+                },
+                OpCode::JumpIfFalse => {
+                    let addr = reader.read_u32().map_err(invalid_args)?;
+                    self.pop(&mut state, 1, offset)?;
+                    successors.push((addr as usize, state));
+                    successors.push((next, state));
+                },
+                OpCode::IteratorNext => {
+                    let addr = reader.read_u32().map_err(invalid_args)?;
+                    // Iterator is empty, nothing is pushed
+                    successors.push((addr as usize, state));
+                    self.push(&mut state, 1, offset);
+                    successors.push((next, state));
+                },
+                OpCode::IteratorEnd => {},
+                OpCode::Return => {
+                    self.add_return(state.height)?;
+                },
+                OpCode::InvokeChunk => {
+                    let id = reader.read_u16().map_err(invalid_args)? as usize;
+                    let on_value = reader.read_bool().map_err(invalid_args)?;
+                    let args = reader.read_u8().map_err(invalid_args)? as usize + on_value as usize;
+                    if id >= module.chunks().len() {
+                        return Err(ValidatorError::UnknownChunk(self.chunk_id, offset));
+                    }
+
+                    self.pop(&mut state, args, offset)?;
+                    self.analysis.calls.push((offset, id, state.height));
+                    // The invoked chunk starts with its parameters on the stack
+                    // and returns at a known height, otherwise the path ends here
+                    match returns[id] {
+                        Some(height) => self.push(&mut state, height, offset),
+                        None => continue
+                    }
+                },
+                OpCode::SysCall => {
+                    let id = reader.read_u16().map_err(invalid_args)?;
+                    let on_value = reader.read_bool().map_err(invalid_args)?;
+                    let args = reader.read_u8().map_err(invalid_args)? as usize + on_value as usize;
+                    let function = self.validator.environment.get_functions()
+                        .get(id as usize)
+                        .ok_or(ValidatorError::UnknownSysCall(self.chunk_id, offset))?;
+
+                    self.pop(&mut state, args, offset)?;
+                    if function.return_type().is_some() {
+                        self.push(&mut state, 1, offset);
+                    }
+                },
+                OpCode::NewObject => {
+                    let n = reader.read_u8().map_err(invalid_args)?;
+                    self.pop(&mut state, n as usize, offset)?;
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::NewMap => {
+                    let n = reader.read_u8().map_err(invalid_args)?;
+                    self.pop(&mut state, n as usize * 2, offset)?;
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::NewRange
+                | OpCode::ArrayCall
+                | OpCode::Add
+                | OpCode::Sub
+                | OpCode::Mul
+                | OpCode::Div
+                | OpCode::Mod
+                | OpCode::Pow
+                | OpCode::And
+                | OpCode::Or
+                | OpCode::BitwiseAnd
+                | OpCode::BitwiseOr
+                | OpCode::BitwiseXor
+                | OpCode::BitwiseShl
+                | OpCode::BitwiseShr
+                | OpCode::Eq
+                | OpCode::Gt
+                | OpCode::Lt
+                | OpCode::Gte
+                | OpCode::Lte => {
+                    self.pop(&mut state, 2, offset)?;
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::Assign
+                | OpCode::AssignAdd
+                | OpCode::AssignSub
+                | OpCode::AssignMul
+                | OpCode::AssignDiv
+                | OpCode::AssignMod
+                | OpCode::AssignPow
+                | OpCode::AssignBitwiseAnd
+                | OpCode::AssignBitwiseOr
+                | OpCode::AssignBitwiseXor
+                | OpCode::AssignBitwiseShl
+                | OpCode::AssignBitwiseShr => self.pop(&mut state, 2, offset)?,
+                OpCode::MemoryOperator => {
+                    let left = reader.read_u16().map_err(invalid_args)?;
+                    let right = reader.read_u16().map_err(invalid_args)?;
+                    let operator = reader.read_u8().map_err(invalid_args)?;
+                    self.check_register(&state, left, offset)?;
+                    self.check_register(&state, right, offset)?;
+                    self.check_operator(operator, offset, |_| true)?;
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::MemoryConstantOperator => {
+                    let register = reader.read_u16().map_err(invalid_args)?;
+                    let constant = reader.read_u16().map_err(invalid_args)?;
+                    let operator = reader.read_u8().map_err(invalid_args)?;
+                    self.check_register(&state, register, offset)?;
+                    self.check_constant(constant, offset)?;
+                    self.check_operator(operator, offset, |_| true)?;
+                    self.push(&mut state, 1, offset);
+                },
+                OpCode::MemoryConstantJumpIfFalse => {
+                    let register = reader.read_u16().map_err(invalid_args)?;
+                    let constant = reader.read_u16().map_err(invalid_args)?;
+                    let operator = reader.read_u8().map_err(invalid_args)?;
+                    let addr = reader.read_u32().map_err(invalid_args)?;
+                    self.check_register(&state, register, offset)?;
+                    self.check_constant(constant, offset)?;
+                    self.check_operator(operator, offset, |op| op.is_comparison())?;
+                    successors.push((addr as usize, state));
+                    successors.push((next, state));
+                },
+                OpCode::MemoryAssignConstant => {
+                    let register = reader.read_u16().map_err(invalid_args)?;
+                    let constant = reader.read_u16().map_err(invalid_args)?;
+                    let operator = reader.read_u8().map_err(invalid_args)?;
+                    self.check_register(&state, register, offset)?;
+                    self.check_constant(constant, offset)?;
+                    self.check_operator(operator, offset, |op| op.as_assign_operator().is_some())?;
+                },
+                OpCode::MemoryIncrement => {
+                    let register = reader.read_u16().map_err(invalid_args)?;
+                    self.check_register(&state, register, offset)?;
+                },
+            };
+
+            // Instructions that don't branch continue to the next one
+            if !matches!(op, OpCode::Jump | OpCode::JumpIfFalse | OpCode::MemoryConstantJumpIfFalse | OpCode::IteratorNext | OpCode::Return) {
+                successors.push((next, state));
+            }
+
+            for (target, state) in successors {
+                if target > len || !boundaries[target] {
+                    return Err(ValidatorError::InvalidJumpTarget(self.chunk_id, offset));
+                }
+
+                match &mut states[target] {
+                    Some(previous) => {
+                        // Stack height must be the same on every path
+                        if previous.height != state.height {
+                            return Err(ValidatorError::StackHeightMismatch(self.chunk_id, target));
+                        }
+
+                        // Only the registers available on every path can be used
+                        if state.registers < previous.registers {
+                            previous.registers = state.registers;
+                            pending.push(target);
+                        }
+                    },
+                    slot @ None => {
+                        *slot = Some(state);
+                        pending.push(target);
+                    }
+                }
+            }
+        }
+
+        Ok(self.analysis)
+    }
+}
+
+impl<'a> ModuleValidator<'a> {
+    // Analyze a chunk with the currently known return heights
+    fn analyze_chunk(&self, chunk_id: usize, arity: Option<usize>, returns: &[Option<usize>]) -> Result<Analysis, ValidatorError<'a>> {
+        let analyzer = Analyzer {
+            validator: self,
+            chunk_id,
+            arity,
+            analysis: Analysis {
+                min_height: 0,
+                max_height: 0,
+                max_height_at: 0,
+                returns: None,
+                calls: Vec::new(),
+                copies: Vec::new()
+            }
+        };
+
+        analyzer.run(returns)
+    }
+
+    // Compute the parameters count of each chunk invoked by another one
+    // All the call sites of a chunk must agree on it
+    fn chunks_arity(&self) -> Result<Vec<Option<usize>>, ValidatorError<'a>> {
+        let chunks = self.module.chunks();
+        let mut arities = vec![None; chunks.len()];
+
+        // Entries and hooks may declare their parameters in the module
+        for (id, parameters) in self.module.chunks_signatures() {
+            let invoked_by_host = self.module.is_entry_chunk(*id)
+                || self.module.hook_chunk_ids().values().any(|chunk_id| chunk_id == id);
+            if !invoked_by_host || *id >= arities.len() {
+                return Err(ValidatorError::InvalidChunkSignature(*id));
+            }
+
+            arities[*id] = Some(parameters.len());
+        }
+
+        for (chunk_id, chunk) in chunks.iter().enumerate() {
+            let mut reader = ChunkReader::new(chunk);
+            while let Some(byte) = reader.next_u8() {
+                let op = OpCode::from_byte(byte)
+                    .ok_or(ValidatorError::InvalidOpCode)?;
+
+                if let OpCode::InvokeChunk = op {
+                    let offset = reader.index() - 1;
+                    let invalid_args = |_| ValidatorError::InvalidOpCodeArguments(op, op.arguments_bytes());
+                    let id = reader.read_u16().map_err(invalid_args)? as usize;
+                    let on_value = reader.read_bool().map_err(invalid_args)?;
+                    let args = reader.read_u8().map_err(invalid_args)? as usize + on_value as usize;
+
+                    let arity = arities.get_mut(id)
+                        .ok_or(ValidatorError::UnknownChunk(chunk_id, offset))?;
+
+                    match arity {
+                        Some(expected) if *expected != args => return Err(ValidatorError::InconsistentChunkArity(id)),
+                        _ => *arity = Some(args)
+                    };
+                } else {
+                    reader.advance(op.arguments_bytes())
+                        .map_err(|_| ValidatorError::InvalidOpCodeArguments(op, op.arguments_bytes()))?;
+                }
+            }
+        }
+
+        Ok(arities)
+    }
+
+    // Verify the stack effects of every chunk using an abstract interpretation
+    // - the stack never underflows or exceeds the VM stack size
+    // - the stack size is not exceeded by the chain of chunks invoked from an entry or a hook
+    // - CopyN only reads values pushed by the chunk or its parameters
+    // - jumps land on instruction boundaries
+    // - registers are set before being used
+    // - the stack height is the same on every path reaching an instruction
+    // - every return point of a chunk has the same stack height
+    // Chunks only invoked by the host (entries and hooks) use the parameters count
+    // of their signature, or have it inferred from the lowest stack height reached
+    // A chain reaching a recursion can't be bounded, the VM stack size limit guards it
+    pub fn verify_stack_effects(&self) -> Result<Vec<ChunkSummary>, ValidatorError<'a>> {
+        let arities = self.chunks_arity()?;
+        let len = arities.len();
+
+        // Resolve the return heights until no new chunk is known to return
+        // Recursive chunks are resolved through their non recursive paths
+        let mut returns = vec![None; len];
+        loop {
+            let mut changed = false;
+            for id in 0..len {
+                if returns[id].is_some() {
+                    continue;
+                }
+
+                let analysis = self.analyze_chunk(id, arities[id], &returns)?;
+                if let Some(height) = analysis.returns {
+                    returns[id] = Some(height.max(0) as usize);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        // Final pass with all the return heights resolved
+        let mut summaries = Vec::with_capacity(len);
+        // (offset, chunk id, height below the arguments) of each invocation
+        let mut calls = Vec::with_capacity(len);
+        let mut max_heights_at = Vec::with_capacity(len);
+        for (id, arity) in arities.iter().copied().enumerate() {
+            let analysis = self.analyze_chunk(id, arity, &returns)?;
+            let shift = match arity {
+                Some(_) => 0,
+                None => (-analysis.min_height).max(0)
+            };
+
+            let max_height = (analysis.max_height + shift) as usize;
+            if max_height > self.config.max_stack_size {
+                return Err(ValidatorError::StackOverflow(id, analysis.max_height_at));
+            }
+
+            // A chunk may be invoked by a native function on an empty stack
+            // so only its own values can be copied
+            for (offset, index, height) in analysis.copies {
+                if index as i64 >= height + shift {
+                    return Err(ValidatorError::StackUnderflow(id, offset));
+                }
+            }
+
+            calls.push(analysis.calls.into_iter()
+                .map(|(offset, callee, height)| (offset, callee, (height + shift) as usize))
+                .collect::<Vec<_>>()
+            );
+            max_heights_at.push(analysis.max_height_at);
+
+            summaries.push(ChunkSummary {
+                parameters: arity.unwrap_or(shift as usize),
+                returns: analysis.returns.map(|h| (h + shift) as usize),
+                max_height,
+                max_call_height: None
+            });
+        }
+
+        // Resolve the height reached through the chunks invoked
+        // until no new chunk is resolved, the chunks left reach a recursion
+        // (height, offset in the chunk where it's reached)
+        let mut call_heights: Vec<Option<(usize, usize)>> = vec![None; len];
+        loop {
+            let mut changed = false;
+            for id in 0..len {
+                if call_heights[id].is_some() {
+                    continue;
+                }
+
+                let mut deepest = Some((summaries[id].max_height, max_heights_at[id]));
+                for (offset, callee, height) in calls[id].iter().copied() {
+                    deepest = match (deepest, call_heights[callee]) {
+                        (Some(current), Some((callee_height, _))) => Some(current.max((height + callee_height, offset))),
+                        _ => None
+                    };
+                }
+
+                if let Some(deepest) = deepest {
+                    call_heights[id] = Some(deepest);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        for (id, summary) in summaries.iter_mut().enumerate() {
+            summary.max_call_height = call_heights[id].map(|(height, _)| height);
+        }
+
+        // Entries and hooks are invoked by the host on an empty stack
+        let roots = self.module.chunks_entry_ids()
+            .iter()
+            .chain(self.module.hook_chunk_ids().values());
+
+        for id in roots {
+            if let Some((height, offset)) = call_heights.get(*id).copied().flatten() {
+                if height > self.config.max_stack_size {
+                    return Err(ValidatorError::StackOverflow(*id, offset));
+                }
+            }
+        }
+
+        Ok(summaries)
+    }
+}